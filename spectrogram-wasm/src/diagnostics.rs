@@ -0,0 +1,541 @@
+// ============================================================
+// 錄音質量診斷
+// 逐通道統計峰值 / RMS / 直流偏移、削波樣本段與無信號通道，
+// 檢查採樣率是否為常見錄音機採樣率，並比較噪聲底的高頻部分
+// (與參考噪聲底或低頻部分相比) 以提示麥克風高頻靈敏度下降。
+// ============================================================
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::find_global_max;
+use crate::flac::{FlacAudio, FlacError};
+use crate::wav::{SampleFormat, WavAudio, WavError};
+use crate::welch::{welch, Average, Detrend};
+
+/// 默認最短削波段長度 (連續樣本數)；單個滿量程樣本不算削波
+pub const DEFAULT_MIN_CLIP_RUN: usize = 3;
+/// 浮點文件的削波閾值
+const FLOAT_CLIP_THRESHOLD: f32 = 0.999;
+/// 去直流後 RMS 低於此值 (dBFS) 的通道視為無信號
+const DEAD_CHANNEL_DB: f32 = -90.0;
+/// 直流偏移超過此值 (滿量程比例) 時給出警告
+const DC_OFFSET_WARNING: f32 = 0.01;
+/// 與參考噪聲底相比，高頻損失超過此值 (dB) 時給出警告
+const HF_LOSS_WARNING_DB: f32 = 6.0;
+/// 沒有參考時，噪聲底高頻相對低頻的衰減超過此值 (dB) 時給出警告
+const HF_ROLLOFF_WARNING_DB: f32 = 30.0;
+/// 噪聲底估計的分段長度
+const FLOOR_SEGMENT: usize = 1024;
+/// 噪聲底估計最多使用的分段數 (限制中位數 Welch 的週期圖內存)
+const MAX_FLOOR_SEGMENTS: usize = 512;
+/// 噪聲底比較的頻率範圍 (Nyquist 頻率的比例，避開直流與抗混疊濾波器過渡帶)
+const FLOOR_RANGE: (f32, f32) = (0.1, 0.85);
+/// 防止 log10(0) 的最小值
+const MIN_LEVEL: f32 = 1e-20;
+
+/// 常見錄音設備的採樣率 (Hz)
+const STANDARD_SAMPLE_RATES: &[u32] = &[
+    8_000, 11_025, 16_000, 22_050, 24_000, 32_000, 44_100, 48_000, 64_000, 88_200, 96_000, 128_000, 176_400,
+    192_000, 200_000, 250_000, 256_000, 300_000, 312_500, 320_000, 352_800, 384_000, 400_000, 441_000, 500_000,
+    768_000,
+];
+
+/// 診斷錯誤
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiagnosticsError {
+    /// 既不是 WAV 也不是 FLAC 文件
+    UnsupportedFile,
+    /// WAV 解碼失敗
+    Wav(WavError),
+    /// FLAC 解碼失敗
+    Flac(FlacError),
+}
+
+impl fmt::Display for DiagnosticsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticsError::UnsupportedFile => write!(f, "not a WAV or FLAC file"),
+            DiagnosticsError::Wav(err) => write!(f, "{}", err),
+            DiagnosticsError::Flac(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for DiagnosticsError {}
+
+impl From<WavError> for DiagnosticsError {
+    fn from(err: WavError) -> Self {
+        DiagnosticsError::Wav(err)
+    }
+}
+
+impl From<FlacError> for DiagnosticsError {
+    fn from(err: FlacError) -> Self {
+        DiagnosticsError::Flac(err)
+    }
+}
+
+/// 單個通道的診斷結果
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelDiagnostics {
+    /// 最大絕對值
+    pub peak: f32,
+    /// 均方根 (包括直流)
+    pub rms: f32,
+    /// 直流偏移 (均值)
+    pub dc_offset: f32,
+    /// 削波段 (起始樣本, 長度)
+    pub clip_runs: Vec<(usize, usize)>,
+    /// 削波段中的樣本總數
+    pub clipped_samples: usize,
+    /// 去直流後沒有信號 (全零或恆定值)
+    pub dead: bool,
+    /// 噪聲底 (dB，中位數 Welch 估計，與 AudioDiagnostics 的頻率軸對應)
+    pub noise_floor_db: Vec<f32>,
+    /// 高頻損失 (dB)：有參考時為相對參考噪聲底的損失，否則為噪聲底高頻相對低頻的衰減
+    pub hf_loss_db: Option<f32>,
+}
+
+/// 線性幅度 -> dBFS
+fn to_db(amplitude: f32) -> f32 {
+    20.0 * amplitude.max(MIN_LEVEL).log10()
+}
+
+/// 中位數 (空切片返回 None)
+fn median(values: &mut [f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    Some(values[values.len() / 2])
+}
+
+/// 在 (頻率, dB) 參考曲線上線性插值；超出範圍時返回 None
+fn interpolate(frequencies: &[f32], levels_db: &[f32], frequency: f32) -> Option<f32> {
+    let upper = frequencies.iter().position(|&f| f >= frequency)?;
+    if upper == 0 {
+        return (frequencies[0] == frequency).then_some(levels_db[0]);
+    }
+    let (f0, f1) = (frequencies[upper - 1], frequencies[upper]);
+    let t = if f1 > f0 { (frequency - f0) / (f1 - f0) } else { 0.0 };
+    Some(levels_db[upper - 1] + t * (levels_db[upper] - levels_db[upper - 1]))
+}
+
+/// 查找連續 `min_run` 個以上、同一符號且絕對值不低於 `threshold` 的樣本段
+fn find_clip_runs(samples: &[f32], threshold: f32, min_run: usize) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    let mut sign = 0i8;
+    for (i, &x) in samples.iter().chain(std::iter::once(&0.0)).enumerate() {
+        let current = if x >= threshold {
+            1
+        } else if x <= -threshold {
+            -1
+        } else {
+            0
+        };
+        if current != sign {
+            if sign != 0 && i - start >= min_run {
+                runs.push((start, i - start));
+            }
+            start = i;
+            sign = current;
+        }
+    }
+    runs
+}
+
+/// AudioDiagnostics: 錄音質量報告
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioDiagnostics {
+    sample_rate: f32,
+    sample_rate_standard: bool,
+    channels: Vec<ChannelDiagnostics>,
+    /// 噪聲底的頻率軸 (Hz)
+    floor_frequencies: Vec<f32>,
+    /// 高頻損失是否相對參考噪聲底計算
+    reference_used: bool,
+    warnings: Vec<String>,
+}
+
+impl AudioDiagnostics {
+    /// 分析已解碼的音頻
+    ///
+    /// # Arguments
+    /// * `channels` - 各通道樣本 (範圍 [-1, 1])
+    /// * `sample_rate` - 採樣率 (Hz)
+    /// * `clip_threshold` - 削波閾值 (絕對值)
+    /// * `min_clip_run` - 最短削波段長度
+    /// * `reference` - 參考噪聲底 (頻率遞增的 Hz, dB)，例如同型號正常麥克風的 noise_floor_db
+    pub fn analyze(
+        channels: &[Vec<f32>],
+        sample_rate: f32,
+        clip_threshold: f32,
+        min_clip_run: usize,
+        reference: Option<(&[f32], &[f32])>,
+    ) -> AudioDiagnostics {
+        let reference = reference.filter(|(f, l)| f.len() >= 2 && f.len() == l.len());
+        let rounded_rate = sample_rate.round() as u32;
+        let sample_rate_standard =
+            (sample_rate - rounded_rate as f32).abs() < 1e-3 && STANDARD_SAMPLE_RATES.contains(&rounded_rate);
+
+        let mut report = AudioDiagnostics {
+            sample_rate,
+            sample_rate_standard,
+            channels: Vec::with_capacity(channels.len()),
+            floor_frequencies: Vec::new(),
+            reference_used: reference.is_some(),
+            warnings: Vec::new(),
+        };
+        if !sample_rate_standard {
+            report.warnings.push(format!(
+                "sample rate {} Hz is not a standard recorder rate (edited, time-expanded or mislabelled file?)",
+                sample_rate
+            ));
+        }
+
+        for (index, samples) in channels.iter().enumerate() {
+            let channel = report.analyze_channel(samples, clip_threshold, min_clip_run.max(1), reference);
+            let label = index + 1;
+            if channel.dead {
+                report.warnings.push(format!("channel {}: no signal (dead or disconnected input)", label));
+            }
+            if !channel.clip_runs.is_empty() {
+                report.warnings.push(format!(
+                    "channel {}: {} clipped runs ({} samples)",
+                    label,
+                    channel.clip_runs.len(),
+                    channel.clipped_samples
+                ));
+            }
+            if channel.dc_offset.abs() > DC_OFFSET_WARNING {
+                report.warnings.push(format!(
+                    "channel {}: DC offset {:.1}% of full scale",
+                    label,
+                    channel.dc_offset * 100.0
+                ));
+            }
+            match channel.hf_loss_db {
+                Some(loss) if report.reference_used && loss > HF_LOSS_WARNING_DB => report.warnings.push(format!(
+                    "channel {}: high-frequency noise floor {:.1} dB below reference (possible microphone degradation)",
+                    label, loss
+                )),
+                Some(loss) if !report.reference_used && loss > HF_ROLLOFF_WARNING_DB => {
+                    report.warnings.push(format!(
+                        "channel {}: noise floor falls {:.1} dB towards Nyquist \
+                         (microphone degradation, low-pass filtering or upsampled audio?)",
+                        label, loss
+                    ))
+                }
+                _ => {}
+            }
+            report.channels.push(channel);
+        }
+        report
+    }
+
+    fn analyze_channel(
+        &mut self,
+        samples: &[f32],
+        clip_threshold: f32,
+        min_clip_run: usize,
+        reference: Option<(&[f32], &[f32])>,
+    ) -> ChannelDiagnostics {
+        let n = samples.len().max(1) as f64;
+        let mean = samples.iter().map(|&x| x as f64).sum::<f64>() / n;
+        let mean_square = samples.iter().map(|&x| (x as f64).powi(2)).sum::<f64>() / n;
+        let ac_rms = (mean_square - mean * mean).max(0.0).sqrt() as f32;
+        let clip_runs = find_clip_runs(samples, clip_threshold, min_clip_run);
+        let dead = to_db(ac_rms) < DEAD_CHANNEL_DB;
+
+        // 中位數 Welch 需要保存每個分段的週期圖，長錄音只取均勻分佈的 MAX_FLOOR_SEGMENTS 段
+        let available = samples.len() / FLOOR_SEGMENT;
+        let excerpt: Vec<f32> = if available > MAX_FLOOR_SEGMENTS {
+            (0..MAX_FLOOR_SEGMENTS)
+                .flat_map(|k| {
+                    let start = k * available / MAX_FLOOR_SEGMENTS * FLOOR_SEGMENT;
+                    samples[start..start + FLOOR_SEGMENT].iter().copied()
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let (floor_input, noverlap) = if excerpt.is_empty() {
+            (samples, FLOOR_SEGMENT / 2)
+        } else {
+            (excerpt.as_slice(), 0)
+        };
+        let welch = welch(
+            floor_input,
+            self.sample_rate,
+            FLOOR_SEGMENT,
+            noverlap,
+            "hann",
+            Detrend::Constant,
+            Average::Median,
+            true,
+            0.0,
+        );
+        if self.floor_frequencies.is_empty() {
+            self.floor_frequencies = welch.get_frequencies();
+        }
+        let noise_floor_db: Vec<f32> = welch.get_values().iter().map(|&v| 10.0 * v.max(MIN_LEVEL).log10()).collect();
+
+        ChannelDiagnostics {
+            peak: find_global_max(samples),
+            rms: mean_square.sqrt() as f32,
+            dc_offset: mean as f32,
+            clipped_samples: clip_runs.iter().map(|&(_, len)| len).sum(),
+            clip_runs,
+            dead,
+            hf_loss_db: if dead { None } else { self.hf_loss(&noise_floor_db, reference) },
+            noise_floor_db,
+        }
+    }
+
+    /// 比較噪聲底低頻三分之一與高頻三分之一的中位數
+    fn hf_loss(&self, floor_db: &[f32], reference: Option<(&[f32], &[f32])>) -> Option<f32> {
+        let nyquist = self.sample_rate / 2.0;
+        let (mut low, mut high) = (FLOOR_RANGE.0 * nyquist, FLOOR_RANGE.1 * nyquist);
+        if let Some((frequencies, _)) = reference {
+            low = low.max(frequencies[0]);
+            high = high.min(frequencies[frequencies.len() - 1]);
+        }
+        if high <= low {
+            return None;
+        }
+        let third = (high - low) / 3.0;
+
+        let (mut lower, mut upper) = (Vec::new(), Vec::new());
+        for (&frequency, &level) in self.floor_frequencies.iter().zip(floor_db) {
+            if frequency < low || frequency > high {
+                continue;
+            }
+            let level = match reference {
+                Some((frequencies, levels)) => level - interpolate(frequencies, levels, frequency)?,
+                None => level,
+            };
+            if frequency <= low + third {
+                lower.push(level);
+            } else if frequency >= high - third {
+                upper.push(level);
+            }
+        }
+        Some(median(&mut lower)? - median(&mut upper)?)
+    }
+
+    /// 解碼 WAV 或 FLAC 文件並分析
+    ///
+    /// 削波閾值取離滿量程 1 LSB 以內 (整數格式，按有效位數) 或 0.999 (浮點格式)，
+    /// 最短削波段為 DEFAULT_MIN_CLIP_RUN。
+    pub fn from_file(bytes: &[u8], reference: Option<(&[f32], &[f32])>) -> Result<AudioDiagnostics, DiagnosticsError> {
+        let int_threshold = |bits: u32| 1.0 - 2.0 / (1u64 << (bits.clamp(2, 32) - 1)) as f32;
+        // 直接借用解碼結果的通道數據，避免再複製一份完整音頻
+        let report = match bytes.get(..4) {
+            Some(b"RIFF" | b"RF64" | b"BW64") => {
+                let wav = WavAudio::decode(bytes)?;
+                let format = wav.info().format();
+                let threshold = match format.sample_format {
+                    SampleFormat::Float => FLOAT_CLIP_THRESHOLD,
+                    SampleFormat::Int => int_threshold(format.valid_bits_per_sample as u32),
+                };
+                AudioDiagnostics::analyze(
+                    wav.channels(),
+                    format.sample_rate as f32,
+                    threshold,
+                    DEFAULT_MIN_CLIP_RUN,
+                    reference,
+                )
+            }
+            _ => {
+                let flac = FlacAudio::decode(bytes).map_err(|err| match err {
+                    FlacError::NotFlac => DiagnosticsError::UnsupportedFile,
+                    err => DiagnosticsError::Flac(err),
+                })?;
+                let info = flac.info();
+                AudioDiagnostics::analyze(
+                    flac.channels(),
+                    info.get_sample_rate() as f32,
+                    int_threshold(info.get_bits_per_sample()),
+                    DEFAULT_MIN_CLIP_RUN,
+                    reference,
+                )
+            }
+        };
+        Ok(report)
+    }
+
+    /// 各通道的診斷結果
+    pub fn channels(&self) -> &[ChannelDiagnostics] {
+        &self.channels
+    }
+
+    /// 警告信息 (英文，每條一句)
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+}
+
+#[wasm_bindgen]
+impl AudioDiagnostics {
+    /// 採樣率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// 採樣率是否為常見錄音機採樣率
+    #[wasm_bindgen]
+    pub fn get_sample_rate_standard(&self) -> bool {
+        self.sample_rate_standard
+    }
+
+    /// 通道數
+    #[wasm_bindgen]
+    pub fn get_num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// 峰值 (絕對值)；通道不存在時返回 0
+    #[wasm_bindgen]
+    pub fn get_peak(&self, channel_idx: usize) -> f32 {
+        self.channels.get(channel_idx).map_or(0.0, |c| c.peak)
+    }
+
+    /// 峰值 (dBFS)
+    #[wasm_bindgen]
+    pub fn get_peak_db(&self, channel_idx: usize) -> f32 {
+        to_db(self.get_peak(channel_idx))
+    }
+
+    /// RMS；通道不存在時返回 0
+    #[wasm_bindgen]
+    pub fn get_rms(&self, channel_idx: usize) -> f32 {
+        self.channels.get(channel_idx).map_or(0.0, |c| c.rms)
+    }
+
+    /// RMS (dBFS)
+    #[wasm_bindgen]
+    pub fn get_rms_db(&self, channel_idx: usize) -> f32 {
+        to_db(self.get_rms(channel_idx))
+    }
+
+    /// 直流偏移 (均值)
+    #[wasm_bindgen]
+    pub fn get_dc_offset(&self, channel_idx: usize) -> f32 {
+        self.channels.get(channel_idx).map_or(0.0, |c| c.dc_offset)
+    }
+
+    /// 削波段中的樣本總數
+    #[wasm_bindgen]
+    pub fn get_clipped_samples(&self, channel_idx: usize) -> usize {
+        self.channels.get(channel_idx).map_or(0, |c| c.clipped_samples)
+    }
+
+    /// 削波段的起始樣本索引
+    #[wasm_bindgen]
+    pub fn get_clip_run_starts(&self, channel_idx: usize) -> Vec<f64> {
+        self.channels
+            .get(channel_idx)
+            .map_or_else(Vec::new, |c| c.clip_runs.iter().map(|&(start, _)| start as f64).collect())
+    }
+
+    /// 削波段的長度 (樣本數，與 get_clip_run_starts 對應)
+    #[wasm_bindgen]
+    pub fn get_clip_run_lengths(&self, channel_idx: usize) -> Vec<f64> {
+        self.channels
+            .get(channel_idx)
+            .map_or_else(Vec::new, |c| c.clip_runs.iter().map(|&(_, len)| len as f64).collect())
+    }
+
+    /// 通道是否沒有信號
+    #[wasm_bindgen]
+    pub fn is_dead(&self, channel_idx: usize) -> bool {
+        self.channels.get(channel_idx).is_some_and(|c| c.dead)
+    }
+
+    /// 噪聲底的頻率軸 (Hz)
+    #[wasm_bindgen]
+    pub fn get_noise_floor_frequencies(&self) -> Vec<f32> {
+        self.floor_frequencies.clone()
+    }
+
+    /// 噪聲底 (dB)；可保存為之後診斷的參考噪聲底
+    #[wasm_bindgen]
+    pub fn get_noise_floor_db(&self, channel_idx: usize) -> Vec<f32> {
+        self.channels.get(channel_idx).map_or_else(Vec::new, |c| c.noise_floor_db.clone())
+    }
+
+    /// 高頻損失 (dB)；音頻太短或通道沒有信號時返回 undefined
+    #[wasm_bindgen]
+    pub fn get_hf_loss_db(&self, channel_idx: usize) -> Option<f32> {
+        self.channels.get(channel_idx)?.hf_loss_db
+    }
+
+    /// 高頻損失是否相對參考噪聲底計算 (否則為噪聲底自身的高頻衰減)
+    #[wasm_bindgen]
+    pub fn get_reference_used(&self) -> bool {
+        self.reference_used
+    }
+
+    /// 警告信息
+    #[wasm_bindgen]
+    pub fn get_warnings(&self) -> Vec<String> {
+        self.warnings.clone()
+    }
+}
+
+/// 空數組表示沒有參考噪聲底
+pub(crate) fn reference_from<'a>(frequencies: &'a [f32], levels_db: &'a [f32]) -> Option<(&'a [f32], &'a [f32])> {
+    (!frequencies.is_empty()).then_some((frequencies, levels_db))
+}
+
+/// 診斷 WAV / FLAC 文件的錄音質量
+///
+/// # Arguments
+/// * `bytes` - 文件內容 (Uint8Array)
+/// * `reference_frequencies` - 參考噪聲底頻率 (Hz，遞增)；空數組表示不使用參考
+/// * `reference_levels_db` - 參考噪聲底 (dB，例如正常設備錄音的 get_noise_floor_db())
+///
+/// # Returns
+/// AudioDiagnostics 對象；文件無效時拋出 Error
+#[wasm_bindgen]
+pub fn diagnose_audio_file(
+    bytes: &[u8],
+    reference_frequencies: &[f32],
+    reference_levels_db: &[f32],
+) -> Result<AudioDiagnostics, JsError> {
+    Ok(AudioDiagnostics::from_file(bytes, reference_from(reference_frequencies, reference_levels_db))?)
+}
+
+/// 診斷單通道音頻的錄音質量
+///
+/// # Arguments
+/// * `channel_data` - 音頻數據 (Float32Array)
+/// * `sample_rate` - 採樣率 (Hz)
+/// * `clip_threshold` - 削波閾值 (絕對值，例如 16 位 PCM 為 0.99994)
+/// * `min_clip_run` - 最短削波段長度 (樣本數)
+/// * `reference_frequencies` / `reference_levels_db` - 參考噪聲底；空數組表示不使用參考
+///
+/// # Returns
+/// AudioDiagnostics 對象
+#[wasm_bindgen]
+pub fn diagnose_audio(
+    channel_data: &[f32],
+    sample_rate: f32,
+    clip_threshold: f32,
+    min_clip_run: usize,
+    reference_frequencies: &[f32],
+    reference_levels_db: &[f32],
+) -> AudioDiagnostics {
+    AudioDiagnostics::analyze(
+        &[channel_data.to_vec()],
+        sample_rate,
+        clip_threshold,
+        min_clip_run,
+        reference_from(reference_frequencies, reference_levels_db),
+    )
+}
@@ -0,0 +1,242 @@
+// ============================================================
+// 逐幀頻譜特徵
+// 在 flow..fhigh 頻帶內由 STFT 線性幅度計算質心、帶寬、平坦度、
+// 熵、滾降頻率、頻譜通量、波峰因數與分頻帶能量比，
+// 供噪聲過濾與自動分類使用。
+// ============================================================
+
+use wasm_bindgen::prelude::*;
+
+/// 防止 log(0) 的最小功率
+const MIN_POWER: f32 = 1e-20;
+
+/// 特徵提取參數
+#[derive(Clone, Debug)]
+pub(crate) struct FeatureParams {
+    pub(crate) sample_rate: f32,
+    pub(crate) fft_size: usize,
+    pub(crate) flow: f32,
+    pub(crate) fhigh: f32,
+    /// 滾降能量比例 (0.0-1.0，典型值: 0.85)
+    pub(crate) rolloff: f32,
+    /// 分頻帶邊界 (Hz，遞增)；相鄰兩個邊界組成一個頻帶
+    pub(crate) band_edges: Vec<f32>,
+}
+
+impl FeatureParams {
+    /// flow..fhigh 對應的 bin 範圍 [start, end)
+    fn bin_range(&self, freq_bins: usize) -> (usize, usize) {
+        let bin_hz = self.sample_rate / self.fft_size as f32;
+        let start = (self.flow.max(0.0) / bin_hz).ceil() as usize;
+        let end = ((self.fhigh / bin_hz).floor() as usize + 1).min(freq_bins);
+        (start.min(end), end)
+    }
+}
+
+/// SpectralFeatures: 每幀的頻譜描述量
+///
+/// 所有數組長度均為 num_frames（分頻帶能量比為 num_frames x num_bands，行優先）。
+/// 頻率類特徵的單位為 Hz；無能量的幀所有特徵均為 0。
+#[wasm_bindgen]
+pub struct SpectralFeatures {
+    num_frames: usize,
+    num_bands: usize,
+    centroid: Vec<f32>,
+    bandwidth: Vec<f32>,
+    flatness: Vec<f32>,
+    entropy: Vec<f32>,
+    rolloff: Vec<f32>,
+    flux: Vec<f32>,
+    crest: Vec<f32>,
+    band_ratios: Vec<f32>,
+}
+
+impl SpectralFeatures {
+    fn with_frames(num_frames: usize, num_bands: usize) -> SpectralFeatures {
+        SpectralFeatures {
+            num_frames,
+            num_bands,
+            centroid: vec![0.0; num_frames],
+            bandwidth: vec![0.0; num_frames],
+            flatness: vec![0.0; num_frames],
+            entropy: vec![0.0; num_frames],
+            rolloff: vec![0.0; num_frames],
+            flux: vec![0.0; num_frames],
+            crest: vec![0.0; num_frames],
+            band_ratios: vec![0.0; num_frames * num_bands],
+        }
+    }
+
+    /// 空結果
+    pub(crate) fn empty() -> SpectralFeatures {
+        SpectralFeatures::with_frames(0, 0)
+    }
+
+    /// 計算所有幀的頻譜特徵
+    ///
+    /// `magnitudes` 為 num_frames x freq_bins 的行優先線性幅度矩陣。
+    pub(crate) fn compute(
+        magnitudes: &[f32],
+        freq_bins: usize,
+        num_frames: usize,
+        params: &FeatureParams,
+    ) -> SpectralFeatures {
+        let num_frames = num_frames.min(magnitudes.len() / freq_bins.max(1));
+        let num_bands = params.band_edges.len().saturating_sub(1);
+        let mut features = SpectralFeatures::with_frames(num_frames, num_bands);
+
+        let (start, end) = params.bin_range(freq_bins);
+        if start >= end {
+            return features;
+        }
+
+        let bin_hz = params.sample_rate / params.fft_size as f32;
+        let band_bins: Vec<(usize, usize)> = params
+            .band_edges
+            .windows(2)
+            .map(|edge| {
+                let lo = ((edge[0] / bin_hz).ceil().max(0.0) as usize).clamp(start, end);
+                let hi = ((edge[1] / bin_hz).ceil().max(0.0) as usize).clamp(lo, end);
+                (lo, hi)
+            })
+            .collect();
+
+        let mut previous: Option<&[f32]> = None;
+        for frame in 0..num_frames {
+            let row = &magnitudes[frame * freq_bins..(frame + 1) * freq_bins];
+            let band = &row[start..end];
+
+            // 頻譜通量: 相鄰幀幅度正向差分的 L2 範數
+            if let Some(prev) = previous {
+                let flux: f32 = band
+                    .iter()
+                    .zip(&prev[start..end])
+                    .map(|(&m, &p)| (m - p).max(0.0).powi(2))
+                    .sum();
+                features.flux[frame] = flux.sqrt();
+            }
+            previous = Some(row);
+
+            let magnitude_sum: f32 = band.iter().sum();
+            let energy: f32 = band.iter().map(|&m| m * m).sum();
+            if magnitude_sum <= 0.0 || energy <= 0.0 {
+                continue;
+            }
+            let n = band.len() as f32;
+            let freq = |i: usize| (start + i) as f32 * bin_hz;
+
+            // 質心與帶寬 (以幅度加權)
+            let centroid: f32 = band.iter().enumerate().map(|(i, &m)| freq(i) * m).sum::<f32>() / magnitude_sum;
+            let variance: f32 = band
+                .iter()
+                .enumerate()
+                .map(|(i, &m)| (freq(i) - centroid).powi(2) * m)
+                .sum::<f32>()
+                / magnitude_sum;
+            features.centroid[frame] = centroid;
+            features.bandwidth[frame] = variance.sqrt();
+
+            // 平坦度: 功率譜幾何平均 / 算術平均
+            let log_mean: f32 = band.iter().map(|&m| (m * m).max(MIN_POWER).ln()).sum::<f32>() / n;
+            features.flatness[frame] = (log_mean.exp() / (energy / n)).min(1.0);
+
+            // 熵: 歸一化功率分佈的香農熵，除以 log(N) 歸一化到 0-1
+            if band.len() > 1 {
+                let entropy: f32 = band
+                    .iter()
+                    .map(|&m| m * m / energy)
+                    .filter(|&p| p > 0.0)
+                    .map(|p| -p * p.ln())
+                    .sum();
+                features.entropy[frame] = entropy / n.ln();
+            }
+
+            // 滾降頻率: 累積能量達到 rolloff 比例的最低頻率
+            let target = energy * params.rolloff.clamp(0.0, 1.0);
+            let mut cumulative = 0.0f32;
+            for (i, &m) in band.iter().enumerate() {
+                cumulative += m * m;
+                if cumulative >= target {
+                    features.rolloff[frame] = freq(i);
+                    break;
+                }
+            }
+
+            // 波峰因數: 峰值幅度 / 平均幅度
+            let peak = band.iter().copied().fold(0.0f32, f32::max);
+            features.crest[frame] = peak / (magnitude_sum / n);
+
+            // 分頻帶能量比
+            let ratios = &mut features.band_ratios[frame * num_bands..(frame + 1) * num_bands];
+            for (ratio, &(lo, hi)) in ratios.iter_mut().zip(&band_bins) {
+                let band_energy: f32 = row[lo..hi].iter().map(|&m| m * m).sum();
+                *ratio = band_energy / energy;
+            }
+        }
+
+        features
+    }
+}
+
+#[wasm_bindgen]
+impl SpectralFeatures {
+    /// 幀數
+    #[wasm_bindgen]
+    pub fn get_num_frames(&self) -> usize {
+        self.num_frames
+    }
+
+    /// 分頻帶數量 (band_edges 長度 - 1)
+    #[wasm_bindgen]
+    pub fn get_num_bands(&self) -> usize {
+        self.num_bands
+    }
+
+    /// 頻譜質心 (Hz)
+    #[wasm_bindgen]
+    pub fn get_centroid(&self) -> Vec<f32> {
+        self.centroid.clone()
+    }
+
+    /// 頻譜帶寬: 以幅度加權的頻率標準差 (Hz)
+    #[wasm_bindgen]
+    pub fn get_bandwidth(&self) -> Vec<f32> {
+        self.bandwidth.clone()
+    }
+
+    /// 頻譜平坦度 (0.0-1.0，白噪聲接近 1，純音接近 0)
+    #[wasm_bindgen]
+    pub fn get_flatness(&self) -> Vec<f32> {
+        self.flatness.clone()
+    }
+
+    /// 歸一化頻譜熵 (0.0-1.0)
+    #[wasm_bindgen]
+    pub fn get_entropy(&self) -> Vec<f32> {
+        self.entropy.clone()
+    }
+
+    /// 滾降頻率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_rolloff(&self) -> Vec<f32> {
+        self.rolloff.clone()
+    }
+
+    /// 頻譜通量（第一幀為 0）
+    #[wasm_bindgen]
+    pub fn get_flux(&self) -> Vec<f32> {
+        self.flux.clone()
+    }
+
+    /// 波峰因數: 峰值幅度 / 平均幅度
+    #[wasm_bindgen]
+    pub fn get_crest(&self) -> Vec<f32> {
+        self.crest.clone()
+    }
+
+    /// 分頻帶能量比 (num_frames x num_bands，行優先)，相對於 flow..fhigh 內的總能量
+    #[wasm_bindgen]
+    pub fn get_band_ratios(&self) -> Vec<f32> {
+        self.band_ratios.clone()
+    }
+}
@@ -0,0 +1,738 @@
+// ============================================================
+// FIR 濾波器設計與線性相位濾波
+// 窗函數法 (windowed-sinc，窗函數沿用 create_window) 與
+// Parks-McClellan 等波紋設計 (Remez 交換算法)；
+// 長核使用 FFT 重疊保留 (overlap-save) 卷積。
+// 濾波器為對稱係數 (線性相位)，群延遲恆為 (N - 1) / 2 個樣本，
+// 補償後叫聲的起止時間不會因濾波而偏移。
+// ============================================================
+
+use std::f64::consts::PI;
+use std::fmt;
+
+use num_complex::Complex;
+use rustfft::FftPlanner;
+use wasm_bindgen::prelude::*;
+
+use crate::create_window;
+use crate::iir::{band_edges, BandType, FilterError};
+
+/// 允許的最大抽頭數
+const MAX_TAPS: usize = 8191;
+/// 等波紋設計允許的最大抽頭數：Remez 交換的耗時約為 O(N² · 網格密度)，
+/// 更長的濾波器會長時間阻塞瀏覽器主線程，應改用窗函數法
+const MAX_EQUIRIPPLE_TAPS: usize = 1025;
+/// 抽頭數不超過此值時直接卷積，否則使用 FFT 重疊保留
+const DIRECT_CONVOLUTION_MAX_TAPS: usize = 64;
+/// Remez 算法的網格密度 (每個極值點的網格點數)
+const GRID_DENSITY: usize = 16;
+/// Remez 算法的最大迭代次數
+const MAX_ITERATIONS: usize = 40;
+/// 測量實際波紋與衰減時每個抽頭的網格點數
+const MEASURE_DENSITY: usize = 16;
+/// 判斷是否達到設計指標時允許的誤差 (dB)
+const SPEC_TOLERANCE_DB: f64 = 0.01;
+
+/// FIR 濾波器設計錯誤
+#[derive(Clone, Debug, PartialEq)]
+pub enum FirError {
+    /// 頻帶類型、採樣率、截止頻率、波紋或衰減參數無效 (與 IIR 設計共用的檢查)
+    Design(FilterError),
+    /// 抽頭數必須在 3..=max_taps 之間 (窗函數法 8191，等波紋設計 1025)
+    InvalidNumTaps { num_taps: usize, max_taps: usize },
+    /// 高通 / 帶阻濾波器在 Nyquist 處增益不為零，抽頭數必須為奇數
+    EvenNumTaps { num_taps: usize },
+    /// 過渡帶寬度必須為正數，且不能使通帶或阻帶為空
+    InvalidTransition { transition_hz: f32 },
+}
+
+impl fmt::Display for FirError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FirError::Design(err) => write!(f, "{}", err),
+            FirError::InvalidNumTaps { num_taps, max_taps } => {
+                write!(f, "number of taps {} is outside 3..={}", num_taps, max_taps)
+            }
+            FirError::EvenNumTaps { num_taps } => write!(
+                f,
+                "highpass and bandstop FIR filters need an odd number of taps, got {}",
+                num_taps
+            ),
+            FirError::InvalidTransition { transition_hz } => write!(
+                f,
+                "transition width {} Hz must be positive and leave every band non-empty",
+                transition_hz
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FirError {}
+
+impl From<FilterError> for FirError {
+    fn from(err: FilterError) -> Self {
+        FirError::Design(err)
+    }
+}
+
+/// 檢查抽頭數 (3..=max_taps)；高通與帶阻要求奇數 (I 型濾波器)
+fn check_num_taps(band: BandType, num_taps: usize, max_taps: usize) -> Result<(), FirError> {
+    if !(3..=max_taps).contains(&num_taps) {
+        return Err(FirError::InvalidNumTaps { num_taps, max_taps });
+    }
+    if num_taps.is_multiple_of(2) && matches!(band, BandType::Highpass | BandType::Bandstop) {
+        return Err(FirError::EvenNumTaps { num_taps });
+    }
+    Ok(())
+}
+
+/// 理想低通濾波器的脈衝響應 (截止頻率 `cutoff` 以採樣率歸一化)，以 `center` 為中心
+fn ideal_lowpass(cutoff: f64, n: f64, center: f64) -> f64 {
+    let x = n - center;
+    if x == 0.0 {
+        2.0 * cutoff
+    } else {
+        (2.0 * PI * cutoff * x).sin() / (PI * x)
+    }
+}
+
+/// 係數 `taps` 在 ω (弧度/樣本) 處的複數響應
+fn taps_response(taps: &[f64], omega: f64) -> Complex<f64> {
+    taps.iter()
+        .enumerate()
+        .map(|(n, &h)| Complex::from_polar(h, -omega * n as f64))
+        .sum()
+}
+
+// ------------------------------------------------------------
+// Parks-McClellan (Remez 交換算法)
+// ------------------------------------------------------------
+
+/// 等波紋設計的一個頻帶 (頻率以採樣率歸一化，0..=0.5)
+#[derive(Clone, Copy, Debug)]
+struct RemezBand {
+    start: f64,
+    end: f64,
+    desired: f64,
+    weight: f64,
+}
+
+/// 在極值點上構造的重心插值多項式 P(x)，x = cos ω
+struct Barycentric {
+    nodes: Vec<f64>,
+    weights: Vec<f64>,
+    values: Vec<f64>,
+}
+
+impl Barycentric {
+    fn eval(&self, x: f64) -> f64 {
+        let mut numerator = 0.0;
+        let mut denominator = 0.0;
+        for ((&node, &w), &y) in self.nodes.iter().zip(&self.weights).zip(&self.values) {
+            let dx = x - node;
+            if dx.abs() < 1e-14 {
+                return y;
+            }
+            let c = w / dx;
+            numerator += c * y;
+            denominator += c;
+        }
+        numerator / denominator
+    }
+}
+
+/// 重心權重 1 / Π(x_i - x_j)
+///
+/// 乘積按步長交錯累乘並乘以 2，避免大量節點時上溢或下溢 (與 remez.c 相同)。
+fn barycentric_weights(nodes: &[f64]) -> Vec<f64> {
+    let n = nodes.len();
+    let stride = (n - 1) / 15 + 1;
+    (0..n)
+        .map(|i| {
+            let mut denom = 1.0;
+            for offset in 0..stride {
+                for j in (offset..n).step_by(stride) {
+                    if j != i {
+                        denom *= 2.0 * (nodes[i] - nodes[j]);
+                    }
+                }
+            }
+            if denom.abs() < 1e-300 {
+                denom = 1e-300;
+            }
+            1.0 / denom
+        })
+        .collect()
+}
+
+/// 從誤差曲線中選出 `count` 個符號交替的極值點
+fn find_extrema(error: &[f64], count: usize) -> Vec<usize> {
+    let n = error.len();
+    let mut candidates: Vec<usize> = (0..n)
+        .filter(|&i| {
+            // 端點或局部極值 (端點沒有的一側鄰居以自身代替)
+            let e = error[i];
+            let left = if i > 0 { error[i - 1] } else { e };
+            let right = if i + 1 < n { error[i + 1] } else { e };
+            (e > 0.0 && left <= e && right <= e) || (e < 0.0 && left >= e && right >= e)
+        })
+        .collect();
+
+    // 相鄰同號的極值只保留幅度較大者
+    let mut alternating: Vec<usize> = Vec::with_capacity(candidates.len());
+    for i in candidates.drain(..) {
+        match alternating.last_mut() {
+            Some(last) if error[*last].signum() == error[i].signum() => {
+                if error[i].abs() > error[*last].abs() {
+                    *last = i;
+                }
+            }
+            _ => alternating.push(i),
+        }
+    }
+
+    // 過多時從兩端去掉幅度較小者，保持交替
+    while alternating.len() > count {
+        let first = error[alternating[0]].abs();
+        let last = error[alternating[alternating.len() - 1]].abs();
+        if first < last {
+            alternating.remove(0);
+        } else {
+            alternating.pop();
+        }
+    }
+    alternating
+}
+
+/// Remez 交換算法設計對稱 FIR 濾波器 (I 型或 II 型)
+///
+/// II 型 (偶數抽頭) 的響應含因子 cos(ω/2)，將期望響應與權重分別除以 / 乘以該因子，
+/// 化為與 I 型相同的餘弦多項式逼近問題。
+fn remez(num_taps: usize, bands: &[RemezBand]) -> Vec<f64> {
+    let odd = num_taps % 2 == 1;
+    let num_coeffs = if odd { num_taps.div_ceil(2) } else { num_taps / 2 };
+    let num_extrema = num_coeffs + 1;
+
+    // 頻率網格: 各頻帶按寬度比例分配網格點
+    let total_width: f64 = bands.iter().map(|b| b.end - b.start).sum();
+    let grid_size = GRID_DENSITY * num_extrema;
+    let mut grid = Vec::new();
+    let mut desired = Vec::new();
+    let mut weight = Vec::new();
+    for band in bands {
+        let mut end = band.end;
+        // II 型在 Nyquist 處增益恆為 0，網格不包含該點
+        if !odd && end > 0.5 - 1e-9 {
+            end -= 0.5 / grid_size as f64;
+        }
+        let points = (((end - band.start) / total_width * grid_size as f64).round() as usize).max(2);
+        for k in 0..points {
+            let f = band.start + (end - band.start) * k as f64 / (points - 1) as f64;
+            let factor = if odd { 1.0 } else { (PI * f).cos() };
+            grid.push(f);
+            desired.push(band.desired / factor);
+            weight.push(band.weight * factor);
+        }
+    }
+
+    // 初始極值點均勻分佈
+    let mut extrema: Vec<usize> = (0..num_extrema)
+        .map(|i| i * (grid.len() - 1) / (num_extrema - 1).max(1))
+        .collect();
+
+    let build = |extrema: &[usize]| -> Barycentric {
+        let nodes: Vec<f64> = extrema.iter().map(|&i| (2.0 * PI * grid[i]).cos()).collect();
+        let weights = barycentric_weights(&nodes);
+        // δ 使插值多項式的最高次係數為零
+        let mut num = 0.0;
+        let mut den = 0.0;
+        let mut sign = 1.0;
+        for (&i, &w) in extrema.iter().zip(&weights) {
+            num += w * desired[i];
+            den += sign * w / weight[i];
+            sign = -sign;
+        }
+        let delta = num / den;
+        let mut sign = 1.0;
+        let values = extrema
+            .iter()
+            .map(|&i| {
+                let v = desired[i] - sign * delta / weight[i];
+                sign = -sign;
+                v
+            })
+            .collect();
+        Barycentric { nodes, weights, values }
+    };
+
+    let mut poly = build(&extrema);
+    for _ in 0..MAX_ITERATIONS {
+        let error: Vec<f64> = grid
+            .iter()
+            .enumerate()
+            .map(|(i, &f)| weight[i] * (desired[i] - poly.eval((2.0 * PI * f).cos())))
+            .collect();
+
+        let next = find_extrema(&error, num_extrema);
+        if next.len() < num_extrema {
+            break;
+        }
+        let magnitudes: Vec<f64> = next.iter().map(|&i| error[i].abs()).collect();
+        let max = magnitudes.iter().cloned().fold(0.0, f64::max);
+        let min = magnitudes.iter().cloned().fold(f64::INFINITY, f64::min);
+        let converged = next == extrema || max <= 0.0 || (max - min) / max < 1e-4;
+        extrema = next;
+        poly = build(&extrema);
+        if converged {
+            break;
+        }
+    }
+
+    // 在 ω_j = 2πj / N 處採樣振幅響應 A(ω)，以逆 DFT 得到對稱係數
+    let amplitude = |omega: f64| {
+        let factor = if odd { 1.0 } else { (omega / 2.0).cos() };
+        factor * poly.eval(omega.cos())
+    };
+    let n = num_taps as f64;
+    let center = (n - 1.0) / 2.0;
+    let samples: Vec<f64> = (0..=(num_taps - 1) / 2).map(|j| amplitude(2.0 * PI * j as f64 / n)).collect();
+    (0..num_taps)
+        .map(|k| {
+            let offset = k as f64 - center;
+            let sum: f64 = samples
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(j, &a)| 2.0 * a * (2.0 * PI * j as f64 * offset / n).cos())
+                .sum();
+            (samples[0] + sum) / n
+        })
+        .collect()
+}
+
+/// 在各頻帶的密集網格上測量實際的通帶波紋與阻帶衰減 (dB)
+///
+/// 通帶波紋為 20·log10((1 + δp) / (1 - δp))，δp 為 |H| 與 1 的最大偏差；
+/// 阻帶衰減為 -20·log10(δs)，δs 為阻帶內 |H| 的最大值。
+fn measure_bands(taps: &[f64], bands: &[RemezBand]) -> (f64, f64) {
+    let total_width: f64 = bands.iter().map(|b| b.end - b.start).sum();
+    let mut delta_pass = 0.0f64;
+    let mut delta_stop = 0.0f64;
+    for band in bands {
+        let points = ((band.end - band.start) / total_width * (MEASURE_DENSITY * taps.len()) as f64) as usize;
+        let points = points.max(2);
+        for k in 0..points {
+            let f = band.start + (band.end - band.start) * k as f64 / (points - 1) as f64;
+            let magnitude = taps_response(taps, 2.0 * PI * f).norm();
+            if band.desired > 0.0 {
+                delta_pass = delta_pass.max((magnitude - 1.0).abs());
+            } else {
+                delta_stop = delta_stop.max(magnitude);
+            }
+        }
+    }
+    let ripple_db = if delta_pass < 1.0 {
+        20.0 * ((1.0 + delta_pass) / (1.0 - delta_pass)).log10()
+    } else {
+        f64::INFINITY
+    };
+    (ripple_db, -20.0 * delta_stop.max(1e-300).log10())
+}
+
+// ------------------------------------------------------------
+// 卷積
+// ------------------------------------------------------------
+
+/// 計算 y[k] = Σ h[m]·x[k - m]，k = 0..out_len (x 在 [0, len) 之外視為 0)
+fn convolve(taps: &[f64], input: &[f32], out_len: usize) -> Vec<f64> {
+    if taps.len() <= DIRECT_CONVOLUTION_MAX_TAPS {
+        return (0..out_len)
+            .map(|k| {
+                let lo = (k + 1).saturating_sub(input.len());
+                let hi = taps.len().min(k + 1);
+                (lo..hi).map(|m| taps[m] * input[k - m] as f64).sum()
+            })
+            .collect();
+    }
+
+    // 重疊保留: 每塊 FFT 大小為 fft_size，有效輸出 block = fft_size - (L - 1) 個樣本
+    let overlap = taps.len() - 1;
+    let fft_size = (4 * taps.len()).next_power_of_two();
+    let block = fft_size - overlap;
+
+    let mut planner = FftPlanner::<f64>::new();
+    let forward = planner.plan_fft_forward(fft_size);
+    let inverse = planner.plan_fft_inverse(fft_size);
+
+    let mut kernel = vec![Complex::new(0.0, 0.0); fft_size];
+    for (slot, &h) in kernel.iter_mut().zip(taps) {
+        *slot = Complex::new(h / fft_size as f64, 0.0);
+    }
+    forward.process(&mut kernel);
+
+    // 輸入前補 L - 1 個零: 第 b 塊覆蓋 x[b·block - (L - 1) .. b·block + block)
+    let sample = |i: isize| -> f64 {
+        if i >= 0 && (i as usize) < input.len() {
+            input[i as usize] as f64
+        } else {
+            0.0
+        }
+    };
+
+    let mut output = Vec::with_capacity(out_len);
+    let mut buffer = vec![Complex::new(0.0, 0.0); fft_size];
+    let mut start = 0usize;
+    while start < out_len {
+        let origin = start as isize - overlap as isize;
+        for (i, slot) in buffer.iter_mut().enumerate() {
+            *slot = Complex::new(sample(origin + i as isize), 0.0);
+        }
+        forward.process(&mut buffer);
+        for (b, k) in buffer.iter_mut().zip(&kernel) {
+            *b *= k;
+        }
+        inverse.process(&mut buffer);
+
+        let count = block.min(out_len - start);
+        output.extend(buffer[overlap..overlap + count].iter().map(|c| c.re));
+        start += block;
+    }
+    output
+}
+
+/// FirFilter: 對稱係數 (線性相位) 的 FIR 數字濾波器
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct FirFilter {
+    taps: Vec<f64>,
+    sample_rate: f64,
+    /// 等波紋設計的指標 (通帶波紋 dB, 阻帶衰減 dB)：(要求值, 實際值)；窗函數法為 None
+    spec: Option<((f64, f64), (f64, f64))>,
+}
+
+impl FirFilter {
+    /// 窗函數法設計 FIR 濾波器
+    ///
+    /// 理想響應的 sinc 核乘以 `create_window` 生成的窗函數，
+    /// 再歸一化使通帶中心增益為 1 (低通 / 帶阻: DC；高通: Nyquist；帶通: 通帶中心)。
+    ///
+    /// # Arguments
+    /// * `band` - 頻帶類型
+    /// * `num_taps` - 抽頭數 (高通 / 帶阻必須為奇數)
+    /// * `sample_rate` - 採樣率 (Hz)
+    /// * `low_hz` - 高通的截止頻率；帶通 / 帶阻的下邊界
+    /// * `high_hz` - 低通的截止頻率；帶通 / 帶阻的上邊界
+    /// * `window_type` - 窗函數類型 (與 SpectrogramEngine 相同)
+    /// * `alpha` - 窗函數參數 (blackman 使用)
+    pub fn windowed_sinc(
+        band: BandType,
+        num_taps: usize,
+        sample_rate: f32,
+        low_hz: f32,
+        high_hz: f32,
+        window_type: &str,
+        alpha: f32,
+    ) -> Result<FirFilter, FirError> {
+        let edges = band_edges(band, sample_rate, low_hz, high_hz)?;
+        check_num_taps(band, num_taps, MAX_TAPS)?;
+
+        let fs = sample_rate as f64;
+        let cutoffs: Vec<f64> = edges.iter().map(|&f| f as f64 / fs).collect();
+        let center = (num_taps - 1) as f64 / 2.0;
+        let window = create_window(window_type, num_taps, alpha);
+
+        let mut taps: Vec<f64> = (0..num_taps)
+            .map(|i| {
+                let n = i as f64;
+                let ideal = match band {
+                    BandType::Lowpass => ideal_lowpass(cutoffs[0], n, center),
+                    BandType::Highpass => ideal_lowpass(0.5, n, center) - ideal_lowpass(cutoffs[0], n, center),
+                    BandType::Bandpass => {
+                        ideal_lowpass(cutoffs[1], n, center) - ideal_lowpass(cutoffs[0], n, center)
+                    }
+                    BandType::Bandstop => {
+                        ideal_lowpass(0.5, n, center) - ideal_lowpass(cutoffs[1], n, center)
+                            + ideal_lowpass(cutoffs[0], n, center)
+                    }
+                };
+                ideal * window[i] as f64
+            })
+            .collect();
+
+        let reference = match band {
+            BandType::Lowpass | BandType::Bandstop => 0.0,
+            BandType::Highpass => 0.5,
+            BandType::Bandpass => (cutoffs[0] + cutoffs[1]) / 2.0,
+        };
+        let gain = taps_response(&taps, 2.0 * PI * reference).norm();
+        if gain > 0.0 {
+            taps.iter_mut().for_each(|h| *h /= gain);
+        }
+
+        Ok(FirFilter { taps, sample_rate: fs, spec: None })
+    }
+
+    /// Parks-McClellan 等波紋設計
+    ///
+    /// 截止頻率位於過渡帶中央: 通帶止於 cutoff - transition / 2，阻帶始於 cutoff + transition / 2
+    /// (高通與帶通方向相反)。阻帶權重取 δp / δs，使兩者的波紋比例符合要求。
+    ///
+    /// # Arguments
+    /// * `band` - 頻帶類型
+    /// * `num_taps` - 抽頭數 (最多 1025；高通 / 帶阻必須為奇數)
+    /// * `sample_rate` - 採樣率 (Hz)
+    /// * `low_hz` - 高通的截止頻率；帶通 / 帶阻的下邊界
+    /// * `high_hz` - 低通的截止頻率；帶通 / 帶阻的上邊界
+    /// * `transition_hz` - 過渡帶寬度 (Hz)
+    /// * `ripple_db` - 通帶波紋 (dB)
+    /// * `attenuation_db` - 阻帶衰減 (dB)
+    #[allow(clippy::too_many_arguments)]
+    pub fn equiripple(
+        band: BandType,
+        num_taps: usize,
+        sample_rate: f32,
+        low_hz: f32,
+        high_hz: f32,
+        transition_hz: f32,
+        ripple_db: f32,
+        attenuation_db: f32,
+    ) -> Result<FirFilter, FirError> {
+        let edges = band_edges(band, sample_rate, low_hz, high_hz)?;
+        check_num_taps(band, num_taps, MAX_EQUIRIPPLE_TAPS)?;
+        if ripple_db.is_nan() || ripple_db <= 0.0 {
+            return Err(FilterError::InvalidRipple { ripple_db }.into());
+        }
+        if attenuation_db.is_nan() || attenuation_db <= 0.0 {
+            return Err(FilterError::InvalidAttenuation { attenuation_db }.into());
+        }
+
+        let fs = sample_rate as f64;
+        let half = transition_hz as f64 / fs / 2.0;
+        let cutoffs: Vec<f64> = edges.iter().map(|&f| f as f64 / fs).collect();
+        let passband_ripple = 10f64.powf(ripple_db as f64 / 20.0);
+        let delta_pass = (passband_ripple - 1.0) / (passband_ripple + 1.0);
+        let delta_stop = 10f64.powf(-attenuation_db as f64 / 20.0);
+        let pass = |start: f64, end: f64| RemezBand { start, end, desired: 1.0, weight: 1.0 };
+        let stop = |start: f64, end: f64| RemezBand { start, end, desired: 0.0, weight: delta_pass / delta_stop };
+
+        let bands = match band {
+            BandType::Lowpass => vec![pass(0.0, cutoffs[0] - half), stop(cutoffs[0] + half, 0.5)],
+            BandType::Highpass => vec![stop(0.0, cutoffs[0] - half), pass(cutoffs[0] + half, 0.5)],
+            BandType::Bandpass => vec![
+                stop(0.0, cutoffs[0] - half),
+                pass(cutoffs[0] + half, cutoffs[1] - half),
+                stop(cutoffs[1] + half, 0.5),
+            ],
+            BandType::Bandstop => vec![
+                pass(0.0, cutoffs[0] - half),
+                stop(cutoffs[0] + half, cutoffs[1] - half),
+                pass(cutoffs[1] + half, 0.5),
+            ],
+        };
+        if transition_hz.is_nan() || half <= 0.0 || bands.iter().any(|b| b.start >= b.end) {
+            return Err(FirError::InvalidTransition { transition_hz });
+        }
+
+        // Remez 迭代可能未收斂或抽頭數不足以達到指標，設計後按實際響應測量
+        let taps = remez(num_taps, &bands);
+        let achieved = measure_bands(&taps, &bands);
+        Ok(FirFilter {
+            taps,
+            sample_rate: fs,
+            spec: Some(((ripple_db as f64, attenuation_db as f64), achieved)),
+        })
+    }
+
+    /// 群延遲 (樣本數)
+    pub fn group_delay(&self) -> f64 {
+        (self.taps.len() - 1) as f64 / 2.0
+    }
+
+    /// 因果濾波: 輸出與輸入等長，延遲 group_delay() 個樣本
+    pub fn apply(&self, input: &[f32]) -> Vec<f32> {
+        convolve(&self.taps, input, input.len())
+            .into_iter()
+            .map(|y| y as f32)
+            .collect()
+    }
+
+    /// 延遲補償濾波: 去掉 (N - 1) / 2 個樣本的群延遲，使輸出與輸入在時間上對齊
+    ///
+    /// 奇數抽頭時完全對齊；偶數抽頭時剩餘半個樣本的延遲。
+    pub fn apply_aligned(&self, input: &[f32]) -> Vec<f32> {
+        let delay = (self.taps.len() - 1) / 2;
+        convolve(&self.taps, input, input.len() + delay)
+            .into_iter()
+            .skip(delay)
+            .map(|y| y as f32)
+            .collect()
+    }
+
+    /// 在指定頻率 (Hz) 處的複數頻率響應
+    pub fn response(&self, freq_hz: f32) -> Complex<f64> {
+        taps_response(&self.taps, 2.0 * PI * freq_hz as f64 / self.sample_rate)
+    }
+
+    /// 等波紋設計實際達到的 (通帶波紋 dB, 阻帶衰減 dB)；窗函數法設計返回 None
+    pub fn achieved(&self) -> Option<(f64, f64)> {
+        self.spec.map(|(_, achieved)| achieved)
+    }
+
+    /// 是否達到設計要求的通帶波紋與阻帶衰減 (窗函數法設計沒有指標，總是 true)
+    pub fn meets_spec(&self) -> bool {
+        self.spec.is_none_or(|((ripple_db, attenuation_db), (achieved_ripple, achieved_attenuation))| {
+            achieved_ripple <= ripple_db + SPEC_TOLERANCE_DB
+                && achieved_attenuation >= attenuation_db - SPEC_TOLERANCE_DB
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl FirFilter {
+    /// 抽頭數
+    #[wasm_bindgen]
+    pub fn get_num_taps(&self) -> usize {
+        self.taps.len()
+    }
+
+    /// 濾波器係數 (對稱)
+    #[wasm_bindgen]
+    pub fn get_taps(&self) -> Vec<f64> {
+        self.taps.clone()
+    }
+
+    /// 設計時使用的採樣率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_sample_rate(&self) -> f32 {
+        self.sample_rate as f32
+    }
+
+    /// 群延遲 (樣本數，(N - 1) / 2)
+    #[wasm_bindgen]
+    pub fn get_group_delay(&self) -> f32 {
+        self.group_delay() as f32
+    }
+
+    /// 等波紋設計實際的通帶波紋 (dB)；窗函數法設計返回 undefined
+    #[wasm_bindgen]
+    pub fn get_achieved_ripple_db(&self) -> Option<f32> {
+        self.achieved().map(|(ripple_db, _)| ripple_db as f32)
+    }
+
+    /// 等波紋設計實際的阻帶衰減 (dB)；窗函數法設計返回 undefined
+    #[wasm_bindgen]
+    pub fn get_achieved_attenuation_db(&self) -> Option<f32> {
+        self.achieved().map(|(_, attenuation_db)| attenuation_db as f32)
+    }
+
+    /// 是否達到要求的波紋與衰減 (false 時應增加抽頭數或放寬過渡帶)
+    #[wasm_bindgen]
+    pub fn get_meets_spec(&self) -> bool {
+        self.meets_spec()
+    }
+
+    /// 因果濾波
+    ///
+    /// # Arguments
+    /// * `input` - 輸入信號 (Float32Array)
+    ///
+    /// # Returns
+    /// 濾波後的信號 (與輸入等長，延遲 (N - 1) / 2 個樣本)
+    #[wasm_bindgen]
+    pub fn filter(&self, input: &[f32]) -> Vec<f32> {
+        self.apply(input)
+    }
+
+    /// 延遲補償濾波 (用於測量叫聲起止時間)
+    ///
+    /// # Arguments
+    /// * `input` - 輸入信號 (Float32Array)
+    ///
+    /// # Returns
+    /// 濾波後的信號 (與輸入等長且在時間上對齊)
+    #[wasm_bindgen]
+    pub fn filter_aligned(&self, input: &[f32]) -> Vec<f32> {
+        self.apply_aligned(input)
+    }
+
+    /// 幅度響應
+    ///
+    /// # Arguments
+    /// * `frequencies_hz` - 要計算的頻率 (Hz)
+    ///
+    /// # Returns
+    /// 每個頻率的增益 (dB)
+    #[wasm_bindgen]
+    pub fn get_magnitude_response_db(&self, frequencies_hz: &[f32]) -> Vec<f32> {
+        frequencies_hz
+            .iter()
+            .map(|&f| (20.0 * self.response(f).norm().max(1e-300).log10()) as f32)
+            .collect()
+    }
+}
+
+/// 以窗函數法設計 FIR 濾波器 (JavaScript 接口)
+///
+/// # Arguments
+/// * `band_type` - "lowpass"、"highpass"、"bandpass" 或 "bandstop" ("notch")
+/// * `num_taps` - 抽頭數 (3-8191；高通 / 帶阻必須為奇數)
+/// * `sample_rate` - 採樣率 (Hz)
+/// * `low_hz` - 高通截止頻率；帶通 / 帶阻的下邊界 (低通時忽略)
+/// * `high_hz` - 低通截止頻率；帶通 / 帶阻的上邊界 (高通時忽略)
+/// * `window_type` - 窗函數類型 (hann, hamming, blackman, gauss, rectangular, triangular 等)
+///
+/// # Returns
+/// FirFilter 對象；參數無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+pub fn design_fir_filter(
+    band_type: &str,
+    num_taps: usize,
+    sample_rate: f32,
+    low_hz: f32,
+    high_hz: f32,
+    window_type: &str,
+) -> Result<FirFilter, JsError> {
+    let band = BandType::from_name(band_type).ok_or_else(|| FilterError::UnknownBandType(band_type.to_string()))?;
+    Ok(FirFilter::windowed_sinc(band, num_taps, sample_rate, low_hz, high_hz, window_type, 0.16)?)
+}
+
+/// 以 Parks-McClellan 算法設計等波紋 FIR 濾波器 (JavaScript 接口)
+///
+/// # Arguments
+/// * `band_type` - "lowpass"、"highpass"、"bandpass" 或 "bandstop" ("notch")
+/// * `num_taps` - 抽頭數 (3-1025；高通 / 帶阻必須為奇數)；更長的濾波器請用 design_fir_filter
+/// * `sample_rate` - 採樣率 (Hz)
+/// * `low_hz` - 高通截止頻率；帶通 / 帶阻的下邊界 (低通時忽略)
+/// * `high_hz` - 低通截止頻率；帶通 / 帶阻的上邊界 (高通時忽略)
+/// * `transition_hz` - 過渡帶寬度 (Hz，以截止頻率為中心)
+/// * `ripple_db` - 通帶波紋 (dB，典型值: 0.5)
+/// * `attenuation_db` - 阻帶衰減 (dB，典型值: 60)；僅決定通帶與阻帶的誤差權重，
+///   實際衰減取決於抽頭數
+///
+/// # Returns
+/// FirFilter 對象；參數無效時拋出帶有錯誤說明的 Error。
+/// 抽頭數不足時仍返回濾波器，實際指標見 get_achieved_ripple_db / get_achieved_attenuation_db / get_meets_spec
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn design_equiripple_filter(
+    band_type: &str,
+    num_taps: usize,
+    sample_rate: f32,
+    low_hz: f32,
+    high_hz: f32,
+    transition_hz: f32,
+    ripple_db: f32,
+    attenuation_db: f32,
+) -> Result<FirFilter, JsError> {
+    let band = BandType::from_name(band_type).ok_or_else(|| FilterError::UnknownBandType(band_type.to_string()))?;
+    Ok(FirFilter::equiripple(
+        band,
+        num_taps,
+        sample_rate,
+        low_hz,
+        high_hz,
+        transition_hz,
+        ripple_db,
+        attenuation_db,
+    )?)
+}
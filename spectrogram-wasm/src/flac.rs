@@ -0,0 +1,1088 @@
+// ============================================================
+// FLAC 解碼與編碼
+// 解碼使用 claxon (純 Rust)，輸出與 WAV 路徑相同的去交錯 f32 通道；
+// 元數據塊 (STREAMINFO、VORBIS_COMMENT、APPLICATION 等) 自行解析，
+// 包括 `flac --keep-foreign-metadata` 保存的 RIFF 塊中的 GUANO。
+// 編碼器使用固定預測器 (0-4 階)、分區 Rice 編碼與立體聲去相關，
+// 用於導出剪輯；MD5 簽名寫為全 0 (規範中表示「未計算」)。
+// ============================================================
+
+use std::fmt;
+use std::io::Cursor;
+
+use wasm_bindgen::prelude::*;
+
+use crate::guano::{GuanoMetadata, GUANO_CHUNK_ID};
+use crate::wav::{WavAudio, WavError};
+
+/// 元數據塊類型
+pub const FLAC_STREAMINFO: u8 = 0;
+pub const FLAC_PADDING: u8 = 1;
+pub const FLAC_APPLICATION: u8 = 2;
+pub const FLAC_SEEKTABLE: u8 = 3;
+pub const FLAC_VORBIS_COMMENT: u8 = 4;
+pub const FLAC_CUESHEET: u8 = 5;
+pub const FLAC_PICTURE: u8 = 6;
+
+/// 編碼器每幀的樣本數
+const ENCODER_BLOCK_SIZE: usize = 4096;
+/// Rice 分區階數上限
+const MAX_PARTITION_ORDER: u32 = 8;
+/// 寫入 VORBIS_COMMENT 的編碼器名稱
+const ENCODER_VENDOR: &str = concat!("spectrogram-wasm ", env!("CARGO_PKG_VERSION"));
+/// 保存 GUANO 文本的 Vorbis 注釋鍵
+pub const GUANO_COMMENT_KEY: &str = "GUANO";
+
+/// FLAC 解析 / 編碼錯誤
+#[derive(Clone, Debug, PartialEq)]
+pub enum FlacError {
+    /// 文件不以 fLaC 標記開頭 (可選 ID3v2 標籤之後)
+    NotFlac,
+    /// 第一個元數據塊不是 STREAMINFO
+    MissingStreamInfo,
+    /// 元數據塊被截斷
+    Truncated { block: String },
+    /// 音頻幀解碼失敗
+    Decode(String),
+    /// 編碼器不支持的位深
+    UnsupportedBitDepth { bits_per_sample: u32 },
+    /// 編碼器的採樣率超出 STREAMINFO 範圍
+    InvalidSampleRate { sample_rate: u32 },
+    /// 通道數為 0、超過 8 或各通道長度不同
+    InvalidChannels { reason: String },
+    /// 輸入 WAV 無效
+    Wav(WavError),
+}
+
+impl fmt::Display for FlacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlacError::NotFlac => write!(f, "not a FLAC file"),
+            FlacError::MissingStreamInfo => write!(f, "first metadata block is not STREAMINFO"),
+            FlacError::Truncated { block } => write!(f, "FLAC file is truncated inside the {} block", block),
+            FlacError::Decode(reason) => write!(f, "FLAC decoding failed: {}", reason),
+            FlacError::UnsupportedBitDepth { bits_per_sample } => {
+                write!(f, "unsupported bit depth {} (expected 8, 12, 16, 20 or 24)", bits_per_sample)
+            }
+            FlacError::InvalidSampleRate { sample_rate } => {
+                write!(f, "sample rate {} Hz is outside 1..=655350", sample_rate)
+            }
+            FlacError::InvalidChannels { reason } => write!(f, "invalid channels: {}", reason),
+            FlacError::Wav(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for FlacError {}
+
+impl From<claxon::Error> for FlacError {
+    fn from(err: claxon::Error) -> Self {
+        FlacError::Decode(err.to_string())
+    }
+}
+
+impl From<WavError> for FlacError {
+    fn from(err: WavError) -> Self {
+        FlacError::Wav(err)
+    }
+}
+
+/// 元數據塊類型名稱
+fn block_type_name(block_type: u8) -> &'static str {
+    match block_type {
+        FLAC_STREAMINFO => "STREAMINFO",
+        FLAC_PADDING => "PADDING",
+        FLAC_APPLICATION => "APPLICATION",
+        FLAC_SEEKTABLE => "SEEKTABLE",
+        FLAC_VORBIS_COMMENT => "VORBIS_COMMENT",
+        FLAC_CUESHEET => "CUESHEET",
+        FLAC_PICTURE => "PICTURE",
+        _ => "RESERVED",
+    }
+}
+
+/// 文件中的一個元數據塊
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlacBlock {
+    pub block_type: u8,
+    /// 塊數據在文件中的起始偏移 (不含 4 字節塊頭)
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl FlacBlock {
+    /// 塊類型名稱 (如 `VORBIS_COMMENT`)
+    pub fn type_name(&self) -> &'static str {
+        block_type_name(self.block_type)
+    }
+
+    /// 塊數據
+    pub fn data<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        &bytes[self.offset as usize..(self.offset + self.size) as usize]
+    }
+}
+
+/// 跳過文件開頭的 ID3v2 標籤，返回 fLaC 標記的偏移
+fn stream_start(bytes: &[u8]) -> usize {
+    if bytes.len() < 10 || &bytes[0..3] != b"ID3" {
+        return 0;
+    }
+    // 標籤大小為 4 個 7 位的 syncsafe 字節；標誌位 0x10 表示有 10 字節的尾部
+    let size = bytes[6..10].iter().fold(0usize, |acc, &b| (acc << 7) | (b & 0x7f) as usize);
+    let footer = if bytes[5] & 0x10 != 0 { 10 } else { 0 };
+    (10 + size + footer).min(bytes.len())
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(offset..offset + 4)?.try_into().unwrap()))
+}
+
+/// 解析 VORBIS_COMMENT 塊；格式錯誤時只返回已讀到的部分
+fn parse_vorbis_comment(data: &[u8]) -> (String, Vec<(String, String)>) {
+    let mut comments = Vec::new();
+    let Some(vendor_len) = read_u32_le(data, 0) else {
+        return (String::new(), comments);
+    };
+    let mut offset = 4 + vendor_len as usize;
+    let vendor = data.get(4..offset).map(|v| String::from_utf8_lossy(v).into_owned()).unwrap_or_default();
+    let count = read_u32_le(data, offset).unwrap_or(0);
+    offset += 4;
+    for _ in 0..count {
+        let Some(len) = read_u32_le(data, offset) else {
+            break;
+        };
+        let Some(entry) = data.get(offset + 4..offset + 4 + len as usize) else {
+            break;
+        };
+        offset += 4 + len as usize;
+        let entry = String::from_utf8_lossy(entry);
+        if let Some((key, value)) = entry.split_once('=') {
+            comments.push((key.to_string(), value.to_string()));
+        }
+    }
+    (vendor, comments)
+}
+
+/// FlacInfo: FLAC 文件的流信息與元數據 (不解碼音頻幀)
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct FlacInfo {
+    sample_rate: u32,
+    channels: u32,
+    bits_per_sample: u32,
+    /// 總幀數 (每通道樣本數)；STREAMINFO 中為 0 表示未知
+    num_frames: u64,
+    min_block_size: u16,
+    max_block_size: u16,
+    md5: [u8; 16],
+    blocks: Vec<FlacBlock>,
+    vendor: String,
+    comments: Vec<(String, String)>,
+    /// 由 APPLICATION `riff` 塊還原的原始 WAV 塊 (ID, 數據)
+    foreign_chunks: Vec<([u8; 4], Vec<u8>)>,
+    audio_offset: u64,
+}
+
+impl FlacInfo {
+    /// 解析 fLaC 標記與所有元數據塊
+    pub fn parse(bytes: &[u8]) -> Result<FlacInfo, FlacError> {
+        let start = stream_start(bytes);
+        if bytes.get(start..start + 4) != Some(b"fLaC".as_slice()) {
+            return Err(FlacError::NotFlac);
+        }
+
+        let mut blocks = Vec::new();
+        let mut offset = start + 4;
+        loop {
+            let Some(header) = bytes.get(offset..offset + 4) else {
+                return Err(FlacError::Truncated { block: "metadata header".to_string() });
+            };
+            let last = header[0] & 0x80 != 0;
+            let block_type = header[0] & 0x7f;
+            let size = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+            if offset + 4 + size > bytes.len() {
+                return Err(FlacError::Truncated { block: block_type_name(block_type).to_string() });
+            }
+            blocks.push(FlacBlock { block_type, offset: offset as u64 + 4, size: size as u64 });
+            offset += 4 + size;
+            if last {
+                break;
+            }
+        }
+
+        let streaminfo = blocks
+            .first()
+            .filter(|b| b.block_type == FLAC_STREAMINFO && b.size >= 34)
+            .ok_or(FlacError::MissingStreamInfo)?
+            .data(bytes);
+        // 20 位採樣率、3 位 (通道數 - 1)、5 位 (位深 - 1)、36 位總樣本數
+        let packed = u64::from_be_bytes(streaminfo[10..18].try_into().unwrap());
+
+        let mut vendor = String::new();
+        let mut comments = Vec::new();
+        let mut foreign_chunks = Vec::new();
+        for block in &blocks {
+            let data = block.data(bytes);
+            match block.block_type {
+                FLAC_VORBIS_COMMENT => (vendor, comments) = parse_vorbis_comment(data),
+                FLAC_APPLICATION if data.len() >= 12 && &data[0..4] == b"riff" => {
+                    // 第一個 riff 塊是 RIFF/WAVE 文件頭，其餘每塊保存一個完整的 WAV 塊
+                    let chunk = &data[4..];
+                    if !matches!(&chunk[0..4], b"RIFF" | b"RF64") {
+                        foreign_chunks.push((chunk[0..4].try_into().unwrap(), chunk[8..].to_vec()));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(FlacInfo {
+            sample_rate: (packed >> 44) as u32,
+            channels: ((packed >> 41) & 0x7) as u32 + 1,
+            bits_per_sample: ((packed >> 36) & 0x1f) as u32 + 1,
+            num_frames: packed & 0xf_ffff_ffff,
+            min_block_size: u16::from_be_bytes([streaminfo[0], streaminfo[1]]),
+            max_block_size: u16::from_be_bytes([streaminfo[2], streaminfo[3]]),
+            md5: streaminfo[18..34].try_into().unwrap(),
+            blocks,
+            vendor,
+            comments,
+            foreign_chunks,
+            audio_offset: offset as u64,
+        })
+    }
+
+    /// 所有元數據塊 (按文件順序)
+    pub fn blocks(&self) -> &[FlacBlock] {
+        &self.blocks
+    }
+
+    /// Vorbis 注釋 (鍵, 值)，按文件順序
+    pub fn comments(&self) -> &[(String, String)] {
+        &self.comments
+    }
+
+    /// 第一個鍵為 `key` 的 Vorbis 注釋 (不區分大小寫)
+    pub fn comment(&self, key: &str) -> Option<&str> {
+        self.comments.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v.as_str())
+    }
+
+    /// 由 APPLICATION `riff` 塊還原的第一個 ID 為 `id` 的 WAV 塊
+    pub fn foreign_chunk(&self, id: &[u8; 4]) -> Option<&[u8]> {
+        self.foreign_chunks.iter().find(|(chunk_id, _)| chunk_id == id).map(|(_, data)| data.as_slice())
+    }
+
+    /// 每通道樣本數；未知時為 0
+    pub fn num_frames(&self) -> u64 {
+        self.num_frames
+    }
+
+    /// GUANO 元數據：優先取保存的 `guan` WAV 塊，其次取 `GUANO` Vorbis 注釋
+    pub fn guano(&self) -> Option<GuanoMetadata> {
+        if let Some(chunk) = self.foreign_chunk(GUANO_CHUNK_ID) {
+            if let Ok(guano) = GuanoMetadata::parse(chunk) {
+                return Some(guano);
+            }
+        }
+        GuanoMetadata::parse_text(self.comment(GUANO_COMMENT_KEY)?).ok()
+    }
+}
+
+#[wasm_bindgen]
+impl FlacInfo {
+    /// 採樣率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// 通道數
+    #[wasm_bindgen]
+    pub fn get_num_channels(&self) -> u32 {
+        self.channels
+    }
+
+    /// 每個樣本的位數
+    #[wasm_bindgen]
+    pub fn get_bits_per_sample(&self) -> u32 {
+        self.bits_per_sample
+    }
+
+    /// 每通道樣本數 (STREAMINFO 中為 0 表示未知)
+    #[wasm_bindgen]
+    pub fn get_num_frames(&self) -> f64 {
+        self.num_frames as f64
+    }
+
+    /// 時長 (秒)；未知時為 0
+    #[wasm_bindgen]
+    pub fn get_duration(&self) -> f64 {
+        self.num_frames as f64 / self.sample_rate.max(1) as f64
+    }
+
+    /// 最小 / 最大塊大小 (樣本)
+    #[wasm_bindgen]
+    pub fn get_min_block_size(&self) -> u16 {
+        self.min_block_size
+    }
+
+    #[wasm_bindgen]
+    pub fn get_max_block_size(&self) -> u16 {
+        self.max_block_size
+    }
+
+    /// 未編碼音頻的 MD5 簽名 (十六進制)；全 0 表示未計算
+    #[wasm_bindgen]
+    pub fn get_md5_hex(&self) -> String {
+        self.md5.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// 編碼器名稱 (VORBIS_COMMENT 中的 vendor 字符串)
+    #[wasm_bindgen]
+    pub fn get_vendor(&self) -> String {
+        self.vendor.clone()
+    }
+
+    /// 所有 Vorbis 注釋的鍵 (按文件順序，可重複)
+    #[wasm_bindgen]
+    pub fn get_comment_keys(&self) -> Vec<String> {
+        self.comments.iter().map(|(k, _)| k.clone()).collect()
+    }
+
+    /// 所有 Vorbis 注釋的值 (與 get_comment_keys 一一對應)
+    #[wasm_bindgen]
+    pub fn get_comment_values(&self) -> Vec<String> {
+        self.comments.iter().map(|(_, v)| v.clone()).collect()
+    }
+
+    /// 第一個鍵為 `key` 的注釋 (不區分大小寫)；不存在時返回 undefined
+    #[wasm_bindgen]
+    pub fn get_comment(&self, key: &str) -> Option<String> {
+        self.comment(key).map(str::to_string)
+    }
+
+    /// 所有元數據塊的類型 (0 = STREAMINFO, 4 = VORBIS_COMMENT, ...)
+    #[wasm_bindgen]
+    pub fn get_block_types(&self) -> Vec<u8> {
+        self.blocks.iter().map(|b| b.block_type).collect()
+    }
+
+    /// 所有元數據塊數據的文件偏移
+    #[wasm_bindgen]
+    pub fn get_block_offsets(&self) -> Vec<f64> {
+        self.blocks.iter().map(|b| b.offset as f64).collect()
+    }
+
+    /// 所有元數據塊的大小 (字節)
+    #[wasm_bindgen]
+    pub fn get_block_sizes(&self) -> Vec<f64> {
+        self.blocks.iter().map(|b| b.size as f64).collect()
+    }
+
+    /// 第一個音頻幀的文件偏移
+    #[wasm_bindgen]
+    pub fn get_audio_offset(&self) -> f64 {
+        self.audio_offset as f64
+    }
+
+    /// GUANO 元數據；沒有時返回 undefined
+    #[wasm_bindgen]
+    pub fn get_guano(&self) -> Option<GuanoMetadata> {
+        self.guano()
+    }
+}
+
+/// FlacAudio: 解碼後的 FLAC 文件
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct FlacAudio {
+    info: FlacInfo,
+    channels: Vec<Vec<f32>>,
+    truncated: bool,
+}
+
+impl FlacAudio {
+    /// 解析元數據並解碼所有音頻幀
+    ///
+    /// 文件在幀中間結束時保留已解碼的完整幀並標記為截斷；
+    /// 其他解碼錯誤 (如 CRC 不匹配) 返回錯誤。
+    pub fn decode(bytes: &[u8]) -> Result<FlacAudio, FlacError> {
+        let info = FlacInfo::parse(bytes)?;
+        let mut reader = claxon::FlacReader::new(Cursor::new(&bytes[stream_start(bytes)..]))?;
+        let scale = 1.0 / (1u64 << (info.bits_per_sample - 1)) as f32;
+        let capacity = info.num_frames.min(1 << 24) as usize;
+        let mut channels = vec![Vec::with_capacity(capacity); info.channels as usize];
+
+        let mut frames = reader.blocks();
+        let mut buffer = Vec::new();
+        let mut truncated = false;
+        loop {
+            match frames.read_next_or_eof(buffer) {
+                Ok(Some(block)) => {
+                    for (ch, channel) in channels.iter_mut().enumerate() {
+                        channel.extend(block.channel(ch as u32).iter().map(|&s| s as f32 * scale));
+                    }
+                    buffer = block.into_buffer();
+                }
+                Ok(None) => break,
+                Err(claxon::Error::IoError(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    truncated = true;
+                    break;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let decoded = channels.first().map_or(0, Vec::len) as u64;
+        truncated |= info.num_frames > 0 && decoded < info.num_frames;
+        Ok(FlacAudio { info, channels, truncated })
+    }
+
+    /// 流信息與元數據
+    pub fn info(&self) -> &FlacInfo {
+        &self.info
+    }
+
+    /// 去交錯的通道數據 (範圍 [-1, 1))
+    pub fn channels(&self) -> &[Vec<f32>] {
+        &self.channels
+    }
+}
+
+#[wasm_bindgen]
+impl FlacAudio {
+    /// 流信息與元數據
+    #[wasm_bindgen]
+    pub fn get_info(&self) -> FlacInfo {
+        self.info.clone()
+    }
+
+    /// 採樣率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_sample_rate(&self) -> u32 {
+        self.info.sample_rate
+    }
+
+    /// 通道數
+    #[wasm_bindgen]
+    pub fn get_num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// 實際解碼的每通道樣本數
+    #[wasm_bindgen]
+    pub fn get_num_frames(&self) -> usize {
+        self.channels.first().map_or(0, Vec::len)
+    }
+
+    /// 文件是否在音頻幀中間結束
+    #[wasm_bindgen]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// 第 `channel_idx` 個通道的樣本 (Float32Array，範圍 [-1, 1))；索引越界時返回空數組
+    #[wasm_bindgen]
+    pub fn get_channel(&self, channel_idx: usize) -> Vec<f32> {
+        self.channels.get(channel_idx).cloned().unwrap_or_default()
+    }
+}
+
+// ============================================================
+// 編碼器
+// ============================================================
+
+/// 按位寫入 (高位在前)
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter { bytes: Vec::new(), acc: 0, bits: 0 }
+    }
+
+    /// 寫入 `value` 的低 `bits` 位 (bits <= 32)
+    fn write(&mut self, value: u64, bits: u32) {
+        if bits == 0 {
+            return;
+        }
+        self.acc = (self.acc << bits) | (value & ((1u64 << bits) - 1));
+        self.bits += bits;
+        while self.bits >= 8 {
+            self.bits -= 8;
+            self.bytes.push((self.acc >> self.bits) as u8);
+        }
+    }
+
+    /// 寫入 `zeros` 個 0 與一個結束的 1
+    fn write_unary(&mut self, mut zeros: u64) {
+        while zeros >= 32 {
+            self.write(0, 32);
+            zeros -= 32;
+        }
+        self.write(1, zeros as u32 + 1);
+    }
+
+    /// 補 0 到字節邊界
+    fn align(&mut self) {
+        if self.bits > 0 {
+            self.write(0, 8 - self.bits);
+        }
+    }
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |mut crc, &b| {
+        crc ^= b;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |mut crc, &b| {
+        crc ^= (b as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// 幀頭中的採樣率編碼 (4 位) 與附加字段 (值, 位數)
+fn sample_rate_code(sample_rate: u32) -> (u64, Option<(u64, u32)>) {
+    match sample_rate {
+        88_200 => (1, None),
+        176_400 => (2, None),
+        192_000 => (3, None),
+        8_000 => (4, None),
+        16_000 => (5, None),
+        22_050 => (6, None),
+        24_000 => (7, None),
+        32_000 => (8, None),
+        44_100 => (9, None),
+        48_000 => (10, None),
+        96_000 => (11, None),
+        r if r.is_multiple_of(1000) && r / 1000 < 256 => (12, Some((r as u64 / 1000, 8))),
+        r if r < 65_536 => (13, Some((r as u64, 16))),
+        r if r.is_multiple_of(10) && r / 10 < 65_536 => (14, Some((r as u64 / 10, 16))),
+        // 從 STREAMINFO 讀取
+        _ => (0, None),
+    }
+}
+
+fn sample_size_code(bits_per_sample: u32) -> Option<u64> {
+    match bits_per_sample {
+        8 => Some(1),
+        12 => Some(2),
+        16 => Some(4),
+        20 => Some(5),
+        24 => Some(6),
+        _ => None,
+    }
+}
+
+/// 幀號的 UTF-8 式編碼 (最多 31 位)
+fn write_utf8_number(writer: &mut BitWriter, value: u32) {
+    if value < 0x80 {
+        writer.write(value as u64, 8);
+        return;
+    }
+    let continuation = match value {
+        0..=0x7ff => 1,
+        0x800..=0xffff => 2,
+        0x1_0000..=0x1f_ffff => 3,
+        0x20_0000..=0x3ff_ffff => 4,
+        _ => 5,
+    };
+    let lead_mask = (0xff00u32 >> (continuation + 1)) as u8;
+    writer.write((lead_mask | (value >> (6 * continuation)) as u8) as u64, 8);
+    for i in (0..continuation).rev() {
+        writer.write((0x80 | ((value >> (6 * i)) & 0x3f)) as u64, 8);
+    }
+}
+
+/// 固定預測器殘差 (0-4 階)
+fn fixed_residual(samples: &[i64], order: usize) -> Vec<i64> {
+    (order..samples.len())
+        .map(|i| {
+            let x = |k: usize| samples[i - k];
+            match order {
+                0 => x(0),
+                1 => x(0) - x(1),
+                2 => x(0) - 2 * x(1) + x(2),
+                3 => x(0) - 3 * x(1) + 3 * x(2) - x(3),
+                _ => x(0) - 4 * x(1) + 6 * x(2) - 4 * x(3) + x(4),
+            }
+        })
+        .collect()
+}
+
+/// 有符號殘差折疊為無符號 (0, -1, 1, -2, ... -> 0, 1, 2, 3, ...)
+fn fold(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// 一個分區的最佳 Rice 參數與位數
+fn best_rice_parameter(folded: &[u64], max_parameter: u32) -> (u32, u64) {
+    let sum: u64 = folded.iter().sum();
+    let mean = sum / folded.len().max(1) as u64;
+    let guess = if mean == 0 { 0 } else { (63 - mean.leading_zeros()).min(max_parameter) };
+    let cost = |k: u32| folded.iter().map(|&u| (u >> k) + 1 + k as u64).sum::<u64>();
+    (guess.saturating_sub(1)..=(guess + 1).min(max_parameter)).map(|k| (k, cost(k))).min_by_key(|&(_, c)| c).unwrap()
+}
+
+/// 分區 Rice 編碼方案
+struct RiceCoding {
+    /// false: 4 位參數 (0-14)；true: 5 位參數 (0-30)
+    wide: bool,
+    partition_order: u32,
+    parameters: Vec<u32>,
+    bits: u64,
+}
+
+/// 為殘差 (第一分區少 `order` 個樣本) 選擇位數最少的分區階數與參數
+fn plan_rice(residual: &[i64], block_size: usize, order: usize) -> RiceCoding {
+    let folded: Vec<u64> = residual.iter().map(|&r| fold(r)).collect();
+    let mut best: Option<RiceCoding> = None;
+    for partition_order in 0..=MAX_PARTITION_ORDER {
+        let partition_len = block_size >> partition_order;
+        if !block_size.is_multiple_of(1 << partition_order) || partition_len <= order {
+            break;
+        }
+        let mut parameters = Vec::new();
+        let mut bits = 0;
+        let mut start = 0;
+        for p in 0..(1usize << partition_order) {
+            let len = if p == 0 { partition_len - order } else { partition_len };
+            let (k, cost) = best_rice_parameter(&folded[start..start + len], 30);
+            parameters.push(k);
+            bits += cost;
+            start += len;
+        }
+        let wide = parameters.iter().any(|&k| k > 14);
+        bits += 6 + parameters.len() as u64 * if wide { 5 } else { 4 };
+        if best.as_ref().is_none_or(|b| bits < b.bits) {
+            best = Some(RiceCoding { wide, partition_order, parameters, bits });
+        }
+    }
+    best.unwrap()
+}
+
+/// 子幀編碼方式
+enum Subframe {
+    Constant,
+    Verbatim,
+    Fixed { order: usize, residual: Vec<i64>, rice: RiceCoding },
+}
+
+/// 選擇位數最少的子幀編碼，返回 (方式, 位數)
+fn plan_subframe(samples: &[i64], bits_per_sample: u32) -> (Subframe, u64) {
+    let header = 8;
+    if samples.iter().all(|&s| s == samples[0]) {
+        return (Subframe::Constant, header + bits_per_sample as u64);
+    }
+    let mut best = (Subframe::Verbatim, header + samples.len() as u64 * bits_per_sample as u64);
+    for order in 0..=4.min(samples.len() - 1) {
+        let residual = fixed_residual(samples, order);
+        let rice = plan_rice(&residual, samples.len(), order);
+        let bits = header + order as u64 * bits_per_sample as u64 + rice.bits;
+        if bits < best.1 {
+            best = (Subframe::Fixed { order, residual, rice }, bits);
+        }
+    }
+    best
+}
+
+fn write_subframe(writer: &mut BitWriter, samples: &[i64], bits_per_sample: u32, subframe: &Subframe) {
+    // 子幀頭：1 位填充、6 位類型、1 位 wasted bits 標誌
+    match subframe {
+        Subframe::Constant => {
+            writer.write(0b0000_0000, 8);
+            writer.write(samples[0] as u64, bits_per_sample);
+        }
+        Subframe::Verbatim => {
+            writer.write(0b0000_0010, 8);
+            for &s in samples {
+                writer.write(s as u64, bits_per_sample);
+            }
+        }
+        Subframe::Fixed { order, residual, rice } => {
+            writer.write((0b1000 | *order as u64) << 1, 8);
+            for &s in &samples[..*order] {
+                writer.write(s as u64, bits_per_sample);
+            }
+            writer.write(rice.wide as u64, 2);
+            writer.write(rice.partition_order as u64, 4);
+            let partition_len = samples.len() >> rice.partition_order;
+            let parameter_bits = if rice.wide { 5 } else { 4 };
+            let mut start = 0;
+            for (p, &k) in rice.parameters.iter().enumerate() {
+                let len = if p == 0 { partition_len - order } else { partition_len };
+                writer.write(k as u64, parameter_bits);
+                for &r in &residual[start..start + len] {
+                    let u = fold(r);
+                    writer.write_unary(u >> k);
+                    writer.write(u, k);
+                }
+                start += len;
+            }
+        }
+    }
+}
+
+/// 立體聲去相關方式 (幀頭通道分配編碼)
+const LEFT_SIDE: u64 = 8;
+const RIGHT_SIDE: u64 = 9;
+const MID_SIDE: u64 = 10;
+
+/// 編碼一幀，返回幀數據
+fn encode_frame(
+    channels: &[&[i64]],
+    frame_number: u32,
+    sample_rate: u32,
+    bits_per_sample: u32,
+    size_code: u64,
+) -> Vec<u8> {
+    let block_size = channels[0].len();
+
+    // 立體聲時比較獨立編碼與三種去相關方式 (side 通道多 1 位)
+    let mut assignment = channels.len() as u64 - 1;
+    let mut planned: Vec<(Vec<i64>, u32, Subframe)> = Vec::new();
+    if channels.len() == 2 {
+        let (left, right) = (channels[0], channels[1]);
+        let side: Vec<i64> = left.iter().zip(right).map(|(l, r)| l - r).collect();
+        let mid: Vec<i64> = left.iter().zip(right).map(|(l, r)| (l + r) >> 1).collect();
+        let (l_plan, l_bits) = plan_subframe(left, bits_per_sample);
+        let (r_plan, r_bits) = plan_subframe(right, bits_per_sample);
+        let (s_plan, s_bits) = plan_subframe(&side, bits_per_sample + 1);
+        let (m_plan, m_bits) = plan_subframe(&mid, bits_per_sample);
+        let costs = [
+            (1, l_bits + r_bits),
+            (LEFT_SIDE, l_bits + s_bits),
+            (RIGHT_SIDE, s_bits + r_bits),
+            (MID_SIDE, m_bits + s_bits),
+        ];
+        assignment = costs.iter().min_by_key(|&&(_, c)| c).unwrap().0;
+        planned = match assignment {
+            LEFT_SIDE => vec![(left.to_vec(), bits_per_sample, l_plan), (side, bits_per_sample + 1, s_plan)],
+            RIGHT_SIDE => vec![(side, bits_per_sample + 1, s_plan), (right.to_vec(), bits_per_sample, r_plan)],
+            MID_SIDE => vec![(mid, bits_per_sample, m_plan), (side, bits_per_sample + 1, s_plan)],
+            _ => vec![(left.to_vec(), bits_per_sample, l_plan), (right.to_vec(), bits_per_sample, r_plan)],
+        };
+    } else {
+        for channel in channels {
+            let (plan, _) = plan_subframe(channel, bits_per_sample);
+            planned.push((channel.to_vec(), bits_per_sample, plan));
+        }
+    }
+
+    let mut writer = BitWriter::new();
+    // 同步碼 + 固定塊大小策略
+    writer.write(0xfff8, 16);
+    let block_size_code = match block_size {
+        ENCODER_BLOCK_SIZE => 12,
+        1..=256 => 6,
+        _ => 7,
+    };
+    let (rate_code, rate_extra) = sample_rate_code(sample_rate);
+    writer.write(block_size_code, 4);
+    writer.write(rate_code, 4);
+    writer.write(assignment, 4);
+    writer.write(size_code, 3);
+    writer.write(0, 1);
+    write_utf8_number(&mut writer, frame_number);
+    match block_size_code {
+        6 => writer.write(block_size as u64 - 1, 8),
+        7 => writer.write(block_size as u64 - 1, 16),
+        _ => {}
+    }
+    if let Some((value, bits)) = rate_extra {
+        writer.write(value, bits);
+    }
+    let header_crc = crc8(&writer.bytes);
+    writer.write(header_crc as u64, 8);
+
+    for (samples, bits, plan) in &planned {
+        write_subframe(&mut writer, samples, *bits, plan);
+    }
+    writer.align();
+    let frame_crc = crc16(&writer.bytes);
+    writer.write(frame_crc as u64, 16);
+    writer.bytes
+}
+
+/// 寫入一個元數據塊 (塊頭 + 數據)
+fn push_block(output: &mut Vec<u8>, block_type: u8, last: bool, data: &[u8]) {
+    output.push(block_type | if last { 0x80 } else { 0 });
+    output.extend_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+    output.extend_from_slice(data);
+}
+
+/// 編碼整數樣本為 FLAC 文件
+///
+/// # Arguments
+/// * `channels` - 各通道樣本 (有符號整數，範圍為 `bits_per_sample` 位)
+/// * `sample_rate` - 採樣率 (Hz，1 - 655350)
+/// * `bits_per_sample` - 8、12、16、20 或 24
+/// * `comments` - 寫入 VORBIS_COMMENT 的 (鍵, 值)
+pub fn encode(
+    channels: &[Vec<i32>],
+    sample_rate: u32,
+    bits_per_sample: u32,
+    comments: &[(String, String)],
+) -> Result<Vec<u8>, FlacError> {
+    let size_code = sample_size_code(bits_per_sample).ok_or(FlacError::UnsupportedBitDepth { bits_per_sample })?;
+    if !(1..=655_350).contains(&sample_rate) {
+        return Err(FlacError::InvalidSampleRate { sample_rate });
+    }
+    if channels.is_empty() || channels.len() > 8 {
+        return Err(FlacError::InvalidChannels { reason: format!("{} channels (expected 1-8)", channels.len()) });
+    }
+    let num_frames = channels[0].len();
+    if channels.iter().any(|c| c.len() != num_frames) {
+        return Err(FlacError::InvalidChannels { reason: "channels have different lengths".to_string() });
+    }
+
+    let wide: Vec<Vec<i64>> = channels.iter().map(|c| c.iter().map(|&s| s as i64).collect()).collect();
+    let mut frames = Vec::new();
+    let (mut min_frame, mut max_frame) = (u32::MAX, 0u32);
+    for (frame_number, start) in (0..num_frames).step_by(ENCODER_BLOCK_SIZE).enumerate() {
+        let end = (start + ENCODER_BLOCK_SIZE).min(num_frames);
+        let slices: Vec<&[i64]> = wide.iter().map(|c| &c[start..end]).collect();
+        let frame = encode_frame(&slices, frame_number as u32, sample_rate, bits_per_sample, size_code);
+        min_frame = min_frame.min(frame.len() as u32);
+        max_frame = max_frame.max(frame.len() as u32);
+        frames.extend_from_slice(&frame);
+    }
+    if frames.is_empty() {
+        min_frame = 0;
+    }
+
+    let mut streaminfo = Vec::with_capacity(34);
+    streaminfo.extend_from_slice(&(ENCODER_BLOCK_SIZE as u16).to_be_bytes());
+    streaminfo.extend_from_slice(&(ENCODER_BLOCK_SIZE as u16).to_be_bytes());
+    streaminfo.extend_from_slice(&min_frame.to_be_bytes()[1..]);
+    streaminfo.extend_from_slice(&max_frame.to_be_bytes()[1..]);
+    let packed = (sample_rate as u64) << 44
+        | (channels.len() as u64 - 1) << 41
+        | (bits_per_sample as u64 - 1) << 36
+        | (num_frames as u64 & 0xf_ffff_ffff);
+    streaminfo.extend_from_slice(&packed.to_be_bytes());
+    streaminfo.extend_from_slice(&[0; 16]);
+
+    let mut vorbis = Vec::new();
+    vorbis.extend_from_slice(&(ENCODER_VENDOR.len() as u32).to_le_bytes());
+    vorbis.extend_from_slice(ENCODER_VENDOR.as_bytes());
+    vorbis.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for (key, value) in comments {
+        let entry = format!("{}={}", key, value);
+        vorbis.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+        vorbis.extend_from_slice(entry.as_bytes());
+    }
+
+    let mut output = Vec::with_capacity(frames.len() + vorbis.len() + 46);
+    output.extend_from_slice(b"fLaC");
+    push_block(&mut output, FLAC_STREAMINFO, false, &streaminfo);
+    push_block(&mut output, FLAC_VORBIS_COMMENT, true, &vorbis);
+    output.extend_from_slice(&frames);
+    Ok(output)
+}
+
+/// 將 [-1, 1) 範圍的 f32 樣本量化為 `bits_per_sample` 位整數 (四捨五入並限幅)
+fn quantize(samples: &[f32], bits_per_sample: u32) -> Vec<i32> {
+    let scale = (1u32 << (bits_per_sample - 1)) as f64;
+    let (min, max) = (-scale, scale - 1.0);
+    samples.iter().map(|&s| (s as f64 * scale).round().clamp(min, max) as i32).collect()
+}
+
+/// 將 WAV 文件轉為 FLAC
+///
+/// 8/16/24 位整數 PCM 無損保留；其他格式 (32 位整數、浮點) 量化為 24 位。
+/// GUANO 元數據保存為 `GUANO` Vorbis 注釋。
+pub fn encode_wav(bytes: &[u8]) -> Result<Vec<u8>, FlacError> {
+    let wav = WavAudio::decode(bytes)?;
+    let format = wav.info().format();
+    let bits_per_sample = match (format.sample_format, format.bits_per_sample) {
+        (crate::wav::SampleFormat::Int, bits @ (8 | 16 | 24)) => bits as u32,
+        _ => 24,
+    };
+    let channels: Vec<Vec<i32>> = wav.channels().iter().map(|c| quantize(c, bits_per_sample)).collect();
+    let comments: Vec<(String, String)> = GuanoMetadata::from_wav(bytes)
+        .ok()
+        .flatten()
+        .map(|guano| (GUANO_COMMENT_KEY.to_string(), guano.to_text()))
+        .into_iter()
+        .collect();
+    encode(&channels, format.sample_rate, bits_per_sample, &comments)
+}
+
+/// FlacEncoder: 逐個加入通道與注釋後編碼
+#[wasm_bindgen]
+pub struct FlacEncoder {
+    sample_rate: u32,
+    bits_per_sample: u32,
+    channels: Vec<Vec<f32>>,
+    comments: Vec<(String, String)>,
+}
+
+#[wasm_bindgen]
+impl FlacEncoder {
+    /// 創建編碼器
+    ///
+    /// # Arguments
+    /// * `sample_rate` - 採樣率 (Hz)
+    /// * `bits_per_sample` - 8、12、16、20 或 24
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: u32, bits_per_sample: u32) -> Result<FlacEncoder, JsError> {
+        sample_size_code(bits_per_sample).ok_or(FlacError::UnsupportedBitDepth { bits_per_sample })?;
+        if !(1..=655_350).contains(&sample_rate) {
+            return Err(FlacError::InvalidSampleRate { sample_rate }.into());
+        }
+        Ok(FlacEncoder { sample_rate, bits_per_sample, channels: Vec::new(), comments: Vec::new() })
+    }
+
+    /// 加入一個通道 (Float32Array，範圍 [-1, 1))
+    #[wasm_bindgen]
+    pub fn add_channel(&mut self, samples: &[f32]) {
+        self.channels.push(samples.to_vec());
+    }
+
+    /// 加入一條 Vorbis 注釋 (如 `TITLE`、`DATE`)
+    #[wasm_bindgen]
+    pub fn add_comment(&mut self, key: &str, value: &str) {
+        self.comments.push((key.to_string(), value.to_string()));
+    }
+
+    /// 以 `GUANO` Vorbis 注釋保存 GUANO 元數據 (替換之前設置的)
+    #[wasm_bindgen]
+    pub fn set_guano(&mut self, metadata: &GuanoMetadata) {
+        self.comments.retain(|(k, _)| !k.eq_ignore_ascii_case(GUANO_COMMENT_KEY));
+        self.comments.push((GUANO_COMMENT_KEY.to_string(), metadata.to_text()));
+    }
+
+    /// 編碼為 FLAC 文件 (Uint8Array)；沒有通道或通道長度不同時拋出 Error
+    #[wasm_bindgen]
+    pub fn encode(&self) -> Result<Vec<u8>, JsError> {
+        let channels: Vec<Vec<i32>> = self.channels.iter().map(|c| quantize(c, self.bits_per_sample)).collect();
+        Ok(encode(&channels, self.sample_rate, self.bits_per_sample, &self.comments)?)
+    }
+}
+
+/// 讀取 FLAC 流信息與元數據 (不解碼音頻)
+///
+/// # Arguments
+/// * `bytes` - 文件內容 (Uint8Array)；只需包含所有元數據塊的前綴
+///
+/// # Returns
+/// FlacInfo 對象；文件無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+pub fn read_flac_info(bytes: &[u8]) -> Result<FlacInfo, JsError> {
+    Ok(FlacInfo::parse(bytes)?)
+}
+
+/// 解碼 FLAC 文件
+///
+/// # Arguments
+/// * `bytes` - 完整的文件內容 (Uint8Array)
+///
+/// # Returns
+/// FlacAudio 對象 (可直接傳給 WaveformEngine.load_flac)；文件無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+pub fn decode_flac(bytes: &[u8]) -> Result<FlacAudio, JsError> {
+    Ok(FlacAudio::decode(bytes)?)
+}
+
+/// 將 WAV 文件轉為 FLAC (保留 GUANO 元數據)
+///
+/// # Arguments
+/// * `bytes` - WAV 文件內容 (Uint8Array)
+///
+/// # Returns
+/// FLAC 文件內容 (Uint8Array)
+#[wasm_bindgen]
+pub fn convert_wav_to_flac(bytes: &[u8]) -> Result<Vec<u8>, JsError> {
+    Ok(encode_wav(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 正弦 + 偽隨機噪聲，包括滿量程的正負極值
+    fn test_signal(bits_per_sample: u32, num_frames: usize, seed: u32, correlated_with: Option<&[i32]>) -> Vec<i32> {
+        let max = (1i64 << (bits_per_sample - 1)) - 1;
+        let min = -(1i64 << (bits_per_sample - 1));
+        let mut state = seed;
+        let mut samples: Vec<i32> = (0..num_frames)
+            .map(|i| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                let noise = (state >> 8) as f64 / (1u32 << 24) as f64 - 0.5;
+                let tone = (i as f64 * 0.05).sin() * 0.6 + noise * 0.1;
+                let value = match correlated_with {
+                    Some(other) => other[i] as f64 / max as f64 * 0.8 + noise * 0.05,
+                    None => tone,
+                };
+                ((value * max as f64).round() as i64).clamp(min, max) as i32
+            })
+            .collect();
+        samples[0] = max as i32;
+        samples[1] = min as i32;
+        samples
+    }
+
+    #[test]
+    fn encode_decode_round_trip_is_bit_exact() {
+        // 長度不是塊大小的整數倍，覆蓋最後一個短塊
+        let num_frames = 2 * 4096 + 1234;
+        for &bits_per_sample in &[8, 12, 16, 20, 24] {
+            for &sample_rate in &[44_100, 250_000, 384_000, 500_000, 96_001] {
+                for num_channels in 1..=2 {
+                    let first = test_signal(bits_per_sample, num_frames, 0x1234_5678, None);
+                    let mut channels = vec![first.clone()];
+                    if num_channels == 2 {
+                        channels.push(test_signal(bits_per_sample, num_frames, 0x8765_4321, Some(&first)));
+                    }
+                    let comments = vec![("TITLE".to_string(), "round trip".to_string())];
+                    let bytes = encode(&channels, sample_rate, bits_per_sample, &comments).unwrap();
+
+                    let decoded = FlacAudio::decode(&bytes).unwrap();
+                    let info = decoded.info();
+                    assert_eq!(info.get_sample_rate(), sample_rate);
+                    assert_eq!(info.get_bits_per_sample(), bits_per_sample);
+                    assert_eq!(info.comment("TITLE"), Some("round trip"));
+                    assert!(!decoded.is_truncated());
+
+                    let scale = (1u32 << (bits_per_sample - 1)) as f32;
+                    assert_eq!(decoded.channels().len(), num_channels);
+                    for (original, decoded) in channels.iter().zip(decoded.channels()) {
+                        let restored: Vec<i32> = decoded.iter().map(|&x| (x * scale) as i32).collect();
+                        assert_eq!(
+                            &restored, original,
+                            "{} bits, {} Hz, {} channels",
+                            bits_per_sample, sample_rate, num_channels
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn silent_and_constant_channels_round_trip() {
+        let channels = vec![vec![0; 5000], vec![-3; 5000]];
+        let bytes = encode(&channels, 48_000, 16, &[]).unwrap();
+        let decoded = FlacAudio::decode(&bytes).unwrap();
+        for (original, decoded) in channels.iter().zip(decoded.channels()) {
+            let restored: Vec<i32> = decoded.iter().map(|&x| (x * 32768.0) as i32).collect();
+            assert_eq!(&restored, original);
+        }
+    }
+}
@@ -0,0 +1,458 @@
+// ============================================================
+// GUANO 元數據 (Grand Unified Acoustic Notation Ontology)
+// 解析與序列化 WAV 中 `guan` 塊的 UTF-8 文本：
+// 每行一個 `Key: Value` 字段，廠商字段以 `命名空間|Key` 表示，
+// 多行值中的換行轉義為 `\n`。已知字段按規範類型解析
+// (浮點、整數、經緯度、標籤列表、帶 UTC 偏移的時間戳)。
+// ============================================================
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::timestamp::Timestamp;
+use crate::wav::{replace_chunk, WavError, WavInfo};
+
+/// GUANO 塊的 RIFF ID
+pub const GUANO_CHUNK_ID: &[u8; 4] = b"guan";
+
+/// 寫入時使用的規範版本
+const GUANO_VERSION: &str = "1.0";
+
+/// GUANO 解析錯誤
+#[derive(Clone, Debug, PartialEq)]
+pub enum GuanoError {
+    /// 文本中沒有 `GUANO|Version` 字段
+    MissingVersion,
+    /// WAV 文件無效
+    Wav(WavError),
+}
+
+impl fmt::Display for GuanoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuanoError::MissingVersion => write!(f, "GUANO metadata has no GUANO|Version field"),
+            GuanoError::Wav(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for GuanoError {}
+
+impl From<WavError> for GuanoError {
+    fn from(err: WavError) -> Self {
+        GuanoError::Wav(err)
+    }
+}
+
+/// 已知字段的值類型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FieldType {
+    Text,
+    Integer,
+    Float,
+    Position,
+    List,
+    Timestamp,
+}
+
+/// GUANO 1.0 規範中無命名空間字段的類型
+fn field_type(key: &str) -> FieldType {
+    match key {
+        "Filter HP" | "Filter LP" | "Humidity" | "Length" | "Loc Accuracy" | "Loc Elevation" | "Temperature Ext"
+        | "Temperature Int" => FieldType::Float,
+        "Samplerate" | "TE" => FieldType::Integer,
+        "Loc Position" => FieldType::Position,
+        "Tags" => FieldType::List,
+        "Timestamp" => FieldType::Timestamp,
+        _ => FieldType::Text,
+    }
+}
+
+/// 按類型解析後的字段值
+#[derive(Clone, Debug, PartialEq)]
+pub enum GuanoValue {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+    /// 緯度、經度 (十進制度，南緯 / 西經為負)
+    Position(f64, f64),
+    List(Vec<String>),
+    Timestamp(Timestamp),
+}
+
+/// 一個 GUANO 字段
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuanoField {
+    /// 廠商命名空間 (如 `WA`、`SB`)；規範字段為 None
+    pub namespace: Option<String>,
+    pub key: String,
+    /// 已還原換行的原始文本值
+    pub value: String,
+}
+
+impl GuanoField {
+    /// 帶命名空間的完整鍵 (`命名空間|Key`)
+    pub fn full_key(&self) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{}|{}", namespace, self.key),
+            None => self.key.clone(),
+        }
+    }
+}
+
+/// 拆分 `命名空間|Key`；命名空間只取第一個 `|` 之前的部分
+fn split_key(full_key: &str) -> (Option<&str>, &str) {
+    match full_key.split_once('|') {
+        Some((namespace, key)) => (Some(namespace.trim()), key.trim()),
+        None => (None, full_key.trim()),
+    }
+}
+
+/// 解析 `緯度 經度` (也接受逗號分隔)
+fn parse_position(value: &str) -> Option<(f64, f64)> {
+    let mut parts = value.split(|c: char| c == ',' || c.is_whitespace()).filter(|p| !p.is_empty());
+    let lat: f64 = parts.next()?.parse().ok()?;
+    let lon: f64 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return None;
+    }
+    Some((lat, lon))
+}
+
+/// GUANO 元數據
+///
+/// 字段按讀入順序保存；序列化時 `GUANO|Version` 總在第一行。
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuanoMetadata {
+    version: String,
+    fields: Vec<GuanoField>,
+}
+
+impl Default for GuanoMetadata {
+    fn default() -> Self {
+        GuanoMetadata { version: GUANO_VERSION.to_string(), fields: Vec::new() }
+    }
+}
+
+impl GuanoMetadata {
+    /// 解析 `guan` 塊數據
+    ///
+    /// 按 UTF-8 解碼，失敗時退回 Latin-1 (部分舊設備的寫法)；
+    /// 接受 CRLF 換行，忽略末尾的 NUL 填充、空行與沒有 `:` 的行。
+    /// 重複的鍵以最後一次出現為準。
+    pub fn parse(bytes: &[u8]) -> Result<GuanoMetadata, GuanoError> {
+        let text = match std::str::from_utf8(bytes) {
+            Ok(text) => text.to_string(),
+            Err(_) => bytes.iter().map(|&b| b as char).collect(),
+        };
+        GuanoMetadata::parse_text(&text)
+    }
+
+    /// 解析 GUANO 文本
+    pub fn parse_text(text: &str) -> Result<GuanoMetadata, GuanoError> {
+        let mut version = None;
+        let mut metadata = GuanoMetadata::default();
+        for line in text.trim_end_matches(['\0', ' ', '\t', '\r', '\n']).lines() {
+            let Some((full_key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (namespace, key) = split_key(full_key.trim_start_matches('\u{feff}'));
+            if key.is_empty() {
+                continue;
+            }
+            let value = value.trim().replace("\\n", "\n");
+            if namespace == Some("GUANO") && key == "Version" {
+                version = Some(value);
+            } else {
+                metadata.set_field(namespace, key, &value);
+            }
+        }
+        metadata.version = version.ok_or(GuanoError::MissingVersion)?;
+        Ok(metadata)
+    }
+
+    /// 讀取 WAV 文件中的 GUANO 元數據；沒有 `guan` 塊時返回 None
+    pub fn from_wav(bytes: &[u8]) -> Result<Option<GuanoMetadata>, GuanoError> {
+        let info = WavInfo::parse(bytes)?;
+        match info.find_chunk(GUANO_CHUNK_ID) {
+            Some(chunk) => Ok(Some(GuanoMetadata::parse(chunk.data(bytes))?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 將元數據寫入 WAV 文件，替換已有的 `guan` 塊或追加到文件末尾
+    pub fn write_to_wav(&self, bytes: &[u8]) -> Result<Vec<u8>, WavError> {
+        replace_chunk(bytes, GUANO_CHUNK_ID, &self.to_bytes())
+    }
+
+    /// 規範版本 (如 `1.0`)
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// 所有字段 (不含 `GUANO|Version`)
+    pub fn fields(&self) -> &[GuanoField] {
+        &self.fields
+    }
+
+    fn position_of(&self, full_key: &str) -> Option<usize> {
+        let (namespace, key) = split_key(full_key);
+        self.fields.iter().position(|f| f.namespace.as_deref() == namespace && f.key == key)
+    }
+
+    fn set_field(&mut self, namespace: Option<&str>, key: &str, value: &str) {
+        match self.fields.iter_mut().find(|f| f.namespace.as_deref() == namespace && f.key == key) {
+            Some(field) => field.value = value.to_string(),
+            None => self.fields.push(GuanoField {
+                namespace: namespace.map(str::to_string),
+                key: key.to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+
+    /// 字段的文本值；`full_key` 可帶命名空間 (如 `WA|Song Meter|Prefix`)
+    pub fn get(&self, full_key: &str) -> Option<&str> {
+        self.position_of(full_key).map(|i| self.fields[i].value.as_str())
+    }
+
+    /// 設置字段文本值；已存在時原位替換，否則追加
+    pub fn set(&mut self, full_key: &str, value: &str) {
+        let (namespace, key) = split_key(full_key);
+        if namespace == Some("GUANO") && key == "Version" {
+            self.version = value.trim().to_string();
+        } else {
+            self.set_field(namespace, key, value);
+        }
+    }
+
+    /// 刪除字段，返回是否存在
+    pub fn remove(&mut self, full_key: &str) -> bool {
+        self.position_of(full_key).map(|i| self.fields.remove(i)).is_some()
+    }
+
+    /// 按規範類型解析字段；無法解析時返回 Text
+    pub fn value(&self, full_key: &str) -> Option<GuanoValue> {
+        let text = self.get(full_key)?;
+        let (namespace, key) = split_key(full_key);
+        let kind = if namespace.is_none() { field_type(key) } else { FieldType::Text };
+        let value = match kind {
+            FieldType::Integer => text.parse().ok().map(GuanoValue::Integer),
+            FieldType::Float => text.parse().ok().map(GuanoValue::Float),
+            FieldType::Position => parse_position(text).map(|(lat, lon)| GuanoValue::Position(lat, lon)),
+            FieldType::List => Some(GuanoValue::List(
+                text.split(',').map(str::trim).filter(|t| !t.is_empty()).map(str::to_string).collect(),
+            )),
+            FieldType::Timestamp => Timestamp::parse(text).map(GuanoValue::Timestamp),
+            FieldType::Text => None,
+        };
+        Some(value.unwrap_or_else(|| GuanoValue::Text(text.to_string())))
+    }
+
+    /// 浮點字段；不存在或無法解析時返回 None
+    pub fn float(&self, full_key: &str) -> Option<f64> {
+        self.get(full_key)?.trim().parse().ok()
+    }
+
+    /// 整數字段；不存在或無法解析時返回 None
+    pub fn integer(&self, full_key: &str) -> Option<i64> {
+        self.get(full_key)?.trim().parse().ok()
+    }
+
+    /// 錄音開始時間 (`Timestamp`)
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        Timestamp::parse(self.get("Timestamp")?)
+    }
+
+    /// 設置錄音開始時間
+    pub fn set_timestamp(&mut self, timestamp: &Timestamp) {
+        self.set_field(None, "Timestamp", &timestamp.to_iso8601());
+    }
+
+    /// 錄音位置 (`Loc Position`)：緯度、經度 (十進制度，西經為負)
+    pub fn position(&self) -> Option<(f64, f64)> {
+        parse_position(self.get("Loc Position")?)
+    }
+
+    /// 設置錄音位置
+    pub fn set_position(&mut self, latitude: f64, longitude: f64) {
+        self.set_field(None, "Loc Position", &format!("{} {}", latitude, longitude));
+    }
+
+    /// 標籤列表 (`Tags`，逗號分隔)
+    pub fn tags(&self) -> Vec<String> {
+        match self.value("Tags") {
+            Some(GuanoValue::List(tags)) => tags,
+            _ => Vec::new(),
+        }
+    }
+
+    /// 序列化為 GUANO 文本 (LF 換行，值中的換行轉義為 `\n`)
+    pub fn to_text(&self) -> String {
+        let mut text = format!("GUANO|Version: {}\n", self.version);
+        for field in &self.fields {
+            let value = field.value.replace("\r\n", "\n").replace('\n', "\\n");
+            text.push_str(&format!("{}: {}\n", field.full_key(), value));
+        }
+        text
+    }
+
+    /// 序列化為 `guan` 塊數據 (UTF-8)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_text().into_bytes()
+    }
+}
+
+#[wasm_bindgen]
+impl GuanoMetadata {
+    /// 創建只有版本字段的空元數據
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> GuanoMetadata {
+        GuanoMetadata::default()
+    }
+
+    /// 規範版本
+    #[wasm_bindgen]
+    pub fn get_version(&self) -> String {
+        self.version.clone()
+    }
+
+    /// 所有字段的完整鍵 (按文件順序，不含 `GUANO|Version`)
+    #[wasm_bindgen]
+    pub fn get_keys(&self) -> Vec<String> {
+        self.fields.iter().map(GuanoField::full_key).collect()
+    }
+
+    /// 字段文本值；不存在時返回 undefined
+    #[wasm_bindgen(js_name = get)]
+    pub fn get_value(&self, key: &str) -> Option<String> {
+        self.get(key).map(str::to_string)
+    }
+
+    /// 設置字段文本值
+    #[wasm_bindgen(js_name = set)]
+    pub fn set_value(&mut self, key: &str, value: &str) {
+        self.set(key, value);
+    }
+
+    /// 刪除字段，返回是否存在
+    #[wasm_bindgen(js_name = remove)]
+    pub fn remove_value(&mut self, key: &str) -> bool {
+        self.remove(key)
+    }
+
+    /// 錄音開始時間 (ISO 8601，保留原有 UTC 偏移)；缺失或無效時返回 undefined
+    #[wasm_bindgen]
+    pub fn get_timestamp_iso(&self) -> Option<String> {
+        self.timestamp().map(|t| t.to_iso8601())
+    }
+
+    /// 錄音開始時間 (Unix 秒)；未標明時區時按 UTC 計算
+    #[wasm_bindgen]
+    pub fn get_timestamp_unix(&self) -> Option<f64> {
+        self.timestamp().map(|t| t.to_unix_seconds())
+    }
+
+    /// 時間戳的 UTC 偏移 (分鐘)；未標明時區時返回 undefined
+    #[wasm_bindgen]
+    pub fn get_utc_offset_minutes(&self) -> Option<i16> {
+        self.timestamp()?.utc_offset_minutes
+    }
+
+    /// 以 Unix 時間設置錄音開始時間
+    ///
+    /// # Arguments
+    /// * `seconds` - Unix 時間 (秒)
+    /// * `utc_offset_minutes` - 寫出本地時間所用的 UTC 偏移；undefined 時寫出不帶時區的 UTC 時間
+    #[wasm_bindgen]
+    pub fn set_timestamp_unix(&mut self, seconds: f64, utc_offset_minutes: Option<i16>) {
+        self.set_timestamp(&Timestamp::from_unix_seconds(seconds, utc_offset_minutes));
+    }
+
+    /// 緯度 (十進制度)
+    #[wasm_bindgen]
+    pub fn get_latitude(&self) -> Option<f64> {
+        self.position().map(|(lat, _)| lat)
+    }
+
+    /// 經度 (十進制度，西經為負)
+    #[wasm_bindgen]
+    pub fn get_longitude(&self) -> Option<f64> {
+        self.position().map(|(_, lon)| lon)
+    }
+
+    /// 設置錄音位置 (`Loc Position`)
+    #[wasm_bindgen(js_name = set_position)]
+    pub fn set_position_js(&mut self, latitude: f64, longitude: f64) {
+        self.set_position(latitude, longitude);
+    }
+
+    /// 浮點字段；不存在或無法解析時返回 undefined
+    #[wasm_bindgen]
+    pub fn get_float(&self, key: &str) -> Option<f64> {
+        self.float(key)
+    }
+
+    /// 整數字段 (如 `Samplerate`、`TE`)；不存在或無法解析時返回 undefined
+    #[wasm_bindgen]
+    pub fn get_integer(&self, key: &str) -> Option<f64> {
+        self.integer(key).map(|v| v as f64)
+    }
+
+    /// 標籤列表
+    #[wasm_bindgen]
+    pub fn get_tags(&self) -> Vec<String> {
+        self.tags()
+    }
+
+    /// 序列化為 GUANO 文本
+    #[wasm_bindgen(js_name = to_text)]
+    pub fn to_text_js(&self) -> String {
+        self.to_text()
+    }
+
+    /// 序列化為 `guan` 塊數據
+    #[wasm_bindgen(js_name = to_bytes)]
+    pub fn to_bytes_js(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+}
+
+/// 解析 `guan` 塊數據
+///
+/// # Arguments
+/// * `bytes` - 塊數據 (Uint8Array)
+///
+/// # Returns
+/// GuanoMetadata 對象；缺少版本字段時拋出 Error
+#[wasm_bindgen]
+pub fn parse_guano(bytes: &[u8]) -> Result<GuanoMetadata, JsError> {
+    Ok(GuanoMetadata::parse(bytes)?)
+}
+
+/// 讀取 WAV 文件中的 GUANO 元數據
+///
+/// # Arguments
+/// * `bytes` - 文件內容 (Uint8Array)；`guan` 塊通常位於 data 之後，需傳入完整文件
+///
+/// # Returns
+/// GuanoMetadata 對象；沒有 `guan` 塊時返回 undefined，文件無效時拋出 Error
+#[wasm_bindgen]
+pub fn read_guano(bytes: &[u8]) -> Result<Option<GuanoMetadata>, JsError> {
+    Ok(GuanoMetadata::from_wav(bytes)?)
+}
+
+/// 將 GUANO 元數據寫入 WAV 文件
+///
+/// # Arguments
+/// * `bytes` - 原文件內容 (Uint8Array)
+/// * `metadata` - 要寫入的元數據
+///
+/// # Returns
+/// 新文件內容 (Uint8Array)；已有的 `guan` 塊被原位替換，否則追加到文件末尾
+#[wasm_bindgen]
+pub fn write_guano(bytes: &[u8], metadata: &GuanoMetadata) -> Result<Vec<u8>, JsError> {
+    Ok(metadata.write_to_wav(bytes)?)
+}
@@ -0,0 +1,257 @@
+// ============================================================
+// 諧波結構分析
+// 由平均頻譜中能量最強的峰值出發，判斷它是第幾次諧波，
+// 推算基頻並列出存在的諧波序列及其相對能量。
+// 蹄蝠科 (Hipposideros) 與菊頭蝠科 (Rhinolophus) 的主能量通常在第二諧波，
+// 基頻本身較弱，因此不能直接把最強峰值當作基頻。
+// ============================================================
+
+use wasm_bindgen::prelude::*;
+
+use crate::peaks::{interpolate_peak, magnitude_to_db, Interpolation};
+
+/// 候選基頻的下限 (FFT bin 數)；更低時相鄰諧波無法分辨
+const MIN_FUNDAMENTAL_BINS: f32 = 2.0;
+
+/// 諧波分析參數
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct HarmonicParams {
+    pub(crate) sample_rate: f32,
+    pub(crate) fft_size: usize,
+    /// 分析頻帶下限 (Hz)；基頻本身可以低於此值 (只要有其他諧波落在頻帶內)
+    pub(crate) fmin: f32,
+    /// 分析頻帶上限 (Hz)
+    pub(crate) fmax: f32,
+    /// 考慮的最高諧波次數
+    pub(crate) max_harmonics: usize,
+    /// 諧波頻率的相對容差 (例如 0.03 表示 ±3%)
+    pub(crate) tolerance: f32,
+    /// 諧波被視為存在所需高出噪聲底的 dB 數
+    pub(crate) threshold_db: f32,
+}
+
+/// 單個存在的諧波
+#[derive(Clone, Copy, Debug)]
+struct Harmonic {
+    number: usize,
+    freq_hz: f32,
+    level_db: f32,
+}
+
+/// 最大公約數
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// 頻譜 (單幀或平均後的線性幅度) 上的諧波搜索
+struct SpectrumView<'a> {
+    magnitudes: &'a [f32],
+    bin_hz: f32,
+    /// 分析頻帶的 bin 範圍 [start, end)
+    start: usize,
+    end: usize,
+    /// 噪聲底 (頻帶內幅度中位數的 dB)
+    floor_db: f32,
+}
+
+impl SpectrumView<'_> {
+    /// 在 `freq` 的容差範圍內尋找高於噪聲底的峰值
+    fn find_partial(&self, freq: f32, params: &HarmonicParams) -> Option<(f32, f32)> {
+        let lo = ((freq * (1.0 - params.tolerance)) / self.bin_hz).floor().max(self.start as f32) as usize;
+        let hi = (((freq * (1.0 + params.tolerance)) / self.bin_hz).ceil() as usize + 1).min(self.end);
+        if lo >= hi {
+            return None;
+        }
+
+        let (idx, &peak) = self.magnitudes[lo..hi]
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))?;
+        if magnitude_to_db(peak) < self.floor_db + params.threshold_db {
+            return None;
+        }
+
+        let bin = lo + idx;
+        let (offset, level_db) = interpolate_peak(self.magnitudes, bin, Interpolation::Gaussian);
+        Some(((bin as f32 + offset) * self.bin_hz, level_db))
+    }
+}
+
+/// HarmonicAnalysis: 諧波結構分析結果
+///
+/// 僅列出高於噪聲底的諧波；諧波次數從 1 (基頻) 開始。
+/// 未檢測到有效峰值時基頻與主諧波次數均為 0。
+#[wasm_bindgen]
+pub struct HarmonicAnalysis {
+    fundamental_hz: f32,
+    dominant_harmonic: u32,
+    noise_floor_db: f32,
+    harmonic_numbers: Vec<u32>,
+    frequencies: Vec<f32>,
+    levels_db: Vec<f32>,
+    relative_energies: Vec<f32>,
+}
+
+impl HarmonicAnalysis {
+    /// 空結果
+    pub(crate) fn empty() -> HarmonicAnalysis {
+        HarmonicAnalysis {
+            fundamental_hz: 0.0,
+            dominant_harmonic: 0,
+            noise_floor_db: 0.0,
+            harmonic_numbers: Vec::new(),
+            frequencies: Vec::new(),
+            levels_db: Vec::new(),
+            relative_energies: Vec::new(),
+        }
+    }
+
+    /// 分析線性幅度頻譜 (長度 fft_size / 2) 的諧波結構
+    ///
+    /// 1. 找出頻帶內最強峰值 fd；
+    /// 2. 對 n = max_harmonics..1，若 fd / n 的諧波序列中存在與 n 互質的其他諧波，
+    ///    則 fd 為第 n 次諧波 (取最大的 n)；互質條件排除了次諧波的誤判；
+    /// 3. 以存在諧波的加權最小二乘擬合修正基頻，並列出各諧波的頻率與能量。
+    pub(crate) fn analyze(magnitudes: &[f32], params: &HarmonicParams) -> HarmonicAnalysis {
+        let bin_hz = params.sample_rate / params.fft_size as f32;
+        let start = ((params.fmin.max(0.0) / bin_hz).ceil() as usize).max(1);
+        let end = ((params.fmax / bin_hz).floor() as usize + 1).min(magnitudes.len());
+        if start >= end || params.max_harmonics == 0 {
+            return HarmonicAnalysis::empty();
+        }
+
+        let mut sorted: Vec<f32> = magnitudes[start..end].to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let view = SpectrumView {
+            magnitudes,
+            bin_hz,
+            start,
+            end,
+            floor_db: magnitude_to_db(sorted[sorted.len() / 2]),
+        };
+
+        // 最強峰值
+        let (dominant_bin, &dominant_peak) = match magnitudes[start..end]
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+        {
+            Some((idx, value)) => (start + idx, value),
+            None => return HarmonicAnalysis::empty(),
+        };
+        if dominant_peak <= 0.0 || magnitude_to_db(dominant_peak) < view.floor_db + params.threshold_db {
+            return HarmonicAnalysis { noise_floor_db: view.floor_db, ..HarmonicAnalysis::empty() };
+        }
+        let (offset, _) = interpolate_peak(magnitudes, dominant_bin, Interpolation::Gaussian);
+        let dominant_hz = (dominant_bin as f32 + offset) * bin_hz;
+
+        // 判斷最強峰值的諧波次數
+        let mut order = 1;
+        for n in (2..=params.max_harmonics).rev() {
+            let f0 = dominant_hz / n as f32;
+            // 基頻不必落在分析頻帶內：頻帶從第二諧波開始時第一諧波本來就看不到
+            if f0 < MIN_FUNDAMENTAL_BINS * bin_hz {
+                continue;
+            }
+            let supported = (1..=params.max_harmonics)
+                .filter(|&k| k != n && gcd(k, n) == 1)
+                .map(|k| k as f32 * f0)
+                .take_while(|&freq| freq <= params.fmax)
+                .any(|freq| view.find_partial(freq, params).is_some());
+            if supported {
+                order = n;
+                break;
+            }
+        }
+
+        // 收集存在的諧波並以最小二乘擬合基頻: f0 = Σ k·f_k / Σ k²
+        let collect = |f0: f32| -> Vec<Harmonic> {
+            (1..=params.max_harmonics)
+                .take_while(|&k| k as f32 * f0 <= params.fmax)
+                .filter_map(|k| {
+                    view.find_partial(k as f32 * f0, params)
+                        .map(|(freq_hz, level_db)| Harmonic { number: k, freq_hz, level_db })
+                })
+                .collect()
+        };
+        let initial = collect(dominant_hz / order as f32);
+        let numerator: f32 = initial.iter().map(|h| h.number as f32 * h.freq_hz).sum();
+        let denominator: f32 = initial.iter().map(|h| (h.number * h.number) as f32).sum();
+        let fundamental_hz = if denominator > 0.0 { numerator / denominator } else { dominant_hz / order as f32 };
+        let harmonics = collect(fundamental_hz);
+
+        let energies: Vec<f32> = harmonics.iter().map(|h| 10f32.powf(h.level_db / 10.0)).collect();
+        let total_energy: f32 = energies.iter().sum();
+        let dominant_harmonic = harmonics
+            .iter()
+            .zip(&energies)
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(h, _)| h.number as u32)
+            .unwrap_or(0);
+
+        HarmonicAnalysis {
+            fundamental_hz,
+            dominant_harmonic,
+            noise_floor_db: view.floor_db,
+            harmonic_numbers: harmonics.iter().map(|h| h.number as u32).collect(),
+            frequencies: harmonics.iter().map(|h| h.freq_hz).collect(),
+            levels_db: harmonics.iter().map(|h| h.level_db).collect(),
+            relative_energies: energies.iter().map(|&e| e / total_energy).collect(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl HarmonicAnalysis {
+    /// 估計的基頻 (Hz)，即使基頻本身能量很弱
+    #[wasm_bindgen]
+    pub fn get_fundamental(&self) -> f32 {
+        self.fundamental_hz
+    }
+
+    /// 能量最強的諧波次數 (1 = 基頻)；無有效峰值時為 0
+    #[wasm_bindgen]
+    pub fn get_dominant_harmonic(&self) -> u32 {
+        self.dominant_harmonic
+    }
+
+    /// 是否存在諧波結構 (至少兩個諧波高於噪聲底)
+    #[wasm_bindgen]
+    pub fn has_harmonics(&self) -> bool {
+        self.harmonic_numbers.len() >= 2
+    }
+
+    /// 分析頻帶內的噪聲底 (dB)
+    #[wasm_bindgen]
+    pub fn get_noise_floor_db(&self) -> f32 {
+        self.noise_floor_db
+    }
+
+    /// 存在的諧波次數 (Uint32Array，遞增)
+    #[wasm_bindgen]
+    pub fn get_harmonic_numbers(&self) -> Vec<u32> {
+        self.harmonic_numbers.clone()
+    }
+
+    /// 各諧波的峰值頻率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_frequencies(&self) -> Vec<f32> {
+        self.frequencies.clone()
+    }
+
+    /// 各諧波的峰值幅度 (dB)
+    #[wasm_bindgen]
+    pub fn get_levels_db(&self) -> Vec<f32> {
+        self.levels_db.clone()
+    }
+
+    /// 各諧波佔所有列出諧波總能量的比例 (總和為 1)
+    #[wasm_bindgen]
+    pub fn get_relative_energies(&self) -> Vec<f32> {
+        self.relative_energies.clone()
+    }
+}
@@ -0,0 +1,856 @@
+// ============================================================
+// IIR 濾波器設計與零相位濾波
+// 設計 Butterworth、Chebyshev I/II 與橢圓 (Elliptic) 濾波器：
+// 模擬原型 (零極點) -> 頻率變換 -> 預扭曲雙線性變換 -> 二階節 (SOS)。
+// 所有設計與濾波均以 f64 計算，支持因果濾波、零相位 (filtfilt) 濾波與頻率響應。
+// ============================================================
+
+use std::f64::consts::PI;
+use std::fmt;
+
+use num_complex::Complex;
+use wasm_bindgen::prelude::*;
+
+type C64 = Complex<f64>;
+
+/// 允許的最高濾波器階數
+const MAX_ORDER: usize = 32;
+/// 判斷根是否為實數的相對容差
+const REAL_TOLERANCE: f64 = 1e-10;
+
+/// 濾波器類型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterFamily {
+    /// 通帶最平坦
+    Butterworth,
+    /// 通帶等波紋
+    Chebyshev1,
+    /// 阻帶等波紋
+    Chebyshev2,
+    /// 通帶與阻帶均等波紋，過渡帶最窄
+    Elliptic,
+}
+
+impl FilterFamily {
+    /// 根據名稱解析類型
+    pub fn from_name(name: &str) -> Option<FilterFamily> {
+        match name {
+            "butterworth" | "butter" => Some(FilterFamily::Butterworth),
+            "chebyshev1" | "cheby1" => Some(FilterFamily::Chebyshev1),
+            "chebyshev2" | "cheby2" => Some(FilterFamily::Chebyshev2),
+            "elliptic" | "ellip" => Some(FilterFamily::Elliptic),
+            _ => None,
+        }
+    }
+}
+
+/// 頻帶類型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BandType {
+    Lowpass,
+    Highpass,
+    Bandpass,
+    /// 帶阻 (陷波)
+    Bandstop,
+}
+
+impl BandType {
+    /// 根據名稱解析頻帶類型 ("notch" 等同於 "bandstop")
+    pub fn from_name(name: &str) -> Option<BandType> {
+        match name {
+            "lowpass" => Some(BandType::Lowpass),
+            "highpass" => Some(BandType::Highpass),
+            "bandpass" => Some(BandType::Bandpass),
+            "bandstop" | "notch" => Some(BandType::Bandstop),
+            _ => None,
+        }
+    }
+}
+
+/// 濾波器設計錯誤
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterError {
+    /// 未知的濾波器類型名稱
+    UnknownFamily(String),
+    /// 未知的頻帶類型名稱
+    UnknownBandType(String),
+    /// 階數必須在 1..=32 之間
+    InvalidOrder { order: usize },
+    /// 採樣率必須為正數
+    InvalidSampleRate,
+    /// 截止頻率必須在 (0, Nyquist) 之間
+    InvalidFrequency { freq_hz: f32, nyquist_hz: f32 },
+    /// 帶通 / 帶阻濾波器要求 low_hz < high_hz
+    InvalidBand { low_hz: f32, high_hz: f32 },
+    /// 通帶波紋必須為正數
+    InvalidRipple { ripple_db: f32 },
+    /// 阻帶衰減必須為正數 (橢圓濾波器還要求大於通帶波紋)
+    InvalidAttenuation { attenuation_db: f32 },
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::UnknownFamily(name) => write!(f, "unknown filter family '{}'", name),
+            FilterError::UnknownBandType(name) => write!(f, "unknown band type '{}'", name),
+            FilterError::InvalidOrder { order } => {
+                write!(f, "filter order {} is outside 1..={}", order, MAX_ORDER)
+            }
+            FilterError::InvalidSampleRate => write!(f, "sample rate must be positive"),
+            FilterError::InvalidFrequency { freq_hz, nyquist_hz } => write!(
+                f,
+                "cutoff frequency {} Hz must be between 0 and the Nyquist frequency {} Hz",
+                freq_hz, nyquist_hz
+            ),
+            FilterError::InvalidBand { low_hz, high_hz } => {
+                write!(f, "band edges must satisfy low < high, got {}..{} Hz", low_hz, high_hz)
+            }
+            FilterError::InvalidRipple { ripple_db } => {
+                write!(f, "passband ripple must be positive, got {} dB", ripple_db)
+            }
+            FilterError::InvalidAttenuation { attenuation_db } => write!(
+                f,
+                "stopband attenuation {} dB must be positive and larger than the passband ripple",
+                attenuation_db
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+/// 檢查採樣率與截止頻率，返回頻帶類型所需的邊界頻率 (Hz，遞增)
+///
+/// 低通使用 `high_hz`，高通使用 `low_hz`，帶通 / 帶阻使用兩者。
+pub(crate) fn band_edges(
+    band: BandType,
+    sample_rate: f32,
+    low_hz: f32,
+    high_hz: f32,
+) -> Result<Vec<f32>, FilterError> {
+    if sample_rate.is_nan() || sample_rate <= 0.0 {
+        return Err(FilterError::InvalidSampleRate);
+    }
+
+    let nyquist_hz = sample_rate / 2.0;
+    let check = |freq_hz: f32| {
+        if freq_hz > 0.0 && freq_hz < nyquist_hz {
+            Ok(freq_hz)
+        } else {
+            Err(FilterError::InvalidFrequency { freq_hz, nyquist_hz })
+        }
+    };
+    match band {
+        BandType::Lowpass => Ok(vec![check(high_hz)?]),
+        BandType::Highpass => Ok(vec![check(low_hz)?]),
+        BandType::Bandpass | BandType::Bandstop => {
+            let (low, high) = (check(low_hz)?, check(high_hz)?);
+            if low >= high {
+                return Err(FilterError::InvalidBand { low_hz, high_hz });
+            }
+            Ok(vec![low, high])
+        }
+    }
+}
+
+/// 零極點增益表示
+#[derive(Clone, Debug)]
+struct Zpk {
+    zeros: Vec<C64>,
+    poles: Vec<C64>,
+    gain: f64,
+}
+
+impl Zpk {
+    /// 極點數 - 零點數
+    fn degree(&self) -> usize {
+        self.poles.len() - self.zeros.len()
+    }
+}
+
+fn product(values: &[C64]) -> C64 {
+    values.iter().fold(C64::new(1.0, 0.0), |acc, &v| acc * v)
+}
+
+// ------------------------------------------------------------
+// 橢圓函數
+// ------------------------------------------------------------
+
+/// 算術-幾何平均
+fn agm(mut a: f64, mut b: f64) -> f64 {
+    for _ in 0..64 {
+        if (a - b).abs() <= 1e-15 * a {
+            break;
+        }
+        let next = 0.5 * (a + b);
+        b = (a * b).sqrt();
+        a = next;
+    }
+    a
+}
+
+/// 第一類完全橢圓積分 K(m)
+fn ellipk(m: f64) -> f64 {
+    PI / (2.0 * agm(1.0, (1.0 - m).sqrt()))
+}
+
+/// K(1 - p)，在 p 很小時仍保持精度
+fn ellipkm1(p: f64) -> f64 {
+    PI / (2.0 * agm(1.0, p.sqrt()))
+}
+
+/// Carlson 對稱橢圓積分 R_F(x, y, z)
+fn carlson_rf(mut x: f64, mut y: f64, mut z: f64) -> f64 {
+    loop {
+        let (sx, sy, sz) = (x.sqrt(), y.sqrt(), z.sqrt());
+        let lambda = sx * (sy + sz) + sy * sz;
+        x = 0.25 * (x + lambda);
+        y = 0.25 * (y + lambda);
+        z = 0.25 * (z + lambda);
+
+        let mean = (x + y + z) / 3.0;
+        let (dx, dy, dz) = ((mean - x) / mean, (mean - y) / mean, (mean - z) / mean);
+        if dx.abs().max(dy.abs()).max(dz.abs()) < 1e-4 {
+            let e2 = dx * dy - dz * dz;
+            let e3 = dx * dy * dz;
+            return (1.0 + (e2 / 24.0 - 0.1 - 3.0 * e3 / 44.0) * e2 + e3 / 14.0) / mean.sqrt();
+        }
+    }
+}
+
+/// 第一類不完全橢圓積分 F(φ | m)，0 <= φ <= π/2
+fn ellipf(phi: f64, m: f64) -> f64 {
+    let (s, c) = phi.sin_cos();
+    s * carlson_rf(c * c, 1.0 - m * s * s, 1.0)
+}
+
+/// Jacobi 橢圓函數 (sn, cn, dn)，使用降序 AGM (Abramowitz & Stegun 16.4)
+fn ellipj(u: f64, m: f64) -> (f64, f64, f64) {
+    if m < 1e-12 {
+        let (s, c) = u.sin_cos();
+        return (s, c, 1.0);
+    }
+    if m > 1.0 - 1e-12 {
+        let sech = 1.0 / u.cosh();
+        return (u.tanh(), sech, sech);
+    }
+
+    let mut a = vec![1.0f64];
+    let mut c = vec![m.sqrt()];
+    let mut b = (1.0 - m).sqrt();
+    while c[c.len() - 1].abs() > 1e-15 && a.len() < 64 {
+        let an = a[a.len() - 1];
+        a.push(0.5 * (an + b));
+        c.push(0.5 * (an - b));
+        b = (an * b).sqrt();
+    }
+
+    let n = a.len() - 1;
+    let mut phi = (1u64 << n) as f64 * a[n] * u;
+    let mut prev = phi;
+    for i in (1..=n).rev() {
+        prev = phi;
+        phi = 0.5 * (phi + (c[i] / a[i] * phi.sin()).asin());
+    }
+    let (sn, cn) = phi.sin_cos();
+    (sn, cn, cn / (prev - phi).cos())
+}
+
+/// 由 K(m) / K(1 - m) = ratio 求參數 m (使用 nome 與 theta 函數)
+fn ellip_deg(ratio: f64) -> f64 {
+    if ratio > 1.0 {
+        return 1.0 - ellip_deg(1.0 / ratio);
+    }
+
+    // q = exp(-π K'/K)；ratio <= 1 時 q <= e^{-π}，級數收斂很快
+    let q = (-PI / ratio).exp();
+    let mut theta2 = 0.0f64;
+    let mut theta3 = 1.0f64;
+    for n in 0..32 {
+        let n = n as f64;
+        theta2 += q.powf(n * (n + 1.0));
+        if n > 0.0 {
+            theta3 += 2.0 * q.powf(n * n);
+        }
+    }
+    theta2 *= 2.0 * q.powf(0.25);
+    (theta2 / theta3).powi(4)
+}
+
+// ------------------------------------------------------------
+// 模擬原型 (截止頻率 1 rad/s)
+// ------------------------------------------------------------
+
+/// 對稱索引 m = -N+1, -N+3, ..., N-1
+fn symmetric_indices(order: usize) -> impl Iterator<Item = f64> {
+    (0..order).map(move |i| (2 * i) as f64 - (order as f64 - 1.0))
+}
+
+fn butterworth_prototype(order: usize) -> Zpk {
+    let poles = symmetric_indices(order)
+        .map(|m| -C64::from_polar(1.0, PI * m / (2.0 * order as f64)))
+        .collect();
+    Zpk { zeros: Vec::new(), poles, gain: 1.0 }
+}
+
+fn chebyshev1_prototype(order: usize, ripple_db: f64) -> Zpk {
+    let eps = (10f64.powf(0.1 * ripple_db) - 1.0).sqrt();
+    let mu = (1.0 / eps).asinh() / order as f64;
+    let poles: Vec<C64> = symmetric_indices(order)
+        .map(|m| -(C64::new(mu, PI * m / (2.0 * order as f64))).sinh())
+        .collect();
+
+    let mut gain = product(&poles.iter().map(|p| -p).collect::<Vec<_>>()).re;
+    if order.is_multiple_of(2) {
+        gain /= (1.0 + eps * eps).sqrt();
+    }
+    Zpk { zeros: Vec::new(), poles, gain }
+}
+
+fn chebyshev2_prototype(order: usize, attenuation_db: f64) -> Zpk {
+    let de = 1.0 / (10f64.powf(0.1 * attenuation_db) - 1.0).sqrt();
+    let mu = (1.0 / de).asinh() / order as f64;
+    let n = order as f64;
+
+    // 奇數階時 m = 0 對應無窮遠處的零點，省略
+    let zeros: Vec<C64> = symmetric_indices(order)
+        .filter(|&m| m != 0.0)
+        .map(|m| -(C64::new(0.0, 1.0) / (m * PI / (2.0 * n)).sin()).conj())
+        .collect();
+    let poles: Vec<C64> = symmetric_indices(order)
+        .map(|m| {
+            let p = -C64::from_polar(1.0, PI * m / (2.0 * n));
+            1.0 / C64::new(mu.sinh() * p.re, mu.cosh() * p.im)
+        })
+        .collect();
+
+    let gain = (product(&poles.iter().map(|p| -p).collect::<Vec<_>>())
+        / product(&zeros.iter().map(|z| -z).collect::<Vec<_>>()))
+    .re;
+    Zpk { zeros, poles, gain }
+}
+
+fn elliptic_prototype(order: usize, ripple_db: f64, attenuation_db: f64) -> Zpk {
+    let eps_sq = 10f64.powf(0.1 * ripple_db) - 1.0;
+    if order == 1 {
+        let p = -(1.0 / eps_sq).sqrt();
+        return Zpk { zeros: Vec::new(), poles: vec![C64::new(p, 0.0)], gain: -p };
+    }
+
+    let eps = eps_sq.sqrt();
+    let ck1_sq = eps_sq / (10f64.powf(0.1 * attenuation_db) - 1.0);
+    let k1 = ellipk(ck1_sq);
+    let k1_prime = ellipkm1(ck1_sq);
+    let m = ellip_deg(order as f64 * k1 / k1_prime);
+    let capk = ellipk(m);
+
+    let n = order as f64;
+    let indices: Vec<f64> = ((1 - order % 2)..order).step_by(2).map(|j| j as f64).collect();
+    let jacobi: Vec<(f64, f64, f64)> = indices.iter().map(|&j| ellipj(j * capk / n, m)).collect();
+
+    let mut zeros = Vec::new();
+    for &(s, _, _) in &jacobi {
+        if s.abs() > 1e-12 {
+            let z = C64::new(0.0, 1.0 / (m.sqrt() * s));
+            zeros.push(z);
+            zeros.push(z.conj());
+        }
+    }
+
+    // r = sc⁻¹(1/ε | 1 - k1²)
+    let r = ellipf((1.0 / eps).atan(), 1.0 - ck1_sq);
+    let v0 = capk * r / (n * k1);
+    let (sv, cv, dv) = ellipj(v0, 1.0 - m);
+
+    let mut poles = Vec::new();
+    for &(s, c, d) in &jacobi {
+        let p = -C64::new(c * d * sv * cv, s * dv) / (1.0 - (d * sv).powi(2));
+        poles.push(p);
+        if p.im.abs() > 1e-12 {
+            poles.push(p.conj());
+        } else {
+            // 奇數階的實極點
+            poles.last_mut().unwrap().im = 0.0;
+        }
+    }
+
+    let mut gain = (product(&poles.iter().map(|p| -p).collect::<Vec<_>>())
+        / product(&zeros.iter().map(|z| -z).collect::<Vec<_>>()))
+    .re;
+    if order.is_multiple_of(2) {
+        gain /= (1.0 + eps_sq).sqrt();
+    }
+    Zpk { zeros, poles, gain }
+}
+
+// ------------------------------------------------------------
+// 頻率變換與雙線性變換
+// ------------------------------------------------------------
+
+fn lowpass_to_lowpass(zpk: Zpk, wo: f64) -> Zpk {
+    let degree = zpk.degree() as i32;
+    Zpk {
+        zeros: zpk.zeros.iter().map(|z| z * wo).collect(),
+        poles: zpk.poles.iter().map(|p| p * wo).collect(),
+        gain: zpk.gain * wo.powi(degree),
+    }
+}
+
+fn lowpass_to_highpass(zpk: Zpk, wo: f64) -> Zpk {
+    let degree = zpk.degree();
+    let gain = zpk.gain
+        * (product(&zpk.zeros.iter().map(|z| -z).collect::<Vec<_>>())
+            / product(&zpk.poles.iter().map(|p| -p).collect::<Vec<_>>()))
+        .re;
+    let mut zeros: Vec<C64> = zpk.zeros.iter().map(|z| wo / z).collect();
+    zeros.extend(std::iter::repeat_n(C64::new(0.0, 0.0), degree));
+    Zpk {
+        zeros,
+        poles: zpk.poles.iter().map(|p| wo / p).collect(),
+        gain,
+    }
+}
+
+/// 將每個根 r 映射為 r ± sqrt(r² - wo²)
+fn split_roots(roots: &[C64], wo: f64) -> Vec<C64> {
+    let mut result = Vec::with_capacity(roots.len() * 2);
+    for &r in roots {
+        let d = (r * r - wo * wo).sqrt();
+        result.push(r + d);
+    }
+    for &r in roots {
+        let d = (r * r - wo * wo).sqrt();
+        result.push(r - d);
+    }
+    result
+}
+
+fn lowpass_to_bandpass(zpk: Zpk, wo: f64, bw: f64) -> Zpk {
+    let degree = zpk.degree();
+    let scaled_zeros: Vec<C64> = zpk.zeros.iter().map(|z| z * bw / 2.0).collect();
+    let scaled_poles: Vec<C64> = zpk.poles.iter().map(|p| p * bw / 2.0).collect();
+    let mut zeros = split_roots(&scaled_zeros, wo);
+    zeros.extend(std::iter::repeat_n(C64::new(0.0, 0.0), degree));
+    Zpk {
+        zeros,
+        poles: split_roots(&scaled_poles, wo),
+        gain: zpk.gain * bw.powi(degree as i32),
+    }
+}
+
+fn lowpass_to_bandstop(zpk: Zpk, wo: f64, bw: f64) -> Zpk {
+    let degree = zpk.degree();
+    let gain = zpk.gain
+        * (product(&zpk.zeros.iter().map(|z| -z).collect::<Vec<_>>())
+            / product(&zpk.poles.iter().map(|p| -p).collect::<Vec<_>>()))
+        .re;
+    let inverted_zeros: Vec<C64> = zpk.zeros.iter().map(|z| (bw / 2.0) / z).collect();
+    let inverted_poles: Vec<C64> = zpk.poles.iter().map(|p| (bw / 2.0) / p).collect();
+    let mut zeros = split_roots(&inverted_zeros, wo);
+    zeros.extend(std::iter::repeat_n(C64::new(0.0, wo), degree));
+    zeros.extend(std::iter::repeat_n(C64::new(0.0, -wo), degree));
+    Zpk {
+        zeros,
+        poles: split_roots(&inverted_poles, wo),
+        gain,
+    }
+}
+
+/// 雙線性變換 s -> z (fs2 = 2 * sample_rate)
+fn bilinear(zpk: Zpk, sample_rate: f64) -> Zpk {
+    let fs2 = 2.0 * sample_rate;
+    let degree = zpk.degree();
+    let gain = zpk.gain
+        * (product(&zpk.zeros.iter().map(|z| fs2 - z).collect::<Vec<_>>())
+            / product(&zpk.poles.iter().map(|p| fs2 - p).collect::<Vec<_>>()))
+        .re;
+    let mut zeros: Vec<C64> = zpk.zeros.iter().map(|z| (fs2 + z) / (fs2 - z)).collect();
+    zeros.extend(std::iter::repeat_n(C64::new(-1.0, 0.0), degree));
+    Zpk {
+        zeros,
+        poles: zpk.poles.iter().map(|p| (fs2 + p) / (fs2 - p)).collect(),
+        gain,
+    }
+}
+
+// ------------------------------------------------------------
+// 二階節
+// ------------------------------------------------------------
+
+/// 二階節係數 [b0, b1, b2, a0, a1, a2]，a0 恆為 1
+type Section = [f64; 6];
+
+fn is_real(root: C64) -> bool {
+    root.im.abs() <= REAL_TOLERANCE * root.norm().max(1.0)
+}
+
+/// 將根分為共軛對 (只保留虛部為正者) 與實根
+fn split_conjugates(roots: &[C64]) -> (Vec<C64>, Vec<f64>) {
+    let mut complex = Vec::new();
+    let mut real = Vec::new();
+    for &r in roots {
+        if is_real(r) {
+            real.push(r.re);
+        } else if r.im > 0.0 {
+            complex.push(r);
+        }
+    }
+    (complex, real)
+}
+
+/// 由最多兩個根構成二次多項式係數 [1, c1, c2]
+fn quadratic(roots: &[C64]) -> [f64; 3] {
+    match roots {
+        [] => [1.0, 0.0, 0.0],
+        [r] => [1.0, -r.re, 0.0],
+        [r1, r2] => {
+            let sum = r1 + r2;
+            let prod = r1 * r2;
+            [1.0, -sum.re, prod.re]
+        }
+        _ => unreachable!("a section holds at most two roots"),
+    }
+}
+
+/// 零極點 -> 二階節
+///
+/// 極點按共軛對 (或兩個實極點) 分組，離單位圓最近的組放在最後；
+/// 從最靠近單位圓的極點組開始，為每組分配距離最近的零點。
+fn zpk_to_sos(zpk: &Zpk) -> Vec<Section> {
+    let (complex_poles, mut real_poles) = split_conjugates(&zpk.poles);
+    let (mut complex_zeros, mut real_zeros) = split_conjugates(&zpk.zeros);
+
+    // 極點分組
+    let mut groups: Vec<Vec<C64>> = complex_poles.iter().map(|&p| vec![p, p.conj()]).collect();
+    real_poles.sort_by(|a, b| (1.0 - a.abs()).total_cmp(&(1.0 - b.abs())));
+    for pair in real_poles.chunks(2) {
+        groups.push(pair.iter().map(|&p| C64::new(p, 0.0)).collect());
+    }
+    if groups.is_empty() && !zpk.zeros.is_empty() {
+        groups.push(Vec::new());
+    }
+    // 離單位圓最遠的組在前
+    let distance = |group: &Vec<C64>| group.iter().map(|p| 1.0 - p.norm()).fold(f64::INFINITY, f64::min);
+    groups.sort_by(|a, b| distance(b).total_cmp(&distance(a)));
+
+    let mut group_zeros: Vec<Vec<C64>> = vec![Vec::new(); groups.len()];
+    for (group, zeros) in groups.iter().zip(group_zeros.iter_mut()).rev() {
+        let anchor = group.first().copied().unwrap_or_default();
+        let slots = group.len().max(1);
+
+        while zeros.len() < slots {
+            let nearest_complex = complex_zeros
+                .iter()
+                .enumerate()
+                .min_by(|a, b| (a.1 - anchor).norm().total_cmp(&(b.1 - anchor).norm()))
+                .map(|(i, z)| (i, (z - anchor).norm()));
+            let nearest_real = real_zeros
+                .iter()
+                .enumerate()
+                .min_by(|a, b| (*a.1 - anchor).norm().total_cmp(&(*b.1 - anchor).norm()))
+                .map(|(i, &z)| (i, (z - anchor).norm()));
+
+            match (nearest_complex, nearest_real) {
+                (Some((ci, cd)), real) if slots - zeros.len() >= 2 && real.is_none_or(|(_, rd)| cd <= rd) => {
+                    let z = complex_zeros.swap_remove(ci);
+                    zeros.push(z);
+                    zeros.push(z.conj());
+                }
+                (_, Some((ri, _))) => zeros.push(C64::new(real_zeros.swap_remove(ri), 0.0)),
+                _ => break,
+            }
+        }
+    }
+
+    let mut sections: Vec<Section> = groups
+        .iter()
+        .zip(&group_zeros)
+        .map(|(poles, zeros)| {
+            let b = quadratic(zeros);
+            let a = quadratic(poles);
+            [b[0], b[1], b[2], a[0], a[1], a[2]]
+        })
+        .collect();
+
+    // 剩餘零點 (極少出現) 放入純 FIR 節，保持傳遞函數不變
+    let mut leftover: Vec<C64> = complex_zeros.iter().flat_map(|&z| [z, z.conj()]).collect();
+    leftover.extend(real_zeros.iter().map(|&z| C64::new(z, 0.0)));
+    for chunk in leftover.chunks(2) {
+        let b = quadratic(chunk);
+        sections.push([b[0], b[1], b[2], 1.0, 0.0, 0.0]);
+    }
+
+    if let Some(first) = sections.first_mut() {
+        for coeff in first.iter_mut().take(3) {
+            *coeff *= zpk.gain;
+        }
+    }
+    sections
+}
+
+/// 計算二階節級聯在 ω (弧度/樣本) 處的複數響應
+fn sos_response(sections: &[Section], omega: f64) -> C64 {
+    let z1 = C64::from_polar(1.0, -omega);
+    let z2 = z1 * z1;
+    sections.iter().fold(C64::new(1.0, 0.0), |acc, s| {
+        acc * (s[0] + s[1] * z1 + s[2] * z2) / (s[3] + s[4] * z1 + s[5] * z2)
+    })
+}
+
+/// 直接 II 型轉置結構的級聯濾波；`state` 為每節的 (z1, z2)
+fn sos_filter(sections: &[Section], data: &mut [f64], state: &mut [[f64; 2]]) {
+    for (s, st) in sections.iter().zip(state.iter_mut()) {
+        let [b0, b1, b2, _, a1, a2] = *s;
+        let [mut z1, mut z2] = *st;
+        for x in data.iter_mut() {
+            let y = b0 * *x + z1;
+            z1 = b1 * *x - a1 * y + z2;
+            z2 = b2 * *x - a2 * y;
+            *x = y;
+        }
+        *st = [z1, z2];
+    }
+}
+
+/// 單位階躍輸入的穩態初始狀態 (與 scipy.signal.sosfilt_zi 相同)
+fn sos_steady_state(sections: &[Section]) -> Vec<[f64; 2]> {
+    let mut scale = 1.0f64;
+    sections
+        .iter()
+        .map(|s| {
+            let [b0, _, b2, _, a1, a2] = *s;
+            let dc_gain = (s[0] + s[1] + s[2]) / (1.0 + a1 + a2);
+            let zi = [scale * (dc_gain - b0), scale * (b2 - a2 * dc_gain)];
+            scale *= dc_gain;
+            zi
+        })
+        .collect()
+}
+
+/// IirFilter: 以二階節表示的 IIR 數字濾波器
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct IirFilter {
+    sections: Vec<Section>,
+    sample_rate: f64,
+}
+
+impl IirFilter {
+    /// 設計 IIR 濾波器
+    ///
+    /// # Arguments
+    /// * `family` - 濾波器類型
+    /// * `band` - 頻帶類型
+    /// * `order` - 原型階數 (帶通 / 帶阻濾波器的實際階數為 2 * order)
+    /// * `sample_rate` - 採樣率 (Hz)
+    /// * `low_hz` - 高通的截止頻率；帶通 / 帶阻的下邊界
+    /// * `high_hz` - 低通的截止頻率；帶通 / 帶阻的上邊界
+    /// * `ripple_db` - 通帶波紋 (Chebyshev I 與橢圓濾波器)
+    /// * `attenuation_db` - 阻帶衰減 (Chebyshev II 與橢圓濾波器)
+    ///
+    /// Chebyshev II 的截止頻率為增益首次降到 -attenuation_db 的頻率，
+    /// 其餘類型為通帶邊界 (Butterworth 為 -3 dB 點)。
+    #[allow(clippy::too_many_arguments)]
+    pub fn design(
+        family: FilterFamily,
+        band: BandType,
+        order: usize,
+        sample_rate: f32,
+        low_hz: f32,
+        high_hz: f32,
+        ripple_db: f32,
+        attenuation_db: f32,
+    ) -> Result<IirFilter, FilterError> {
+        if order == 0 || order > MAX_ORDER {
+            return Err(FilterError::InvalidOrder { order });
+        }
+        let edges = band_edges(band, sample_rate, low_hz, high_hz)?;
+
+        let needs_ripple = matches!(family, FilterFamily::Chebyshev1 | FilterFamily::Elliptic);
+        let needs_attenuation = matches!(family, FilterFamily::Chebyshev2 | FilterFamily::Elliptic);
+        if needs_ripple && (ripple_db.is_nan() || ripple_db <= 0.0) {
+            return Err(FilterError::InvalidRipple { ripple_db });
+        }
+        if needs_attenuation
+            && (attenuation_db.is_nan()
+                || attenuation_db <= 0.0
+                || (family == FilterFamily::Elliptic && attenuation_db <= ripple_db))
+        {
+            return Err(FilterError::InvalidAttenuation { attenuation_db });
+        }
+
+        let prototype = match family {
+            FilterFamily::Butterworth => butterworth_prototype(order),
+            FilterFamily::Chebyshev1 => chebyshev1_prototype(order, ripple_db as f64),
+            FilterFamily::Chebyshev2 => chebyshev2_prototype(order, attenuation_db as f64),
+            FilterFamily::Elliptic => elliptic_prototype(order, ripple_db as f64, attenuation_db as f64),
+        };
+
+        // 預扭曲，使數字濾波器的截止頻率準確
+        let fs = sample_rate as f64;
+        let warped: Vec<f64> = edges.iter().map(|&f| 2.0 * fs * (PI * f as f64 / fs).tan()).collect();
+        let analog = match band {
+            BandType::Lowpass => lowpass_to_lowpass(prototype, warped[0]),
+            BandType::Highpass => lowpass_to_highpass(prototype, warped[0]),
+            BandType::Bandpass => {
+                lowpass_to_bandpass(prototype, (warped[0] * warped[1]).sqrt(), warped[1] - warped[0])
+            }
+            BandType::Bandstop => {
+                lowpass_to_bandstop(prototype, (warped[0] * warped[1]).sqrt(), warped[1] - warped[0])
+            }
+        };
+
+        Ok(IirFilter {
+            sections: zpk_to_sos(&bilinear(analog, fs)),
+            sample_rate: fs,
+        })
+    }
+
+    /// 因果濾波 (f64 計算)
+    pub fn apply(&self, input: &[f32]) -> Vec<f32> {
+        let mut data: Vec<f64> = input.iter().map(|&x| x as f64).collect();
+        let mut state = vec![[0.0; 2]; self.sections.len()];
+        sos_filter(&self.sections, &mut data, &mut state);
+        data.into_iter().map(|x| x as f32).collect()
+    }
+
+    /// 零相位濾波: 正向與反向各濾波一次
+    ///
+    /// 兩端以奇對稱延拓 3 * (2 * 節數 + 1) 個樣本，
+    /// 並以穩態初始條件開始，以減少邊緣瞬態 (與 scipy.signal.sosfiltfilt 相同)。
+    pub fn apply_zero_phase(&self, input: &[f32]) -> Vec<f32> {
+        let n = input.len();
+        if n < 2 {
+            return self.apply(input);
+        }
+
+        let pad = (3 * (2 * self.sections.len() + 1)).min(n - 1);
+        let first = input[0] as f64;
+        let last = input[n - 1] as f64;
+
+        let mut data = Vec::with_capacity(n + 2 * pad);
+        data.extend((1..=pad).rev().map(|i| 2.0 * first - input[i] as f64));
+        data.extend(input.iter().map(|&x| x as f64));
+        data.extend((1..=pad).map(|i| 2.0 * last - input[n - 1 - i] as f64));
+
+        let zi = sos_steady_state(&self.sections);
+        let scaled = |x0: f64| -> Vec<[f64; 2]> { zi.iter().map(|z| [z[0] * x0, z[1] * x0]).collect() };
+
+        let mut state = scaled(data[0]);
+        sos_filter(&self.sections, &mut data, &mut state);
+        data.reverse();
+        let mut state = scaled(data[0]);
+        sos_filter(&self.sections, &mut data, &mut state);
+        data.reverse();
+
+        data[pad..pad + n].iter().map(|&x| x as f32).collect()
+    }
+
+    /// 在指定頻率 (Hz) 處的複數頻率響應
+    pub fn response(&self, freq_hz: f32) -> Complex<f64> {
+        sos_response(&self.sections, 2.0 * PI * freq_hz as f64 / self.sample_rate)
+    }
+}
+
+#[wasm_bindgen]
+impl IirFilter {
+    /// 二階節數量
+    #[wasm_bindgen]
+    pub fn get_num_sections(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// 二階節係數 (num_sections x 6，每行 [b0, b1, b2, a0, a1, a2]，與 scipy 的 sos 格式相同)
+    #[wasm_bindgen]
+    pub fn get_sos(&self) -> Vec<f64> {
+        self.sections.iter().flatten().copied().collect()
+    }
+
+    /// 設計時使用的採樣率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_sample_rate(&self) -> f32 {
+        self.sample_rate as f32
+    }
+
+    /// 因果濾波
+    ///
+    /// # Arguments
+    /// * `input` - 輸入信號 (Float32Array)
+    ///
+    /// # Returns
+    /// 濾波後的信號 (與輸入等長，有相位延遲)
+    #[wasm_bindgen]
+    pub fn filter(&self, input: &[f32]) -> Vec<f32> {
+        self.apply(input)
+    }
+
+    /// 零相位濾波 (filtfilt)
+    ///
+    /// # Arguments
+    /// * `input` - 輸入信號 (Float32Array)
+    ///
+    /// # Returns
+    /// 濾波後的信號 (無相位延遲，幅度響應為設計值的平方)
+    #[wasm_bindgen]
+    pub fn filtfilt(&self, input: &[f32]) -> Vec<f32> {
+        self.apply_zero_phase(input)
+    }
+
+    /// 幅度響應
+    ///
+    /// # Arguments
+    /// * `frequencies_hz` - 要計算的頻率 (Hz)
+    ///
+    /// # Returns
+    /// 每個頻率的增益 (dB)
+    #[wasm_bindgen]
+    pub fn get_magnitude_response_db(&self, frequencies_hz: &[f32]) -> Vec<f32> {
+        frequencies_hz
+            .iter()
+            .map(|&f| (20.0 * self.response(f).norm().max(1e-300).log10()) as f32)
+            .collect()
+    }
+
+    /// 相位響應
+    ///
+    /// # Arguments
+    /// * `frequencies_hz` - 要計算的頻率 (Hz)
+    ///
+    /// # Returns
+    /// 每個頻率的相位 (弧度，-π..π)
+    #[wasm_bindgen]
+    pub fn get_phase_response(&self, frequencies_hz: &[f32]) -> Vec<f32> {
+        frequencies_hz.iter().map(|&f| self.response(f).arg() as f32).collect()
+    }
+}
+
+/// 設計 IIR 濾波器 (JavaScript 接口)
+///
+/// # Arguments
+/// * `family` - "butterworth"、"chebyshev1"、"chebyshev2" 或 "elliptic"
+/// * `band_type` - "lowpass"、"highpass"、"bandpass" 或 "bandstop" ("notch")
+/// * `order` - 原型階數 (1-32；帶通 / 帶阻的實際階數為 2 * order)
+/// * `sample_rate` - 採樣率 (Hz)
+/// * `low_hz` - 高通截止頻率；帶通 / 帶阻的下邊界 (低通時忽略)
+/// * `high_hz` - 低通截止頻率；帶通 / 帶阻的上邊界 (高通時忽略)
+/// * `ripple_db` - 通帶波紋 (dB，Chebyshev I 與橢圓濾波器，典型值: 1)
+/// * `attenuation_db` - 阻帶衰減 (dB，Chebyshev II 與橢圓濾波器，典型值: 60)
+///
+/// # Returns
+/// IirFilter 對象；參數無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn design_iir_filter(
+    family: &str,
+    band_type: &str,
+    order: usize,
+    sample_rate: f32,
+    low_hz: f32,
+    high_hz: f32,
+    ripple_db: f32,
+    attenuation_db: f32,
+) -> Result<IirFilter, JsError> {
+    let family = FilterFamily::from_name(family).ok_or_else(|| FilterError::UnknownFamily(family.to_string()))?;
+    let band = BandType::from_name(band_type).ok_or_else(|| FilterError::UnknownBandType(band_type.to_string()))?;
+    Ok(IirFilter::design(family, band, order, sample_rate, low_hz, high_hz, ripple_db, attenuation_db)?)
+}
@@ -0,0 +1,307 @@
+// ============================================================
+// 可分步執行的長時間計算作業
+// 每次 step() 處理固定數量的幀，期間可查詢進度或取消，
+// 使 UI 能顯示進度條並在用戶再次縮放時丟棄過時的渲染。
+// ============================================================
+
+use wasm_bindgen::prelude::*;
+
+use crate::stft::frame_count;
+use crate::{ImagePlan, SpectrogramEngine};
+
+/// 作業狀態
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum JobStatus {
+    Running,
+    Completed,
+    Cancelled,
+}
+
+/// 作業類型及其中間結果
+enum JobKind {
+    /// 對應 compute_spectrogram_u8
+    SpectrogramU8 {
+        step: usize,
+        num_frames: usize,
+        output_bins: usize,
+        gain_db: f32,
+        range_db: f32,
+        magnitudes: Vec<f32>,
+        result: Vec<u8>,
+    },
+    /// 對應 compute_spectrogram_image：先計算引用幀的頻譜，再逐行色彩化
+    Image {
+        plan: Option<ImagePlan>,
+        gain_db: f32,
+        range_db: f32,
+        frame_spectra: Vec<u8>,
+        output: Vec<u8>,
+    },
+}
+
+/// SpectrogramJob: 可恢復的頻譜圖計算作業
+///
+/// 典型用法 (JavaScript):
+/// ```javascript
+/// const job = engine.start_image_job(audio, width, height, noverlap, gain, range);
+/// while (!job.step(engine, 64)) {
+///     progressBar.value = job.get_progress();
+///     await new Promise(r => setTimeout(r, 0));  // 讓出事件迴圈
+///     if (isStale) { job.cancel(); break; }
+/// }
+/// const pixels = job.take_result();
+/// ```
+#[wasm_bindgen]
+pub struct SpectrogramJob {
+    audio: Vec<f32>,
+    // 創建作業時的 FFT 大小與配置版本 (全局唯一)；引擎配置改變或傳入其他引擎時作業自動取消
+    fft_size: usize,
+    config_version: u64,
+    kind: JobKind,
+    processed_units: usize,
+    total_units: usize,
+    status: JobStatus,
+}
+
+impl SpectrogramJob {
+    pub(crate) fn spectrogram_u8(
+        engine: &SpectrogramEngine,
+        audio_data: &[f32],
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+    ) -> SpectrogramJob {
+        let fft_size = engine.get_fft_size();
+        let step = fft_size.saturating_sub(noverlap);
+        let num_frames = frame_count(audio_data.len(), fft_size, step);
+        let output_bins = engine.output_bins();
+
+        SpectrogramJob {
+            audio: audio_data.to_vec(),
+            fft_size,
+            config_version: engine.config_version(),
+            kind: JobKind::SpectrogramU8 {
+                step,
+                num_frames,
+                output_bins,
+                gain_db,
+                range_db,
+                magnitudes: vec![0.0; fft_size / 2 * num_frames],
+                result: vec![0; output_bins * num_frames],
+            },
+            processed_units: 0,
+            total_units: num_frames,
+            status: JobStatus::Running,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn image(
+        engine: &SpectrogramEngine,
+        audio_data: &[f32],
+        width: usize,
+        height: usize,
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+    ) -> SpectrogramJob {
+        let plan = engine.plan_image(audio_data.len(), width, height, noverlap);
+        // 工作單位 = 需要計算的幀數 + 輸出行數
+        let (total_units, spectra_len) = match &plan {
+            Some(plan) => (plan.needed_frames.len() + plan.height, plan.needed_frames.len() * plan.spec_height()),
+            None => (0, 0),
+        };
+
+        SpectrogramJob {
+            audio: audio_data.to_vec(),
+            fft_size: engine.get_fft_size(),
+            config_version: engine.config_version(),
+            kind: JobKind::Image {
+                plan,
+                gain_db,
+                range_db,
+                frame_spectra: vec![0; spectra_len],
+                output: vec![0; width * height * 4],
+            },
+            processed_units: 0,
+            total_units,
+            status: JobStatus::Running,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl SpectrogramJob {
+    /// 執行一步計算
+    ///
+    /// # Arguments
+    /// * `engine` - 創建此作業的 SpectrogramEngine
+    /// * `max_frames` - 本步最多處理的幀數（圖像作業的色彩化階段按行計）
+    ///
+    /// # Returns
+    /// 作業已完成或已取消時返回 true
+    #[wasm_bindgen]
+    pub fn step(&mut self, engine: &mut SpectrogramEngine, max_frames: usize) -> bool {
+        if self.status != JobStatus::Running {
+            return true;
+        }
+
+        // 引擎的 FFT 大小、濾波器組或色彩映射已改變，或不是創建作業的引擎，結果已過時
+        if engine.get_fft_size() != self.fft_size || engine.config_version() != self.config_version {
+            self.cancel();
+            return true;
+        }
+
+        let budget = max_frames.max(1);
+        let fft_size = self.fft_size;
+        let freq_bins = fft_size / 2;
+        let start = self.processed_units;
+        let end = (start + budget).min(self.total_units);
+
+        match &mut self.kind {
+            JobKind::SpectrogramU8 {
+                step,
+                num_frames,
+                output_bins,
+                gain_db,
+                range_db,
+                magnitudes,
+                result,
+            } => {
+                if end > start {
+                    let fft = engine.plan_fft();
+                    let rows = &mut magnitudes[start * freq_bins..end * freq_bins];
+                    engine.magnitude_rows(fft.as_ref(), &self.audio, *step, start, rows);
+                    engine.quantize_rows(
+                        &magnitudes[start * freq_bins..end * freq_bins],
+                        *gain_db,
+                        *range_db,
+                        &mut result[start * *output_bins..end * *output_bins],
+                    );
+                }
+
+                if end == self.total_units {
+                    // 保存幅度值供 get_peaks() 使用，與 compute_spectrogram_u8 行為一致
+                    engine.store_magnitudes(std::mem::take(magnitudes), *num_frames, *step);
+                }
+            }
+            JobKind::Image {
+                plan,
+                gain_db,
+                range_db,
+                frame_spectra,
+                output,
+            } => {
+                if let Some(plan) = plan {
+                    let spec_height = plan.spec_height();
+                    let num_needed = plan.needed_frames.len();
+
+                    // 階段 1: 引用幀的頻譜
+                    let spectra_end = end.min(num_needed);
+                    if start < spectra_end {
+                        let fft = engine.plan_fft();
+                        engine.image_frame_spectra(
+                            fft.as_ref(),
+                            &self.audio,
+                            plan,
+                            start,
+                            *gain_db,
+                            *range_db,
+                            &mut frame_spectra[start * spec_height..spectra_end * spec_height],
+                        );
+                    }
+
+                    // 階段 2: 逐行插值並色彩化
+                    let row_start = start.max(num_needed) - num_needed;
+                    let row_end = end.max(num_needed) - num_needed;
+                    if row_start < row_end {
+                        let row_len = plan.width * 4;
+                        engine.colorize_image_rows(
+                            plan,
+                            frame_spectra,
+                            row_start,
+                            &mut output[row_start * row_len..row_end * row_len],
+                        );
+                    }
+                }
+            }
+        }
+
+        self.processed_units = end;
+        if end == self.total_units {
+            self.status = JobStatus::Completed;
+            return true;
+        }
+        false
+    }
+
+    /// 取消作業（已計算的部分結果將被丟棄）
+    #[wasm_bindgen]
+    pub fn cancel(&mut self) {
+        if self.status == JobStatus::Running {
+            self.status = JobStatus::Cancelled;
+            self.audio = Vec::new();
+            match &mut self.kind {
+                JobKind::SpectrogramU8 { magnitudes, result, .. } => {
+                    *magnitudes = Vec::new();
+                    *result = Vec::new();
+                }
+                JobKind::Image { plan, frame_spectra, output, .. } => {
+                    *plan = None;
+                    *frame_spectra = Vec::new();
+                    *output = Vec::new();
+                }
+            }
+        }
+    }
+
+    /// 獲取進度 (0.0 - 1.0)
+    #[wasm_bindgen]
+    pub fn get_progress(&self) -> f32 {
+        if self.total_units == 0 {
+            return if self.status == JobStatus::Running { 0.0 } else { 1.0 };
+        }
+        self.processed_units as f32 / self.total_units as f32
+    }
+
+    /// 已處理的工作單位數（幀數，圖像作業另加已色彩化的行數）
+    #[wasm_bindgen]
+    pub fn get_processed_units(&self) -> usize {
+        self.processed_units
+    }
+
+    /// 工作單位總數
+    #[wasm_bindgen]
+    pub fn get_total_units(&self) -> usize {
+        self.total_units
+    }
+
+    /// 作業是否已完成
+    #[wasm_bindgen]
+    pub fn is_done(&self) -> bool {
+        self.status == JobStatus::Completed
+    }
+
+    /// 作業是否已被取消
+    #[wasm_bindgen]
+    pub fn is_cancelled(&self) -> bool {
+        self.status == JobStatus::Cancelled
+    }
+
+    /// 取出計算結果
+    ///
+    /// # Returns
+    /// 頻譜作業：與 compute_spectrogram_u8 相同的 Uint8Array；
+    /// 圖像作業：與 compute_spectrogram_image 相同的 RGBA 數據。
+    /// 作業未完成或已取消時返回空數組。
+    #[wasm_bindgen]
+    pub fn take_result(&mut self) -> Vec<u8> {
+        if self.status != JobStatus::Completed {
+            return Vec::new();
+        }
+        match &mut self.kind {
+            JobKind::SpectrogramU8 { result, .. } => std::mem::take(result),
+            JobKind::Image { output, .. } => std::mem::take(output),
+        }
+    }
+}
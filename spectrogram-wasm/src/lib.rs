@@ -1,28 +1,115 @@
+// 信號處理迴圈按索引書寫以對照公式，不改寫為迭代器形式
+#![allow(clippy::needless_range_loop)]
+
 use wasm_bindgen::prelude::*;
 use rustfft::FftPlanner;
-use num_complex::Complex;
+use std::borrow::Cow;
 use std::f32::consts::PI;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+mod diagnostics;
+mod features;
+mod fir;
+mod flac;
+mod guano;
+mod harmonics;
+mod iir;
+mod job;
+mod parallel;
+mod peaks;
+mod playback;
+mod recording_time;
+mod resample;
+mod ridge;
+mod simd;
+mod spectrum_peaks;
+mod stft;
+mod timestamp;
+mod tone;
+mod vendor_meta;
+mod wav;
+mod wav_edit;
+mod welch;
+mod zc;
+
+pub use diagnostics::{
+    diagnose_audio, diagnose_audio_file, AudioDiagnostics, ChannelDiagnostics, DiagnosticsError, DEFAULT_MIN_CLIP_RUN,
+};
+pub use features::SpectralFeatures;
+pub use flac::{
+    convert_wav_to_flac, decode_flac, read_flac_info, FlacAudio, FlacBlock, FlacEncoder, FlacError, FlacInfo,
+    GUANO_COMMENT_KEY,
+};
+pub use fir::{design_equiripple_filter, design_fir_filter, FirError, FirFilter};
+pub use guano::{parse_guano, read_guano, write_guano, GuanoField, GuanoMetadata, GuanoValue, GUANO_CHUNK_ID};
+pub use harmonics::HarmonicAnalysis;
+pub use iir::{design_iir_filter, BandType, FilterError, FilterFamily, IirFilter};
+pub use job::SpectrogramJob;
+pub use peaks::SpectralPeaks;
+pub use playback::{
+    frequency_division, heterodyne, pitch_shift, render_frequency_division, render_heterodyne, render_pitch_shift,
+    PlaybackError,
+};
+pub use recording_time::{
+    resolve_recording_timestamp, timestamp_from_filename, ResolvedTimestamp, TimestampConfidence,
+};
+pub use resample::{
+    create_resampler, decimate_audio, resample_audio, ResampleError, ResampleQuality, Resampler,
+};
+pub use ridge::RidgeContours;
+pub use spectrum_peaks::{
+    analyze_spectrum_peaks, find_spectrum_peaks, SpectrumError, SpectrumPeak, SpectrumPeakList, EDGE_LEVELS_DB,
+};
+pub use timestamp::Timestamp;
+pub use tone::{compute_goertzel_tracks, compute_sliding_dft_tracks, ToneTracks};
+pub use vendor_meta::{
+    parse_audiomoth_comment, parse_wamd, read_recording_metadata, RecordingMetadata, WAMD_CHUNK_ID,
+};
+pub use wav::{
+    decode_wav, read_wav_info, replace_chunk, SampleFormat, WavAudio, WavChunk, WavError, WavFormat, WavInfo,
+    WAVE_FORMAT_EXTENSIBLE, WAVE_FORMAT_IEEE_FLOAT, WAVE_FORMAT_PCM,
+};
+pub use wav_edit::{concatenate, crop, crop_wav, split, split_wav, EditError, WavConcatenator, WavSegments};
+pub use welch::{compute_welch_spectrum, WelchError, WelchSpectrum};
+pub use zc::{convert_to_zc, read_zc, ZcError, ZcRecording};
+
+use simd::{Kernels, SparseFilterBank};
+use stft::{frame_count, frame_magnitudes, FrameScratch};
+
+// 啟用 `parallel` 特性的 wasm 構建需要在 JS 端先調用 initThreadPool(navigator.hardwareConcurrency)
+#[cfg(all(feature = "parallel", target_arch = "wasm32"))]
+pub use wasm_bindgen_rayon::init_thread_pool;
 
 /// SpectrogramEngine: 處理音頻頻譜圖計算
 /// 將 FFT、窗函數應用、濾波器組應用和 dB 轉換從 JavaScript 移到 Rust
+/// 下一個配置版本號 (所有引擎共用的計數器)
+fn next_config_version() -> u64 {
+    static NEXT_CONFIG_VERSION: AtomicU64 = AtomicU64::new(1);
+    NEXT_CONFIG_VERSION.fetch_add(1, Ordering::Relaxed)
+}
+
 #[wasm_bindgen]
 pub struct SpectrogramEngine {
     fft_size: usize,
     _window_func: String,  // 保留用於調試
     window_values: Vec<f32>,
     planner: FftPlanner<f32>,
-    scratch_buffer: Vec<Complex<f32>>,
-    _output_buffer: Vec<f32>,  // 保留用於未來擴展
+    // 持久輸出緩衝區：JS 可通過指針直接建立 Float32Array 視圖 (零複製)；
+    // 在第一次 compute_spectrogram_in_place 之前為空
+    output_buffer: Vec<f32>,
     _alpha: f32,  // 保留用於未來擴展
     // 濾波器組相關字段
-    // 扁平化的濾波器組矩陣 (行優先順序)
-    // 維度: num_filters x (fft_size / 2 + 1)
-    filter_bank: Vec<f32>,
+    // 由扁平化矩陣轉換的稀疏表示；兩條路徑沿用各自原有的行步長：
+    // compute_spectrogram_u8 為 fft_size / 2，compute_spectrogram_image 為 fft_size / 2 + 1
+    filter_bank: SparseFilterBank,
+    image_filter_bank: SparseFilterBank,
     num_filters: usize,
     use_filter_bank: bool,
     // 內部緩衝區：存儲最後計算的線性幅度值 (用於峰值檢測)
     last_magnitude_buffer: Vec<f32>,
     last_num_frames: usize,
+    last_step: usize,
     last_global_max: f32,
     // 色彩映射：256 種顏色的 RGBA 值 (u32 packed)
     color_map: Vec<u32>,
@@ -30,8 +117,16 @@ pub struct SpectrogramEngine {
     current_scale: String,  // "linear", "mel", "log", "bark", "erb"
     freq_min: f32,
     freq_max: f32,
-    // 輸出緩衝區 (避免每次分配)
+    // 輸出緩衝區 (避免每次分配，JS 可直接建立 Uint8Array / Uint8ClampedArray 視圖)
+    u8_buffer: Vec<u8>,
     image_buffer: Vec<u8>,
+    // SIMD 計算核心 (可在運行時切換回標量實現以驗證結果)
+    kernels: Kernels,
+    // 配置版本號：創建引擎以及濾波器組或色彩映射改變時取自全局計數器，用於使進行中的作業失效；
+    // 全局唯一，另一個引擎的作業不會因版本號碰巧相同而被當作當前結果
+    config_version: u64,
+    // 多通道音頻數據 (索引: channels[channel_idx][sample_idx])
+    channels: Vec<Vec<f32>>,
 }
 
 #[wasm_bindgen]
@@ -52,29 +147,33 @@ impl SpectrogramEngine {
         // 創建 FFT 規劃器
         let planner = FftPlanner::new();
         
-        // 預分配緩衝區
-        let scratch_buffer = vec![Complex::default(); fft_size];
-        let output_buffer = vec![0.0; fft_size / 2];
+        // 預留一幀的容量；長度為 0，計算之前 get_output_buffer_len() 不會報告虛假的靜音幀
+        let output_buffer = Vec::with_capacity(fft_size / 2);
         
         SpectrogramEngine {
             fft_size,
             _window_func: window_func,
             window_values,
             planner,
-            scratch_buffer,
-            _output_buffer: output_buffer,
+            output_buffer,
             _alpha: alpha,
-            filter_bank: Vec::new(),
+            filter_bank: SparseFilterBank::default(),
+            image_filter_bank: SparseFilterBank::default(),
             num_filters: 0,
             use_filter_bank: false,
             last_magnitude_buffer: Vec::new(),
             last_num_frames: 0,
+            last_step: 0,
             last_global_max: 0.0,
             color_map: Vec::new(),  // 256 * 4 bytes
             current_scale: "linear".to_string(),
             freq_min: 0.0,
             freq_max: 0.0,
+            u8_buffer: Vec::new(),
             image_buffer: Vec::new(),
+            kernels: Kernels::detect(),
+            config_version: next_config_version(),
+            channels: Vec::new(),
         }
     }
 
@@ -88,17 +187,29 @@ impl SpectrogramEngine {
     /// 每行長度: fft_size / 2 + 1
     #[wasm_bindgen]
     pub fn load_filter_bank(&mut self, flat_weights: &[f32], num_filters: usize) {
-        self.filter_bank = flat_weights.to_vec();
+        let freq_bins = self.fft_size / 2;
+        let sparse = |row_len: usize| {
+            if flat_weights.is_empty() || num_filters == 0 {
+                SparseFilterBank::default()
+            } else {
+                SparseFilterBank::from_dense(flat_weights, num_filters, row_len, freq_bins)
+            }
+        };
+        self.filter_bank = sparse(freq_bins);
+        self.image_filter_bank = sparse(freq_bins + 1);
         self.num_filters = num_filters;
         self.use_filter_bank = true;
+        self.config_version = next_config_version();
     }
 
     /// 清除濾波器組 (禁用濾波)
     #[wasm_bindgen]
     pub fn clear_filter_bank(&mut self) {
-        self.filter_bank.clear();
+        self.filter_bank = SparseFilterBank::default();
+        self.image_filter_bank = SparseFilterBank::default();
         self.num_filters = 0;
         self.use_filter_bank = false;
+        self.config_version = next_config_version();
     }
 
     /// 計算 FFT 頻譜（返回幅度值，不進行 dB 轉換）
@@ -115,51 +226,26 @@ impl SpectrogramEngine {
         audio_data: &[f32],
         noverlap: usize,
     ) -> Vec<f32> {
-        let step = self.fft_size - noverlap;
-        let num_frames = if audio_data.len() >= self.fft_size {
-            (audio_data.len() - self.fft_size) / step + 1
-        } else {
-            0
-        };
-        
-        let freq_bins = self.fft_size / 2;
-        let mut result = vec![0.0f32; freq_bins * num_frames];
-        
-        // 獲取 FFT 算法
-        let fft = self.planner.plan_fft_forward(self.fft_size);
-        
-        let mut pos = 0;
-        for frame_idx in 0..num_frames {
-            if pos + self.fft_size > audio_data.len() {
-                break;
-            }
-            
-            // 應用窗函數並準備 FFT 輸入
-            for i in 0..self.fft_size {
-                let windowed = audio_data[pos + i] * self.window_values[i];
-                self.scratch_buffer[i] = Complex {
-                    re: windowed,
-                    im: 0.0,
-                };
-            }
-            
-            // 執行 FFT
-            fft.process(&mut self.scratch_buffer);
-            
-            // 計算幅度（不轉換為 dB，讓 JavaScript 處理）
-            let scale = 2.0 / self.fft_size as f32;
-            for i in 0..freq_bins {
-                let c = self.scratch_buffer[i];
-                let magnitude = (c.re * c.re + c.im * c.im).sqrt();
-                result[frame_idx * freq_bins + i] = magnitude * scale;
-            }
-            
-            pos += step;
-        }
-        
+        let mut result = Vec::new();
+        self.spectrogram_into(audio_data, noverlap, &mut result);
         result
     }
 
+    /// 計算 FFT 頻譜到引擎持有的輸出緩衝區 (零複製版本的 compute_spectrogram)
+    ///
+    /// 結果通過 get_output_buffer_ptr() / get_output_buffer_len() 訪問:
+    /// `new Float32Array(wasm_memory().buffer, ptr, len)`
+    ///
+    /// # Returns
+    /// 緩衝區中的 f32 元素數量
+    #[wasm_bindgen]
+    pub fn compute_spectrogram_in_place(&mut self, audio_data: &[f32], noverlap: usize) -> usize {
+        let mut buffer = std::mem::take(&mut self.output_buffer);
+        self.spectrogram_into(audio_data, noverlap, &mut buffer);
+        self.output_buffer = buffer;
+        self.output_buffer.len()
+    }
+
     /// 獲取窗函數值（用於調試/驗證）
     #[wasm_bindgen]
     pub fn get_window_values(&self) -> Vec<f32> {
@@ -184,6 +270,28 @@ impl SpectrogramEngine {
         self.fft_size / 2
     }
 
+    /// 啟用或禁用 SIMD 計算核心
+    /// 
+    /// 禁用時使用標量參考實現（精確 log10），可用於驗證 SIMD 結果。
+    /// 兩者的誤差界見 simd.rs 模塊說明。
+    #[wasm_bindgen]
+    pub fn set_simd_enabled(&mut self, enabled: bool) {
+        self.kernels = if enabled {
+            Kernels::detect()
+        } else {
+            Kernels::scalar()
+        };
+    }
+
+    /// 獲取當前使用的計算後端名稱
+    /// 
+    /// # Returns
+    /// "avx2"、"sse2"、"simd128" 或 "scalar"
+    #[wasm_bindgen]
+    pub fn get_simd_backend(&self) -> String {
+        self.kernels.backend().name().to_string()
+    }
+
     /// 計算頻譜圖並轉換為 u8 量化值 (0-255)
     /// 
     /// # Arguments
@@ -203,134 +311,169 @@ impl SpectrogramEngine {
         gain_db: f32,
         range_db: f32,
     ) -> Vec<u8> {
-        let step = self.fft_size - noverlap;
-        let num_frames = if audio_data.len() >= self.fft_size {
-            (audio_data.len() - self.fft_size) / step + 1
-        } else {
-            0
-        };
-        
-        let fft = self.planner.plan_fft_forward(self.fft_size);
-        let freq_bins = self.fft_size / 2;
-        
-        // 決定輸出大小
-        let output_bins = if self.use_filter_bank && self.num_filters > 0 {
-            self.num_filters
-        } else {
-            freq_bins
-        };
-        
-        let mut result = vec![0u8; output_bins * num_frames];
-        
-        // 初始化內部緩衝區用於存儲所有時間幀的線性幅度值
-        let mut all_magnitudes = vec![0.0f32; freq_bins * num_frames];
-        let mut global_max = 0.0f32;
-        
-        let mut pos = 0;
+        let mut result = Vec::new();
+        self.spectrogram_u8_into(audio_data, noverlap, gain_db, range_db, &mut result);
+        result
+    }
+
+    /// 計算 u8 頻譜圖到引擎持有的輸出緩衝區 (零複製版本的 compute_spectrogram_u8)
+    ///
+    /// 結果通過 get_u8_buffer_ptr() / get_u8_buffer_len() 訪問。
+    /// 同樣更新 get_peaks() 使用的內部幅度緩衝區。
+    ///
+    /// # Returns
+    /// 緩衝區中的字節數
+    #[wasm_bindgen]
+    pub fn compute_spectrogram_u8_in_place(
+        &mut self,
+        audio_data: &[f32],
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+    ) -> usize {
+        let mut buffer = std::mem::take(&mut self.u8_buffer);
+        self.spectrogram_u8_into(audio_data, noverlap, gain_db, range_db, &mut buffer);
+        self.u8_buffer = buffer;
+        self.u8_buffer.len()
+    }
+
+    /// 預分配指定數量的音頻通道 (清除已加載的數據)
+    /// 
+    /// # Arguments
+    /// * `num_channels` - 音頻通道數量
+    #[wasm_bindgen]
+    pub fn resize_channels(&mut self, num_channels: usize) {
+        self.channels.clear();
+        self.channels.resize(num_channels, Vec::new());
+    }
+
+    /// 加載單個通道的完整音頻數據
+    /// 
+    /// # Arguments
+    /// * `channel_idx` - 通道索引
+    /// * `data` - 音頻樣本數據 (Float32Array)
+    #[wasm_bindgen]
+    pub fn load_channel(&mut self, channel_idx: usize, data: &[f32]) {
+        if channel_idx >= self.channels.len() {
+            return;
+        }
         
-        // 預計算 dB 範圍值，以優化迴圈
-        let gain_db_neg = -gain_db;
-        let range_db_reciprocal = 255.0 / range_db;
+        self.channels[channel_idx] = data.to_vec();
+    }
+
+    /// 獲取已加載的通道數量
+    #[wasm_bindgen]
+    pub fn get_num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// 清除所有通道數據
+    #[wasm_bindgen]
+    pub fn clear_channels(&mut self) {
+        self.channels.clear();
+    }
+
+    /// 計算多通道頻譜圖並轉換為 u8 量化值 (0-255)
+    /// 
+    /// # Arguments
+    /// * `mode` - 通道模式:
+    ///   - "channel": 單個通道 (`channel_idx`)
+    ///   - "mid": 所有通道的平均 (mixdown)
+    ///   - "side": 通道 0 與通道 1 的差 (L - R) / 2
+    ///   - "max": 每個頻率 bin 取所有通道中的最大值
+    /// * `channel_idx` - "channel" 模式使用的通道索引
+    /// * `start_sample` - 起始樣本索引
+    /// * `end_sample` - 結束樣本索引（不包含）
+    /// * `noverlap` - 重疊樣本數
+    /// * `gain_db` - 增益 dB 值
+    /// * `range_db` - 動態範圍 dB 值
+    /// 
+    /// # Returns
+    /// 與 compute_spectrogram_u8 相同佈局的 Uint8Array；同樣更新 get_peaks() 使用的幅度緩衝區
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn compute_multichannel_u8(
+        &mut self,
+        mode: &str,
+        channel_idx: usize,
+        start_sample: usize,
+        end_sample: usize,
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+    ) -> Vec<u8> {
+        let channels = std::mem::take(&mut self.channels);
+        let range = channel_range(&channels, start_sample, end_sample);
+        let mut result = Vec::new();
         
-        for frame_idx in 0..num_frames {
-            if pos + self.fft_size > audio_data.len() {
-                break;
+        match ChannelMode::from_name(mode) {
+            ChannelMode::Max => {
+                self.max_spectrogram_u8_into(&channels, range, noverlap, gain_db, range_db, &mut result);
             }
-            
-            // 第一步: 應用窗函數並準備 FFT 輸入
-            for i in 0..self.fft_size {
-                let windowed = audio_data[pos + i] * self.window_values[i];
-                self.scratch_buffer[i] = Complex {
-                    re: windowed,
-                    im: 0.0,
-                };
-            }
-            
-            // 第二步: 執行 FFT
-            fft.process(&mut self.scratch_buffer);
-            
-            // 第三步: 計算線性幅度
-            let scale = 2.0 / self.fft_size as f32;
-            let mut magnitude = vec![0.0f32; freq_bins];
-            for i in 0..freq_bins {
-                let c = self.scratch_buffer[i];
-                let mag = (c.re * c.re + c.im * c.im).sqrt() * scale;
-                magnitude[i] = mag;
-                
-                // 更新全局最大值
-                if mag > global_max {
-                    global_max = mag;
-                }
-                
-                // 保存到內部緩衝區
-                all_magnitudes[frame_idx * freq_bins + i] = mag;
-            }
-            
-            // 第四步: 應用濾波器組 (如果啟用)
-            let filtered = if self.use_filter_bank && self.num_filters > 0 {
-                self.apply_filter_bank(&magnitude)
-            } else {
-                magnitude.clone()
-            };
-            
-            // 第五步: 轉換為 dB 並量化到 0-255
-            for i in 0..filtered.len() {
-                let mag = filtered[i];
-                // 防止 log10(0)，使用最小值 1e-10
-                let safe_mag = if mag > 1e-10 { mag } else { 1e-10 };
-                let db = 20.0 * safe_mag.log10();
-                
-                // 映射到 0-255 範圍
-                let u8_val = if db < gain_db_neg - range_db {
-                    0
-                } else if db > gain_db_neg {
-                    255
-                } else {
-                    ((db - (gain_db_neg - range_db)) * range_db_reciprocal) as u8
-                };
-                
-                result[frame_idx * output_bins + i] = u8_val;
+            channel_mode => {
+                let signal = mix_channels(&channels, channel_mode, channel_idx, range);
+                self.spectrogram_u8_into(&signal, noverlap, gain_db, range_db, &mut result);
             }
-            
-            pos += step;
         }
         
-        // 保存最後的幅度值和幀數到內部狀態，供 get_peaks() 使用
-        self.last_magnitude_buffer = all_magnitudes;
-        self.last_num_frames = num_frames;
-        self.last_global_max = global_max;
-        
+        self.channels = channels;
         result
     }
 
-    /// 內部方法: 應用濾波器組 (矩陣乘法)
+    /// 計算多通道光譜圖像 (FFT -> 重採樣 -> 色彩化)
     /// 
-    /// magnitude: 線性幅度頻譜 (長度: freq_bins)
-    /// 返回: 濾波後的幅度 (長度: num_filters)
-    fn apply_filter_bank(&self, magnitude: &[f32]) -> Vec<f32> {
-        let mut result = vec![0.0f32; self.num_filters];
-        
-        if self.filter_bank.is_empty() || magnitude.is_empty() {
-            return result;
-        }
-        
-        let freq_bins = magnitude.len();
+    /// 參數含義與 compute_multichannel_u8 和 compute_spectrogram_image 相同。
+    /// 
+    /// # Returns
+    /// RGBA 圖像數據 (Uint8ClampedArray) 大小：width * height * 4
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn compute_multichannel_image(
+        &mut self,
+        mode: &str,
+        channel_idx: usize,
+        start_sample: usize,
+        end_sample: usize,
+        width: usize,
+        height: usize,
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+    ) -> Vec<u8> {
+        let channels = std::mem::take(&mut self.channels);
+        let range = channel_range(&channels, start_sample, end_sample);
+        let mut output = Vec::new();
         
-        // 矩陣乘法: result[i] = sum(magnitude[j] * filter_bank[i * freq_bins + j])
-        for i in 0..self.num_filters {
-            let mut sum = 0.0f32;
-            let row_start = i * freq_bins;
-            
-            for j in 0..freq_bins {
-                if row_start + j < self.filter_bank.len() {
-                    sum += magnitude[j] * self.filter_bank[row_start + j];
-                }
+        match ChannelMode::from_name(mode) {
+            ChannelMode::Max => {
+                self.max_image_into(&channels, range, width, height, noverlap, gain_db, range_db, &mut output);
+            }
+            channel_mode => {
+                let signal = mix_channels(&channels, channel_mode, channel_idx, range);
+                self.image_into(&signal, width, height, noverlap, gain_db, range_db, &mut output);
             }
-            
-            result[i] = sum;
         }
         
-        result
+        self.channels = channels;
+        output
+    }
+
+    /// 創建分步執行的 compute_spectrogram_u8 作業
+    /// 
+    /// 參數與 compute_spectrogram_u8 相同。作業完成時同樣更新
+    /// get_peaks() 等使用的內部幅度緩衝區。
+    /// 
+    /// # Returns
+    /// SpectrogramJob，通過 job.step(engine, n) 逐步推進
+    #[wasm_bindgen]
+    pub fn start_spectrogram_u8_job(
+        &self,
+        audio_data: &[f32],
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+    ) -> SpectrogramJob {
+        SpectrogramJob::spectrogram_u8(self, audio_data, noverlap, gain_db, range_db)
     }
 
     /// 獲取峰值檢測結果 (頻率 bin 索引)
@@ -409,121 +552,718 @@ impl SpectrogramEngine {
             let frame_start = frame_idx * freq_bins;
             let frame_end = frame_start + freq_bins;
             
-            if frame_end > self.last_magnitude_buffer.len() {
-                break;
+            if frame_end > self.last_magnitude_buffer.len() {
+                break;
+            }
+            
+            let frame_data = &self.last_magnitude_buffer[frame_start..frame_end];
+            
+            // 找到此幀中的最大值
+            let max_val = frame_data.iter()
+                .fold(0.0f32, |acc, &val| {
+                    if val > acc { val } else { acc }
+                });
+            
+            // 僅當最大值超過閾值時才記錄幅度值
+            if max_val >= threshold {
+                magnitudes[frame_idx] = max_val;
+            }
+        }
+        
+        magnitudes
+    }
+
+    /// 獲取最後計算的全局最大幅度值
+    /// 
+    /// 此值在最後一次 compute_spectrogram_u8 調用時計算。
+    /// 用於與閾值進行比較以進行峰值檢測。
+    /// 
+    /// # Returns
+    /// 線性幅度值（未轉換為 dB）
+    #[wasm_bindgen]
+    pub fn get_global_max(&self) -> f32 {
+        self.last_global_max
+    }
+
+    /// 獲取每幀前 K 個頻譜峰值
+    ///
+    /// 基於在最後一次 compute_spectrogram_u8 調用中計算的線性幅度值。
+    /// 與 get_peaks 只返回每幀最大 bin 不同，此方法返回多個局部峰值，
+    /// 因此重疊的蝙蝠叫聲與諧波都能同時被檢出。
+    ///
+    /// # Arguments
+    /// * `sample_rate` - 音頻採樣率 (Hz)
+    /// * `max_peaks` - 每幀最多返回的峰值數
+    /// * `threshold_db` - 相對於參考最大值的閾值 (dB，典型值: -30)
+    /// * `threshold_mode` - "global" (相對於全局最大值) 或 "local" (相對於當前幀最大值)
+    /// * `interpolation` - "parabolic"、"gaussian" 或 "none"
+    /// * `min_prominence_db` - 最小峰值突出度 (dB，典型值: 6)
+    ///
+    /// # Returns
+    /// SpectralPeaks 對象，包含每個峰值的幀索引、頻率 (Hz)、幅度 (dB) 與突出度
+    #[wasm_bindgen]
+    pub fn find_spectral_peaks(
+        &self,
+        sample_rate: f32,
+        max_peaks: usize,
+        threshold_db: f32,
+        threshold_mode: &str,
+        interpolation: &str,
+        min_prominence_db: f32,
+    ) -> SpectralPeaks {
+        if self.last_magnitude_buffer.is_empty() || self.last_global_max <= 0.0 {
+            return SpectralPeaks::from_frames(&[]);
+        }
+
+        let params = peaks::PeakParams {
+            sample_rate,
+            fft_size: self.fft_size,
+            max_peaks,
+            threshold_db,
+            threshold_mode: peaks::ThresholdMode::from_name(threshold_mode),
+            interpolation: peaks::Interpolation::from_name(interpolation),
+            min_prominence_db,
+        };
+        let frames = peaks::find_peaks(
+            &self.last_magnitude_buffer,
+            self.fft_size / 2,
+            self.last_num_frames,
+            self.last_global_max,
+            &params,
+        );
+        SpectralPeaks::from_frames(&frames)
+    }
+
+    /// 將每幀頻譜峰值沿時間連接成叫聲輪廓 (脊線追蹤)
+    ///
+    /// 基於在最後一次 compute_spectrogram_u8 調用中計算的線性幅度值。
+    /// 每幀取最多 8 個超過閾值的峰值 (高斯插值)，再依頻率連續性、
+    /// 斜率約束與間隔容忍度連接。
+    ///
+    /// # Arguments
+    /// * `sample_rate` - 音頻採樣率 (Hz)
+    /// * `threshold_db` - 相對於全局最大值的峰值閾值 (dB，典型值: -40)
+    /// * `min_prominence_db` - 最小峰值突出度 (dB，典型值: 6)
+    /// * `max_jump_hz` - 相鄰幀之間允許的最大頻率變化 (Hz)
+    /// * `max_slope_change_hz` - 相鄰兩段斜率 (Hz/幀) 的最大變化，<= 0 表示不限制
+    /// * `max_gap_frames` - 允許跳過的最大幀數
+    /// * `min_length` - 輪廓的最少點數
+    /// * `method` - "greedy" (貪婪連接) 或 "viterbi" (動態規劃路徑搜索)
+    ///
+    /// # Returns
+    /// RidgeContours 對象，包含每個輪廓點的時間 (秒)、頻率 (Hz) 與幅度 (dB)
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn track_ridges(
+        &self,
+        sample_rate: f32,
+        threshold_db: f32,
+        min_prominence_db: f32,
+        max_jump_hz: f32,
+        max_slope_change_hz: f32,
+        max_gap_frames: usize,
+        min_length: usize,
+        method: &str,
+    ) -> RidgeContours {
+        if self.last_magnitude_buffer.is_empty() || self.last_global_max <= 0.0 || sample_rate <= 0.0 {
+            return RidgeContours::from_contours(&[], |_| 0.0);
+        }
+
+        let peak_params = peaks::PeakParams {
+            sample_rate,
+            fft_size: self.fft_size,
+            max_peaks: ridge::MAX_PEAKS_PER_FRAME,
+            threshold_db,
+            threshold_mode: peaks::ThresholdMode::Global,
+            interpolation: peaks::Interpolation::Gaussian,
+            min_prominence_db,
+        };
+        let frames = peaks::find_peaks(
+            &self.last_magnitude_buffer,
+            self.fft_size / 2,
+            self.last_num_frames,
+            self.last_global_max,
+            &peak_params,
+        );
+
+        let ridge_params = ridge::RidgeParams {
+            max_jump_hz,
+            max_slope_change_hz,
+            max_gap_frames,
+            min_length,
+        };
+        let contours = ridge::track_ridges(&frames, &ridge_params, ridge::RidgeMethod::from_name(method));
+
+        // 點的時間取幀中心
+        let step = self.last_step;
+        let half = self.fft_size / 2;
+        RidgeContours::from_contours(&contours, |frame| (frame * step + half) as f32 / sample_rate)
+    }
+
+    /// 計算每幀的頻譜特徵
+    ///
+    /// 基於在最後一次 compute_spectrogram_u8 調用中計算的線性幅度值，
+    /// 僅使用 flow..fhigh 頻帶內的 bin。
+    ///
+    /// # Arguments
+    /// * `sample_rate` - 音頻採樣率 (Hz)
+    /// * `flow` - 頻帶下限 (Hz)
+    /// * `fhigh` - 頻帶上限 (Hz)
+    /// * `rolloff` - 滾降能量比例 (0.0-1.0，典型值: 0.85)
+    /// * `band_edges` - 分頻帶邊界 (Hz，遞增)，例如 [20000, 40000, 60000] 得到 2 個頻帶
+    ///
+    /// # Returns
+    /// SpectralFeatures 對象，包含質心、帶寬、平坦度、熵、滾降、通量、波峰因數與分頻帶能量比
+    #[wasm_bindgen]
+    pub fn compute_spectral_features(
+        &self,
+        sample_rate: f32,
+        flow: f32,
+        fhigh: f32,
+        rolloff: f32,
+        band_edges: &[f32],
+    ) -> SpectralFeatures {
+        if self.last_magnitude_buffer.is_empty() || sample_rate <= 0.0 {
+            return SpectralFeatures::empty();
+        }
+
+        let params = features::FeatureParams {
+            sample_rate,
+            fft_size: self.fft_size,
+            flow,
+            fhigh,
+            rolloff,
+            band_edges: band_edges.to_vec(),
+        };
+        SpectralFeatures::compute(&self.last_magnitude_buffer, self.fft_size / 2, self.last_num_frames, &params)
+    }
+
+    /// 分析叫聲的諧波結構
+    ///
+    /// 基於在最後一次 compute_spectrogram_u8 調用中計算的線性幅度值，
+    /// 先對 [start_frame, end_frame) 內的幀取均方根平均頻譜，再估計基頻與諧波序列。
+    ///
+    /// # Arguments
+    /// * `sample_rate` - 音頻採樣率 (Hz)
+    /// * `start_frame` - 起始幀索引 (包含)
+    /// * `end_frame` - 結束幀索引 (不包含)，超出範圍時截斷
+    /// * `fmin` - 分析頻帶下限 (Hz)；基頻可低於此值
+    /// * `fmax` - 分析頻帶上限 (Hz)
+    /// * `max_harmonics` - 考慮的最高諧波次數 (典型值: 5)
+    /// * `tolerance` - 諧波頻率的相對容差 (典型值: 0.03)
+    /// * `threshold_db` - 諧波高於噪聲底的最小 dB 數 (典型值: 10)
+    ///
+    /// # Returns
+    /// HarmonicAnalysis 對象，包含基頻、主諧波次數與各諧波的頻率和相對能量
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn analyze_harmonics(
+        &self,
+        sample_rate: f32,
+        start_frame: usize,
+        end_frame: usize,
+        fmin: f32,
+        fmax: f32,
+        max_harmonics: usize,
+        tolerance: f32,
+        threshold_db: f32,
+    ) -> HarmonicAnalysis {
+        let freq_bins = self.fft_size / 2;
+        let end_frame = end_frame.min(self.last_num_frames).min(self.last_magnitude_buffer.len() / freq_bins.max(1));
+        if start_frame >= end_frame || sample_rate <= 0.0 {
+            return HarmonicAnalysis::empty();
+        }
+
+        // 均方根平均頻譜
+        let mut spectrum = vec![0.0f32; freq_bins];
+        for row in self.last_magnitude_buffer[start_frame * freq_bins..end_frame * freq_bins].chunks_exact(freq_bins) {
+            for (acc, &m) in spectrum.iter_mut().zip(row) {
+                *acc += m * m;
+            }
+        }
+        let count = (end_frame - start_frame) as f32;
+        for value in spectrum.iter_mut() {
+            *value = (*value / count).sqrt();
+        }
+
+        let params = harmonics::HarmonicParams {
+            sample_rate,
+            fft_size: self.fft_size,
+            fmin,
+            fmax,
+            max_harmonics,
+            tolerance,
+            threshold_db,
+        };
+        HarmonicAnalysis::analyze(&spectrum, &params)
+    }
+
+    /// 設置 256 色的色彩映射 (RGBA)
+    /// 
+    /// # Arguments
+    /// * `colors` - 256 * 4 字節的 RGBA 顏色數組
+    #[wasm_bindgen]
+    pub fn set_color_map(&mut self, colors: &[u8]) {
+        // 預期大小：256 * 4 = 1024 字節
+        if colors.len() != 1024 {
+            return;  // 無效的色彩映射大小，跳過
+        }
+        
+        // 轉換 [u8; 4] 成 u32 (RGBA 打包)
+        self.color_map.clear();
+        self.color_map.reserve(256);
+        
+        for i in 0..256 {
+            let offset = i * 4;
+            let r = colors[offset] as u32;
+            let g = colors[offset + 1] as u32;
+            let b = colors[offset + 2] as u32;
+            let a = colors[offset + 3] as u32;
+            
+            // 打包為 u32 (RGBA 順序)
+            let packed = (r << 24) | (g << 16) | (b << 8) | a;
+            self.color_map.push(packed);
+        }
+        self.config_version = next_config_version();
+    }
+
+    /// 設置光譜配置 (用於記錄，但主要用於驗證)
+    #[wasm_bindgen]
+    pub fn set_spectrum_config(&mut self, scale: String, freq_min: f32, freq_max: f32) {
+        self.current_scale = scale;
+        self.freq_min = freq_min;
+        self.freq_max = freq_max;
+    }
+
+    /// 計算完整的光譜圖像 (FFT -> 重採樣 -> 色彩化)
+    /// 
+    /// # Arguments
+    /// * `audio_data` - 單通道音頻數據 (Float32Array)
+    /// * `width` - 輸出圖像寬度 (時間軸)
+    /// * `height` - 輸出圖像高度 (頻率軸)
+    /// * `noverlap` - 窗重疊樣本數
+    /// * `gain_db` - 增益 (dB)
+    /// * `range_db` - 動態範圍 (dB)
+    /// 
+    /// # Returns
+    /// RGBA 圖像數據 (Uint8ClampedArray) 大小：width * height * 4
+    #[wasm_bindgen]
+    pub fn compute_spectrogram_image(
+        &mut self,
+        audio_data: &[f32],
+        width: usize,
+        height: usize,
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+    ) -> Vec<u8> {
+        let mut output = Vec::new();
+        self.image_into(audio_data, width, height, noverlap, gain_db, range_db, &mut output);
+        output
+    }
+
+    /// 渲染光譜圖像到引擎持有的 RGBA 緩衝區 (零複製版本的 compute_spectrogram_image)
+    ///
+    /// 結果通過 get_image_buffer_ptr() / get_image_buffer_len() 訪問:
+    /// `new ImageData(new Uint8ClampedArray(wasm_memory().buffer, ptr, len), width)`
+    ///
+    /// # Returns
+    /// 緩衝區中的字節數 (width * height * 4)
+    #[wasm_bindgen]
+    pub fn render_spectrogram_image(
+        &mut self,
+        audio_data: &[f32],
+        width: usize,
+        height: usize,
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+    ) -> usize {
+        let mut buffer = std::mem::take(&mut self.image_buffer);
+        self.image_into(audio_data, width, height, noverlap, gain_db, range_db, &mut buffer);
+        self.image_buffer = buffer;
+        self.image_buffer.len()
+    }
+
+    /// 獲取 f32 輸出緩衝區在 wasm 線性內存中的地址
+    ///
+    /// 注意：任何可能分配內存的引擎調用都可能使 wasm 內存增長，
+    /// 導致已建立的 JS 視圖失效，因此每次計算後應重新建立視圖。
+    #[wasm_bindgen]
+    pub fn get_output_buffer_ptr(&self) -> *const f32 {
+        self.output_buffer.as_ptr()
+    }
+
+    /// 獲取 f32 輸出緩衝區的元素數量 (尚未計算時為 0)
+    #[wasm_bindgen]
+    pub fn get_output_buffer_len(&self) -> usize {
+        self.output_buffer.len()
+    }
+
+    /// 獲取 u8 頻譜緩衝區在 wasm 線性內存中的地址
+    #[wasm_bindgen]
+    pub fn get_u8_buffer_ptr(&self) -> *const u8 {
+        self.u8_buffer.as_ptr()
+    }
+
+    /// 獲取 u8 頻譜緩衝區的字節數
+    #[wasm_bindgen]
+    pub fn get_u8_buffer_len(&self) -> usize {
+        self.u8_buffer.len()
+    }
+
+    /// 獲取 RGBA 圖像緩衝區在 wasm 線性內存中的地址
+    #[wasm_bindgen]
+    pub fn get_image_buffer_ptr(&self) -> *const u8 {
+        self.image_buffer.as_ptr()
+    }
+
+    /// 獲取 RGBA 圖像緩衝區的字節數
+    #[wasm_bindgen]
+    pub fn get_image_buffer_len(&self) -> usize {
+        self.image_buffer.len()
+    }
+
+    /// 創建分步執行的 compute_spectrogram_image 作業
+    /// 
+    /// 參數與 compute_spectrogram_image 相同，完成後 take_result() 返回相同的 RGBA 數據。
+    #[wasm_bindgen]
+    pub fn start_image_job(
+        &self,
+        audio_data: &[f32],
+        width: usize,
+        height: usize,
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+    ) -> SpectrogramJob {
+        SpectrogramJob::image(self, audio_data, width, height, noverlap, gain_db, range_db)
+    }
+
+    /// 渲染 Anabat 過零錄音的點陣顯示 (使用當前色彩映射與頻率範圍)
+    ///
+    /// 頻率軸取 set_spectrum_config 的 freq_min / freq_max，scale 為 "log" 時使用對數軸，
+    /// 其餘為線性軸。沒有點的像素使用色彩映射的第 0 個顏色。
+    ///
+    /// # Arguments
+    /// * `zc` - 過零錄音
+    /// * `start_time` / `end_time` - 顯示的時間範圍 (秒)
+    /// * `width` / `height` - 輸出圖像大小
+    /// * `dot_size` - 每個點的邊長 (像素)
+    ///
+    /// # Returns
+    /// RGBA 圖像數據 (Uint8ClampedArray) 大小：width * height * 4
+    #[wasm_bindgen]
+    pub fn render_zc_image(
+        &self,
+        zc: &ZcRecording,
+        start_time: f64,
+        end_time: f64,
+        width: usize,
+        height: usize,
+        dot_size: usize,
+    ) -> Vec<u8> {
+        let levels = zc.dot_levels(
+            start_time,
+            end_time,
+            self.freq_min as f64,
+            self.freq_max as f64,
+            self.current_scale == "log",
+            width,
+            height,
+            dot_size,
+        );
+        let mut output = Vec::with_capacity(levels.len() * 4);
+        for level in levels {
+            let rgba = self.color_map.get(level as usize).copied().unwrap_or(0);
+            output.extend_from_slice(&rgba.to_be_bytes());
+        }
+        output
+    }
+}
+
+/// 圖像渲染計劃：輸出像素到源幀/頻率 bin 的映射
+pub(crate) struct ImagePlan {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    frame_step: usize,
+    spec_height: usize,
+    /// 每個輸出列引用的兩個源幀及插值係數
+    columns: Vec<(usize, usize, f32)>,
+    /// 源幀 -> needed_frames 中的位置 (未引用為 usize::MAX)
+    frame_slots: Vec<usize>,
+    /// 需要計算的源幀索引 (每幀只做一次 FFT)
+    pub(crate) needed_frames: Vec<usize>,
+}
+
+impl ImagePlan {
+    /// 每個引用幀的 u8 頻譜長度
+    pub(crate) fn spec_height(&self) -> usize {
+        self.spec_height
+    }
+}
+
+// 內部計算輔助方法（供一次性計算與分步作業共用）
+impl SpectrogramEngine {
+    /// 創建當前 FFT 大小的正向 FFT 計劃
+    pub(crate) fn plan_fft(&mut self) -> std::sync::Arc<dyn rustfft::Fft<f32>> {
+        self.planner.plan_fft_forward(self.fft_size)
+    }
+
+    /// 當前配置版本號（濾波器組或色彩映射改變時更新，所有引擎之間唯一）
+    pub(crate) fn config_version(&self) -> u64 {
+        self.config_version
+    }
+
+    /// 計算線性幅度頻譜到 `out` (重用其容量)
+    fn spectrogram_into(&mut self, audio_data: &[f32], noverlap: usize, out: &mut Vec<f32>) {
+        let step = self.fft_size.saturating_sub(noverlap);
+        let num_frames = frame_count(audio_data.len(), self.fft_size, step);
+        
+        out.clear();
+        out.resize(self.fft_size / 2 * num_frames, 0.0);
+        
+        // 獲取 FFT 算法
+        let fft = self.planner.plan_fft_forward(self.fft_size);
+        self.magnitude_rows(fft.as_ref(), audio_data, step, 0, out);
+    }
+
+    /// 計算 u8 頻譜圖到 `out` (重用其容量)，並保存幅度值供峰值查詢
+    fn spectrogram_u8_into(
+        &mut self,
+        audio_data: &[f32],
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+        out: &mut Vec<u8>,
+    ) {
+        let step = self.fft_size.saturating_sub(noverlap);
+        let num_frames = frame_count(audio_data.len(), self.fft_size, step);
+        
+        let fft = self.planner.plan_fft_forward(self.fft_size);
+        let freq_bins = self.fft_size / 2;
+        
+        // 第一步: 計算所有時間幀的線性幅度 (重用上次的內部緩衝區)
+        let mut all_magnitudes = std::mem::take(&mut self.last_magnitude_buffer);
+        all_magnitudes.clear();
+        all_magnitudes.resize(freq_bins * num_frames, 0.0);
+        self.magnitude_rows(fft.as_ref(), audio_data, step, 0, &mut all_magnitudes);
+        
+        // 第二步: 應用濾波器組 (如果啟用)，轉換為 dB 並量化到 0-255
+        out.clear();
+        out.resize(self.output_bins() * num_frames, 0);
+        self.quantize_rows(&all_magnitudes, gain_db, range_db, out);
+        
+        // 保存最後的幅度值和幀數到內部狀態，供 get_peaks() 使用
+        self.store_magnitudes(all_magnitudes, num_frames, step);
+    }
+
+    /// 渲染 RGBA 光譜圖像到 `out` (重用其容量)
+    #[allow(clippy::too_many_arguments)]
+    fn image_into(
+        &mut self,
+        audio_data: &[f32],
+        width: usize,
+        height: usize,
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+        out: &mut Vec<u8>,
+    ) {
+        // 預分配輸出緩衝區
+        out.clear();
+        out.resize(width * height * 4, 0);
+
+        // 驗證參數並計算重採樣映射
+        let plan = match self.plan_image(audio_data.len(), width, height, noverlap) {
+            Some(plan) => plan,
+            None => return,
+        };
+
+        // 計算被引用幀的 u8 頻譜，然後逐行插值並色彩化
+        let fft = self.planner.plan_fft_forward(self.fft_size);
+        let mut frame_spectra = vec![0u8; plan.needed_frames.len() * plan.spec_height];
+        self.image_frame_spectra(fft.as_ref(), audio_data, &plan, 0, gain_db, range_db, &mut frame_spectra);
+        self.colorize_image_rows(&plan, &frame_spectra, 0, out);
+    }
+
+    /// 計算各通道的 u8 頻譜圖並逐元素取最大值 (同時保存最大幅度供峰值查詢)
+    fn max_spectrogram_u8_into(
+        &mut self,
+        channels: &[Vec<f32>],
+        range: Range<usize>,
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+        out: &mut Vec<u8>,
+    ) {
+        let step = self.fft_size.saturating_sub(noverlap);
+        let num_frames = frame_count(range.len(), self.fft_size, step);
+        let fft = self.planner.plan_fft_forward(self.fft_size);
+        let freq_bins = self.fft_size / 2;
+        let output_bins = self.output_bins();
+        
+        let mut max_magnitudes = vec![0.0f32; freq_bins * num_frames];
+        let mut magnitudes = vec![0.0f32; freq_bins * num_frames];
+        let mut quantized = vec![0u8; output_bins * num_frames];
+        out.clear();
+        out.resize(output_bins * num_frames, 0);
+        
+        for channel in channels {
+            let signal = &channel[range.clone()];
+            self.magnitude_rows(fft.as_ref(), signal, step, 0, &mut magnitudes);
+            self.quantize_rows(&magnitudes, gain_db, range_db, &mut quantized);
+            
+            for (dst, &m) in max_magnitudes.iter_mut().zip(magnitudes.iter()) {
+                *dst = dst.max(m);
             }
-            
-            let frame_data = &self.last_magnitude_buffer[frame_start..frame_end];
-            
-            // 找到此幀中的最大值
-            let max_val = frame_data.iter()
-                .fold(0.0f32, |acc, &val| {
-                    if val > acc { val } else { acc }
-                });
-            
-            // 僅當最大值超過閾值時才記錄幅度值
-            if max_val >= threshold {
-                magnitudes[frame_idx] = max_val;
+            for (dst, &q) in out.iter_mut().zip(quantized.iter()) {
+                *dst = (*dst).max(q);
             }
         }
         
-        magnitudes
+        self.store_magnitudes(max_magnitudes, num_frames, step);
     }
 
-    /// 獲取最後計算的全局最大幅度值
-    /// 
-    /// 此值在最後一次 compute_spectrogram_u8 調用時計算。
-    /// 用於與閾值進行比較以進行峰值檢測。
-    /// 
-    /// # Returns
-    /// 線性幅度值（未轉換為 dB）
-    #[wasm_bindgen]
-    pub fn get_global_max(&self) -> f32 {
-        self.last_global_max
+    /// 渲染各通道頻譜逐元素最大值的 RGBA 圖像
+    #[allow(clippy::too_many_arguments)]
+    fn max_image_into(
+        &mut self,
+        channels: &[Vec<f32>],
+        range: Range<usize>,
+        width: usize,
+        height: usize,
+        noverlap: usize,
+        gain_db: f32,
+        range_db: f32,
+        out: &mut Vec<u8>,
+    ) {
+        out.clear();
+        out.resize(width * height * 4, 0);
+
+        let plan = match self.plan_image(range.len(), width, height, noverlap) {
+            Some(plan) => plan,
+            None => return,
+        };
+
+        let fft = self.planner.plan_fft_forward(self.fft_size);
+        let spectra_len = plan.needed_frames.len() * plan.spec_height;
+        let mut max_spectra = vec![0u8; spectra_len];
+        let mut frame_spectra = vec![0u8; spectra_len];
+        for channel in channels {
+            let signal = &channel[range.clone()];
+            self.image_frame_spectra(fft.as_ref(), signal, &plan, 0, gain_db, range_db, &mut frame_spectra);
+            for (dst, &v) in max_spectra.iter_mut().zip(frame_spectra.iter()) {
+                *dst = (*dst).max(v);
+            }
+        }
+        self.colorize_image_rows(&plan, &max_spectra, 0, out);
     }
 
-    /// 設置 256 色的色彩映射 (RGBA)
-    /// 
-    /// # Arguments
-    /// * `colors` - 256 * 4 字節的 RGBA 顏色數組
-    #[wasm_bindgen]
-    pub fn set_color_map(&mut self, colors: &[u8]) {
-        // 預期大小：256 * 4 = 1024 字節
-        if colors.len() != 1024 {
-            return;  // 無效的色彩映射大小，跳過
+    /// 濾波器組啟用時為 num_filters，否則為 freq_bins
+    pub(crate) fn output_bins(&self) -> usize {
+        if self.use_filter_bank && self.num_filters > 0 {
+            self.num_filters
+        } else {
+            self.fft_size / 2
         }
+    }
+
+    /// 計算從 `first_frame` 開始的連續幀的線性幅度
+    /// 
+    /// `rows` 每行長度為 fft_size / 2，行數決定計算的幀數。
+    pub(crate) fn magnitude_rows(
+        &self,
+        fft: &dyn rustfft::Fft<f32>,
+        audio_data: &[f32],
+        step: usize,
+        first_frame: usize,
+        rows: &mut [f32],
+    ) {
+        let fft_size = self.fft_size;
+        let window = &self.window_values;
+        let kernels = &self.kernels;
         
-        // 轉換 [u8; 4] 成 u32 (RGBA 打包)
-        self.color_map.clear();
-        self.color_map.reserve(256);
+        // 每幀獨立計算（啟用 parallel 特性時分配到多個線程）
+        parallel::for_each_row(
+            rows,
+            fft_size / 2,
+            || FrameScratch::new(fft),
+            |scratch, row_idx, row| {
+                let pos = (first_frame + row_idx) * step;
+                let frame = &audio_data[pos..pos + fft_size];
+                frame_magnitudes(fft, window, frame, scratch, kernels, row);
+            },
+        );
+    }
+
+    /// 將幅度行轉換為 u8 量化行 (濾波器組 -> dB -> 0-255)
+    pub(crate) fn quantize_rows(&self, magnitudes: &[f32], gain_db: f32, range_db: f32, out: &mut [u8]) {
+        let freq_bins = self.fft_size / 2;
+        let use_filter_bank = self.use_filter_bank && self.num_filters > 0;
+        let output_bins = self.output_bins();
+        let num_filters = self.num_filters;
+        let filter_bank = &self.filter_bank;
+        let kernels = &self.kernels;
         
-        for i in 0..256 {
-            let offset = i * 4;
-            let r = colors[offset] as u32;
-            let g = colors[offset + 1] as u32;
-            let b = colors[offset + 2] as u32;
-            let a = colors[offset + 3] as u32;
-            
-            // 打包為 u32 (RGBA 順序)
-            let packed = (r << 24) | (g << 16) | (b << 8) | a;
-            self.color_map.push(packed);
-        }
+        // 預計算 dB 範圍值，以優化迴圈
+        let gain_db_neg = -gain_db;
+        let range_db_reciprocal = 255.0 / range_db;
+        
+        parallel::for_each_row(
+            out,
+            output_bins,
+            || (vec![0.0f32; num_filters], vec![0.0f32; output_bins]),
+            |(filtered, db), frame_idx, row| {
+                let magnitude = &magnitudes[frame_idx * freq_bins..(frame_idx + 1) * freq_bins];
+                let values: &[f32] = if use_filter_bank {
+                    kernels.apply_filters(filter_bank, magnitude, filtered);
+                    filtered
+                } else {
+                    magnitude
+                };
+                
+                // 防止 log10(0)，使用最小值 1e-10
+                kernels.amplitude_to_db(values, 1e-10, db);
+                
+                // 映射到 0-255 範圍
+                kernels.quantize(db, gain_db_neg - range_db, gain_db_neg, range_db_reciprocal, row);
+            },
+        );
     }
 
-    /// 設置光譜配置 (用於記錄，但主要用於驗證)
-    #[wasm_bindgen]
-    pub fn set_spectrum_config(&mut self, scale: String, freq_min: f32, freq_max: f32) {
-        self.current_scale = scale;
-        self.freq_min = freq_min;
-        self.freq_max = freq_max;
+    /// 保存幅度緩衝區，供 get_peaks() 等峰值查詢使用
+    pub(crate) fn store_magnitudes(&mut self, magnitudes: Vec<f32>, num_frames: usize, step: usize) {
+        self.last_global_max = magnitudes.iter().copied().fold(0.0f32, f32::max);
+        self.last_magnitude_buffer = magnitudes;
+        self.last_num_frames = num_frames;
+        self.last_step = step;
     }
 
-    /// 計算完整的光譜圖像 (FFT -> 重採樣 -> 色彩化)
-    /// 
-    /// # Arguments
-    /// * `audio_data` - 單通道音頻數據 (Float32Array)
-    /// * `width` - 輸出圖像寬度 (時間軸)
-    /// * `height` - 輸出圖像高度 (頻率軸)
-    /// * `noverlap` - 窗重疊樣本數
-    /// * `gain_db` - 增益 (dB)
-    /// * `range_db` - 動態範圍 (dB)
-    /// 
-    /// # Returns
-    /// RGBA 圖像數據 (Uint8ClampedArray) 大小：width * height * 4
-    #[wasm_bindgen]
-    pub fn compute_spectrogram_image(
-        &mut self,
-        audio_data: &[f32],
+    /// 計算圖像渲染計劃；無需繪製時 (尺寸為 0、無色彩映射或無完整幀) 返回 None
+    pub(crate) fn plan_image(
+        &self,
+        audio_len: usize,
         width: usize,
         height: usize,
         noverlap: usize,
-        gain_db: f32,
-        range_db: f32,
-    ) -> Vec<u8> {
+    ) -> Option<ImagePlan> {
         // 驗證參數
         if width == 0 || height == 0 || self.color_map.is_empty() {
-            return vec![0; width * height * 4];
+            return None;
         }
 
-        // 預分配輸出緩衝區
-        let mut output = vec![0u8; width * height * 4];
-
-        // 步驟 1: 計算完整光譜 (FFT -> u8 量化)
         let fft_size = self.fft_size;
         let freq_bins = fft_size / 2;
         
         // 計算窗函數與幀數
-        let frame_step = fft_size - noverlap;
-        let num_frames = if audio_data.len() >= fft_size {
-            (audio_data.len() - noverlap) / frame_step
+        let frame_step = fft_size.saturating_sub(noverlap).max(1);
+        let num_frames = if audio_len >= fft_size {
+            (audio_len - noverlap) / frame_step
         } else {
             0
         };
 
-        if num_frames == 0 {
-            return output;
-        }
-
         // 決定輸出大小：如果使用濾波器組，高度是 num_filters；否則是 freq_bins
         let spec_height = if self.use_filter_bank {
             self.num_filters
@@ -531,16 +1271,117 @@ impl SpectrogramEngine {
             freq_bins
         };
 
-        // 步驟 2: 計算重採樣映射
+        if num_frames == 0 || spec_height == 0 {
+            return None;
+        }
+
         // 源座標系統: (time_idx, freq_idx) -> time_idx in [0, num_frames), freq_idx in [0, spec_height)
         // 目標座標系統: (x, y) -> x in [0, width), y in [0, height)
-        
-        // 預計算時間和頻率的採樣因子
         let time_sample_step = num_frames as f32 / width as f32;
+        let columns: Vec<(usize, usize, f32)> = (0..width)
+            .map(|x| {
+                let src_time_idx = x as f32 * time_sample_step;
+                let src_time_int = src_time_idx.floor() as usize;
+                let src_time_frac = src_time_idx - src_time_int as f32;
+                (
+                    src_time_int.min(num_frames - 1),
+                    (src_time_int + 1).min(num_frames - 1),
+                    src_time_frac,
+                )
+            })
+            .collect();
+
+        // 只計算被引用的幀，每幀一次 FFT
+        let mut frame_slots = vec![usize::MAX; num_frames];
+        let mut needed_frames = Vec::new();
+        for &(time_idx0, time_idx1, _) in &columns {
+            for time_idx in [time_idx0, time_idx1] {
+                if frame_slots[time_idx] == usize::MAX {
+                    frame_slots[time_idx] = needed_frames.len();
+                    needed_frames.push(time_idx);
+                }
+            }
+        }
+
+        Some(ImagePlan {
+            width,
+            height,
+            frame_step,
+            spec_height,
+            columns,
+            frame_slots,
+            needed_frames,
+        })
+    }
+
+    /// 計算 `plan.needed_frames[first_slot..]` 中各幀的 u8 頻譜
+    /// 
+    /// `out` 每行長度為 plan.spec_height()，行數決定計算的幀數。
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn image_frame_spectra(
+        &self,
+        fft: &dyn rustfft::Fft<f32>,
+        audio_data: &[f32],
+        plan: &ImagePlan,
+        first_slot: usize,
+        gain_db: f32,
+        range_db: f32,
+        out: &mut [u8],
+    ) {
+        let fft_size = self.fft_size;
+        let freq_bins = fft_size / 2;
+        let window = &self.window_values;
+        let kernels = &self.kernels;
+        let empty_bank = SparseFilterBank::default();
+        let filter_bank = if self.use_filter_bank {
+            &self.image_filter_bank
+        } else {
+            &empty_bank
+        };
+        let num_filters = self.num_filters;
+
+        parallel::for_each_row(
+            out,
+            plan.spec_height,
+            || {
+                (
+                    FrameScratch::new(fft),
+                    vec![0.0f32; freq_bins],
+                    vec![0.0f32; num_filters],
+                    vec![0.0f32; freq_bins.max(num_filters)],
+                )
+            },
+            |(scratch, magnitudes, filtered, db), row_idx, row| {
+                let frame_start = plan.needed_frames[first_slot + row_idx] * plan.frame_step;
+                let frame_end = (frame_start + fft_size).min(audio_data.len());
+                if frame_end <= frame_start {
+                    return;
+                }
+                let frame = &audio_data[frame_start..frame_end];
+                frame_magnitudes(fft, window, frame, scratch, kernels, magnitudes);
+                quantize_frame_spectrum(kernels, magnitudes, filter_bank, filtered, db, gain_db, range_db, row);
+            },
+        );
+    }
+
+    /// 對輸出圖像從 `first_row` 開始的連續行執行雙線性插值並色彩化
+    /// 
+    /// `out` 每行長度為 width * 4 (RGBA)，行數決定處理的行數。
+    pub(crate) fn colorize_image_rows(
+        &self,
+        plan: &ImagePlan,
+        frame_spectra: &[u8],
+        first_row: usize,
+        out: &mut [u8],
+    ) {
+        let color_map = &self.color_map;
+        let spec_height = plan.spec_height;
+        let height = plan.height;
         let freq_sample_step = spec_height as f32 / height as f32;
 
-        // 步驟 3: 處理每個輸出像素
-        for y in 0..height {
+        parallel::for_each_row(out, plan.width * 4, || (), |_, row_idx, row| {
+            let y = first_row + row_idx;
+
             // 頻率軸採樣（從上到下對應從高到低頻率）
             let src_freq_idx = (height - 1 - y) as f32 * freq_sample_step;
             let src_freq_int = src_freq_idx.floor() as usize;
@@ -550,131 +1391,153 @@ impl SpectrogramEngine {
             let src_freq_idx0 = src_freq_int.min(spec_height - 1);
             let src_freq_idx1 = (src_freq_int + 1).min(spec_height - 1);
 
-            for x in 0..width {
-                // 時間軸採樣
-                let src_time_idx = x as f32 * time_sample_step;
-                let src_time_int = src_time_idx.floor() as usize;
-                let src_time_frac = src_time_idx - src_time_int as f32;
-                
-                // 確保索引在範圍內
-                let src_time_idx0 = src_time_int.min(num_frames - 1);
-                let src_time_idx1 = (src_time_int + 1).min(num_frames - 1);
-
+            for (x, &(src_time_idx0, src_time_idx1, src_time_frac)) in plan.columns.iter().enumerate() {
                 // 執行雙線性插值以獲取幅度值
                 let mut magnitude = 0.0f32;
 
                 // 計算 4 個鄰近點的幅度值
                 for &time_idx in &[src_time_idx0, src_time_idx1] {
-                    for &freq_idx in &[src_freq_idx0, src_freq_idx1] {
-                        // 計算該幀的 u8 頻譜
-                        let frame_start = time_idx * frame_step;
-                        let frame_end = (frame_start + fft_size).min(audio_data.len());
-                        
-                        if frame_end <= frame_start {
-                            continue;
-                        }
+                    let slot = plan.frame_slots[time_idx];
+                    let frame_spec = &frame_spectra[slot * spec_height..(slot + 1) * spec_height];
 
-                        let frame = &audio_data[frame_start..frame_end];
+                    for &freq_idx in &[src_freq_idx0, src_freq_idx1] {
+                        let val = frame_spec[freq_idx] as f32 / 255.0;  // 歸一化至 [0, 1]
                         
-                        // 計算此幀的頻譜
-                        let frame_spec = self.compute_frame_spectrum(frame, gain_db, range_db);
+                        // 加權（雙線性）
+                        let time_weight = if time_idx == src_time_idx0 {
+                            1.0 - src_time_frac
+                        } else {
+                            src_time_frac
+                        };
+                        let freq_weight = if freq_idx == src_freq_idx0 {
+                            1.0 - src_freq_frac
+                        } else {
+                            src_freq_frac
+                        };
                         
-                        if freq_idx < frame_spec.len() {
-                            let val = frame_spec[freq_idx] as f32 / 255.0;  // 歸一化至 [0, 1]
-                            
-                            // 加權（雙線性）
-                            let time_weight = if time_idx == src_time_idx0 {
-                                1.0 - src_time_frac
-                            } else {
-                                src_time_frac
-                            };
-                            let freq_weight = if freq_idx == src_freq_idx0 {
-                                1.0 - src_freq_frac
-                            } else {
-                                src_freq_frac
-                            };
-                            
-                            magnitude += val * time_weight * freq_weight;
-                        }
+                        magnitude += val * time_weight * freq_weight;
                     }
                 }
 
-                // 步驟 4: 色彩化
+                // 色彩化
                 let clamped_idx = (magnitude * 255.0).clamp(0.0, 255.0) as usize;
-                let rgba = self.color_map.get(clamped_idx).copied().unwrap_or(0);
+                let rgba = color_map.get(clamped_idx).copied().unwrap_or(0);
 
                 // 解包 RGBA 並寫入輸出
-                let pixel_idx = (y * width + x) * 4;
-                output[pixel_idx] = (rgba >> 24) as u8;      // R
-                output[pixel_idx + 1] = ((rgba >> 16) & 0xFF) as u8;  // G
-                output[pixel_idx + 2] = ((rgba >> 8) & 0xFF) as u8;   // B
-                output[pixel_idx + 3] = (rgba & 0xFF) as u8;          // A
+                let pixel_idx = x * 4;
+                row[pixel_idx] = (rgba >> 24) as u8;      // R
+                row[pixel_idx + 1] = ((rgba >> 16) & 0xFF) as u8;  // G
+                row[pixel_idx + 2] = ((rgba >> 8) & 0xFF) as u8;   // B
+                row[pixel_idx + 3] = (rgba & 0xFF) as u8;          // A
             }
-        }
-
-        output
+        });
     }
+}
 
-    /// 輔助方法：計算單幀的頻譜 (返回 u8 值)
-    fn compute_frame_spectrum(&mut self, frame: &[f32], gain_db: f32, range_db: f32) -> Vec<u8> {
-        let fft_size = self.fft_size;
-        let freq_bins = fft_size / 2;
-
-        // 填充窗函數和 FFT
-        let mut input = vec![Complex::default(); fft_size];
-        for (i, val) in frame.iter().enumerate().take(fft_size) {
-            input[i] = Complex::new(val * self.window_values[i], 0.0);
-        }
-
-        // 執行 FFT
-        let fft = self.planner.plan_fft_forward(fft_size);
-        let mut buffer = input;
-        fft.process(&mut buffer);
+/// 多通道頻譜模式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ChannelMode {
+    /// 單個通道
+    Channel,
+    /// 所有通道的平均 (mixdown)
+    Mid,
+    /// 通道 0 與通道 1 的差 (difference)
+    Side,
+    /// 所有通道逐 bin 取最大值
+    Max,
+}
 
-        // 計算幅度
-        let mut magnitudes = vec![0.0; freq_bins];
-        for i in 0..freq_bins {
-            let magnitude = (buffer[i].norm() * 2.0) / fft_size as f32;  // 歸一化
-            magnitudes[i] = magnitude;
+impl ChannelMode {
+    /// 根據名稱解析模式，未知名稱視為 "channel"
+    fn from_name(name: &str) -> ChannelMode {
+        match name {
+            "mid" | "mixdown" => ChannelMode::Mid,
+            "side" | "difference" => ChannelMode::Side,
+            "max" => ChannelMode::Max,
+            _ => ChannelMode::Channel,
         }
+    }
+}
 
-        // 應用濾波器組（如果已加載）
-        let output = if self.use_filter_bank && !self.filter_bank.is_empty() {
-            let mut filtered = vec![0.0; self.num_filters];
-            let filter_len = fft_size / 2 + 1;
+/// 計算所有通道共同可用的樣本範圍 (以最短通道為準)
+fn channel_range(channels: &[Vec<f32>], start_sample: usize, end_sample: usize) -> Range<usize> {
+    let len = channels.iter().map(|c| c.len()).min().unwrap_or(0);
+    let end = end_sample.min(len);
+    start_sample.min(end)..end
+}
 
-            for (filter_idx, filtered_val) in filtered.iter_mut().enumerate() {
-                let filter_row_start = filter_idx * filter_len;
-                let filter_row_end = filter_row_start + filter_len.min(magnitudes.len());
-                
-                for (j, weight) in self.filter_bank[filter_row_start..filter_row_end].iter().enumerate() {
-                    *filtered_val += magnitudes[j] * weight;
+/// 根據模式生成單通道信號 (Max 模式不適用，按單通道處理)
+fn mix_channels(
+    channels: &[Vec<f32>],
+    mode: ChannelMode,
+    channel_idx: usize,
+    range: Range<usize>,
+) -> Cow<'_, [f32]> {
+    match mode {
+        ChannelMode::Mid if !channels.is_empty() => {
+            let scale = 1.0 / channels.len() as f32;
+            let mut mix = vec![0.0f32; range.len()];
+            for channel in channels {
+                for (dst, &x) in mix.iter_mut().zip(channel[range.clone()].iter()) {
+                    *dst += x;
                 }
             }
-            filtered
-        } else {
-            magnitudes
-        };
-
-        // 轉換為 dB 並量化為 u8
-        let mut result = vec![0u8; output.len()];
-        for (i, &magnitude) in output.iter().enumerate() {
-            let db = if magnitude > 0.0 {
-                20.0 * magnitude.log10()
-            } else {
-                -80.0
-            };
-            
-            // 應用增益和範圍
-            let normalized = (db + range_db / 2.0 + gain_db) / range_db;
-            let clamped = normalized.clamp(0.0, 1.0);
-            result[i] = (clamped * 255.0) as u8;
+            for x in &mut mix {
+                *x *= scale;
+            }
+            Cow::Owned(mix)
         }
-
-        result
+        ChannelMode::Side if channels.len() >= 2 => Cow::Owned(
+            channels[0][range.clone()]
+                .iter()
+                .zip(channels[1][range].iter())
+                .map(|(&l, &r)| (l - r) * 0.5)
+                .collect(),
+        ),
+        ChannelMode::Side => Cow::Owned(vec![0.0; range.len()]),
+        _ => match channels.get(channel_idx) {
+            Some(channel) => Cow::Borrowed(&channel[range]),
+            None => Cow::Borrowed(&[]),
+        },
     }
 }
 
+/// 輔助函數：將單幀線性幅度轉換為圖像用的 u8 頻譜
+///
+/// 濾波器組非空時先應用濾波器組（每行長度 fft_size / 2 + 1），
+/// 然後以 `gain_db` / `range_db` 映射到 0-255。
+#[allow(clippy::too_many_arguments)]
+fn quantize_frame_spectrum(
+    kernels: &Kernels,
+    magnitudes: &[f32],
+    filter_bank: &SparseFilterBank,
+    filtered: &mut [f32],
+    db: &mut [f32],
+    gain_db: f32,
+    range_db: f32,
+    result: &mut [u8],
+) {
+    // 應用濾波器組（如果已加載）
+    let output: &[f32] = if !filter_bank.is_empty() {
+        kernels.apply_filters(filter_bank, magnitudes, filtered);
+        filtered
+    } else {
+        magnitudes
+    };
+
+    // 轉換為 dB（幅度為 0 時使用 -80 dB）
+    let db = &mut db[..output.len()];
+    kernels.amplitude_to_db(output, f32::MIN_POSITIVE, db);
+    for (d, &magnitude) in db.iter_mut().zip(output.iter()) {
+        if magnitude <= 0.0 {
+            *d = -80.0;
+        }
+    }
+
+    // 應用增益和範圍: (db + range / 2 + gain) / range 映射到 [0, 1]
+    let lo = -(range_db / 2.0 + gain_db);
+    kernels.quantize(db, lo, lo + range_db, 255.0 / range_db, result);
+}
 
 /// 根據名稱創建窗函數
 fn create_window(window_name: &str, size: usize, alpha: f32) -> Vec<f32> {
@@ -821,6 +1684,27 @@ pub fn find_global_max(channel_data: &[f32]) -> f32 {
         .fold(0.0f32, f32::max)
 }
 
+/// 獲取 wasm 線性內存對象
+/// 
+/// 與引擎的 get_*_buffer_ptr() / get_*_buffer_len() 配合，
+/// 在 JavaScript 中建立指向結果緩衝區的視圖而無需複製。
+/// 
+/// # Returns
+/// WebAssembly.Memory
+#[wasm_bindgen]
+pub fn wasm_memory() -> JsValue {
+    wasm_bindgen::memory()
+}
+
+/// 獲取計算使用的線程數
+/// 
+/// # Returns
+/// 啟用 `parallel` 特性時為線程池大小，否則為 1
+#[wasm_bindgen]
+pub fn get_num_threads() -> usize {
+    parallel::num_threads()
+}
+
 /// WaveformEngine: 實現波形下採樣和峰值提取
 /// 用於在縮放和滾動時高效渲染波形，避免重複計算
 #[wasm_bindgen]
@@ -830,6 +1714,12 @@ pub struct WaveformEngine {
     channels: Vec<Vec<f32>>,
 }
 
+impl Default for WaveformEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl WaveformEngine {
     /// 創建新的 WaveformEngine 實例
@@ -966,6 +1856,100 @@ impl WaveformEngine {
     pub fn clear(&mut self) {
         self.channels.clear();
     }
+    
+    /// 加載解碼後 WAV 文件的所有通道 (替換現有數據)
+    /// 
+    /// # Arguments
+    /// * `wav` - decode_wav 返回的 WavAudio 對象
+    #[wasm_bindgen]
+    pub fn load_wav(&mut self, wav: &WavAudio) {
+        self.channels = wav.channels().to_vec();
+    }
+    
+    /// 加載解碼後 FLAC 文件的所有通道 (替換現有數據)
+    /// 
+    /// # Arguments
+    /// * `flac` - decode_flac 返回的 FlacAudio 對象
+    #[wasm_bindgen]
+    pub fn load_flac(&mut self, flac: &FlacAudio) {
+        self.channels = flac.channels().to_vec();
+    }
+    
+    /// 診斷已加載所有通道的錄音質量 (削波、直流偏移、電平、無信號通道、採樣率與高頻損失)
+    /// 
+    /// # Arguments
+    /// * `sample_rate` - 已加載數據的採樣率 (Hz)
+    /// * `clip_threshold` - 削波閾值 (絕對值)
+    /// * `min_clip_run` - 最短削波段長度 (樣本數)
+    /// * `reference_frequencies` / `reference_levels_db` - 參考噪聲底；空數組表示不使用參考
+    #[wasm_bindgen]
+    pub fn diagnose(
+        &self,
+        sample_rate: f32,
+        clip_threshold: f32,
+        min_clip_run: usize,
+        reference_frequencies: &[f32],
+        reference_levels_db: &[f32],
+    ) -> AudioDiagnostics {
+        let reference = diagnostics::reference_from(reference_frequencies, reference_levels_db);
+        AudioDiagnostics::analyze(&self.channels, sample_rate, clip_threshold, min_clip_run, reference)
+    }
+    
+    /// 獲取指定通道重採樣後的數據 (不修改已加載的數據)
+    /// 
+    /// # Arguments
+    /// * `channel_idx` - 通道索引
+    /// * `input_rate` - 已加載數據的採樣率 (Hz)
+    /// * `output_rate` - 目標採樣率 (Hz)
+    /// * `quality` - "fast"、"medium"、"high" 或 "best"
+    /// 
+    /// # Returns
+    /// 重採樣後的通道數據；通道不存在或參數無效時拋出 Error
+    #[wasm_bindgen]
+    pub fn get_resampled_channel(
+        &self,
+        channel_idx: usize,
+        input_rate: f32,
+        output_rate: f32,
+        quality: &str,
+    ) -> Result<Vec<f32>, JsError> {
+        let channel = self
+            .channels
+            .get(channel_idx)
+            .ok_or(ResampleError::InvalidChannel { channel_idx })?;
+        Ok(create_resampler(input_rate, output_rate, quality)?.process(channel))
+    }
+    
+    /// 將所有通道重採樣到新的採樣率 (原地替換，之後的樣本索引以新採樣率計)
+    /// 
+    /// # Arguments
+    /// * `input_rate` - 已加載數據的採樣率 (Hz)
+    /// * `output_rate` - 目標採樣率 (Hz)
+    /// * `quality` - "fast"、"medium"、"high" 或 "best"
+    #[wasm_bindgen]
+    pub fn resample_channels(&mut self, input_rate: f32, output_rate: f32, quality: &str) -> Result<(), JsError> {
+        let resampler = create_resampler(input_rate, output_rate, quality)?;
+        for channel in self.channels.iter_mut() {
+            *channel = resampler.process(channel);
+        }
+        Ok(())
+    }
+    
+    /// 對所有通道進行抗混疊整數抽取 (原地替換)
+    /// 
+    /// # Arguments
+    /// * `factor` - 抽取因子 (新採樣率 = 原採樣率 / factor)
+    /// * `quality` - "fast"、"medium"、"high" 或 "best"
+    #[wasm_bindgen]
+    pub fn decimate_channels(&mut self, factor: usize, quality: &str) -> Result<(), JsError> {
+        let quality = ResampleQuality::from_name(quality)
+            .ok_or_else(|| ResampleError::UnknownQuality(quality.to_string()))?;
+        let decimator = Resampler::decimator(factor, quality)?;
+        for channel in self.channels.iter_mut() {
+            *channel = decimator.process(channel);
+        }
+        Ok(())
+    }
 }
 
 // ============================================================
@@ -975,15 +1959,20 @@ impl WaveformEngine {
 
 /// 計算 Power Spectrum (使用 FFT，支持 Overlap)
 /// 
+/// 內部使用 Welch 估計 (每段先減去均值再加窗，均值平均)，
+/// 輸出保持原有的刻度: 10·log10(平均 |X[k]|² / fft_size)，不做單邊折疊。
+///
 /// # Arguments
 /// * `audio_data` - 音頻數據 (Float32Array)
 /// * `sample_rate` - 採樣率 (Hz)
 /// * `fft_size` - FFT 大小
 /// * `window_type` - 窗函數類型 (hann, hamming, blackman, gauss, rectangular, triangular)
-/// * `overlap_percent` - 重疊百分比 (0-99, 或 null/0 表示自動 75%)
+/// * `overlap_percent` - 重疊百分比 (0-99，0 表示不重疊；null 或超出範圍時使用 75%)
 /// 
 /// # Returns
-/// 頻域功率譜 (dB 值)
+/// 頻域功率譜 (dB 值，fft_size / 2 + 1 個頻率點)
+///
+/// 需要頻率軸、去趨勢或平均方式選項時請使用 compute_welch_spectrum。
 #[wasm_bindgen]
 pub fn compute_power_spectrum(
     audio_data: &[f32],
@@ -992,98 +1981,40 @@ pub fn compute_power_spectrum(
     window_type: &str,
     overlap_percent: Option<f32>,
 ) -> Vec<f32> {
-    if audio_data.is_empty() {
+    if audio_data.is_empty() || fft_size == 0 || sample_rate == 0 {
         return Vec::new();
     }
 
     // 確定 hop size (每幀之間的步長)
-    let overlap = overlap_percent.unwrap_or(0.0);
-    let hop_size = if overlap <= 0.0 || overlap >= 100.0 {
-        // Auto mode: 使用 75% overlap
-        (fft_size as f32 * 0.25) as usize
-    } else {
-        (fft_size as f32 * (1.0 - overlap / 100.0)) as usize
-    };
-    let hop_size = hop_size.max(1); // 至少 1
-
-    // 創建窗函數
-    let window = create_window(window_type, fft_size, 0.16);
-
-    // 計算頻率解析度
-    let freq_resolution = sample_rate as f32 / fft_size as f32;
-    let max_freq = sample_rate as f32 / 2.0; // Nyquist
-    let num_bins = ((max_freq / freq_resolution) as usize) + 1;
-
-    // 初始化累積能量譜
-    let mut spectrum = vec![0.0f32; num_bins];
-    let mut frame_count = 0usize;
-
-    // 創建 FFT 規劃器
-    let mut planner = FftPlanner::new();
-    let fft = planner.plan_fft_forward(fft_size);
-
-    // 分幀處理音頻
-    let mut offset = 0;
-    while offset + fft_size <= audio_data.len() {
-        // 提取幀
-        let frame = &audio_data[offset..offset + fft_size];
-
-        // 應用窗函數
-        let mut windowed = vec![0.0f32; fft_size];
-        for i in 0..fft_size {
-            windowed[i] = frame[i] * window[i];
-        }
-
-        // 移除 DC 偏移
-        let mut dc_sum = 0.0f32;
-        for &val in &windowed {
-            dc_sum += val;
-        }
-        let dc_offset = dc_sum / fft_size as f32;
-        for val in &mut windowed {
-            *val -= dc_offset;
-        }
-
-        // 準備 FFT 輸入 (Complex 數據)
-        let mut fft_input: Vec<Complex<f32>> = windowed
-            .iter()
-            .map(|&v| Complex::new(v, 0.0))
-            .collect();
-
-        // 執行 FFT
-        fft.process(&mut fft_input);
-
-        // 提取功率譜並累積
-        for bin in 0..num_bins {
-            if bin < fft_input.len() {
-                let magnitude = fft_input[bin].norm();
-                let power = magnitude * magnitude;
-                spectrum[bin] += power;
-            }
-        }
-
-        frame_count += 1;
-        offset += hop_size;
-    }
-
-    // 如果幀數為 0，返回空
-    if frame_count == 0 {
-        return Vec::new();
-    }
-
-    // 計算平均能量並轉換為 dB
-    let frame_count_f = frame_count as f32;
-    for i in 0..spectrum.len() {
-        let avg_power = spectrum[i] / frame_count_f;
-        // RMS
-        let rms = avg_power.sqrt();
-        // PSD = (RMS^2) / fft_size
-        let psd = (rms * rms) / fft_size as f32;
-        // 轉換為 dB
-        spectrum[i] = 10.0 * psd.max(1e-16).log10();
-    }
-
+    let overlap = overlap_percent.filter(|p| (0.0..100.0).contains(p)).unwrap_or(75.0);
+    let hop_size = ((fft_size as f32 * (1.0 - overlap / 100.0)) as usize).clamp(1, fft_size);
+
+    let spectrum = welch::welch(
+        audio_data,
+        sample_rate as f32,
+        fft_size,
+        fft_size - hop_size,
+        window_type,
+        welch::Detrend::Constant,
+        welch::Average::Mean,
+        true,
+        0.0,
+    );
+
+    // 單邊密度 2·|X|² / (fs·Σw²) 換回原刻度 |X|² / fft_size
+    let window_power: f32 = create_window(window_type, fft_size, 0.16).iter().map(|&w| w * w).sum();
+    let to_legacy = sample_rate as f32 * window_power / fft_size as f32;
+    let last = if fft_size.is_multiple_of(2) { fft_size / 2 } else { fft_size / 2 + 1 };
     spectrum
+        .get_values()
+        .iter()
+        .enumerate()
+        .map(|(bin, &density)| {
+            let folded = if bin == 0 || bin >= last { 1.0 } else { 2.0 };
+            let psd = density * to_legacy / folded;
+            10.0 * psd.max(1e-16).log10()
+        })
+        .collect()
 }
 
 /// 從 Power Spectrum 中找到峰值頻率
@@ -1097,6 +2028,8 @@ pub fn compute_power_spectrum(
 /// 
 /// # Returns
 /// 峰值頻率 (Hz)，如果未找到返回 0
+///
+/// 需要多個峰值、-X dB 帶寬、Q 值或明確錯誤信息時請使用 analyze_spectrum_peaks。
 #[wasm_bindgen]
 pub fn find_peak_frequency_from_spectrum(
     spectrum: &[f32],
@@ -1110,7 +2043,7 @@ pub fn find_peak_frequency_from_spectrum(
     }
 
     let freq_resolution = sample_rate as f32 / fft_size as f32;
-    let min_bin = ((flow_hz / freq_resolution) as usize).max(0);
+    let min_bin = (flow_hz / freq_resolution) as usize;
     let max_bin = ((fhigh_hz / freq_resolution) as usize)
         .min(spectrum.len().saturating_sub(1));
 
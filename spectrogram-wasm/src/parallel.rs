@@ -0,0 +1,51 @@
+// ============================================================
+// 逐行並行執行輔助函數
+// 啟用 `parallel` 特性時使用 rayon 線程池將幀分配到多個核心，
+// 否則退化為順序迴圈，調用方代碼保持一致。
+// ============================================================
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// 按固定行長切分 `data`，對每一行調用 `f(state, row_idx, row)`
+///
+/// `init` 為每個工作線程創建私有狀態（例如 FFT 緩衝區），
+/// 因此 `f` 內不需要任何同步。
+pub(crate) fn for_each_row<T, S, I, F>(data: &mut [T], row_len: usize, init: I, f: F)
+where
+    T: Send,
+    I: Fn() -> S + Sync + Send,
+    F: Fn(&mut S, usize, &mut [T]) + Sync + Send,
+{
+    if row_len == 0 {
+        return;
+    }
+
+    #[cfg(feature = "parallel")]
+    {
+        data.par_chunks_mut(row_len)
+            .enumerate()
+            .for_each_init(init, |state, (row_idx, row)| f(state, row_idx, row));
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        let mut state = init();
+        for (row_idx, row) in data.chunks_mut(row_len).enumerate() {
+            f(&mut state, row_idx, row);
+        }
+    }
+}
+
+/// 當前可用於計算的線程數（未啟用 `parallel` 時為 1）
+pub(crate) fn num_threads() -> usize {
+    #[cfg(feature = "parallel")]
+    {
+        rayon::current_num_threads()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        1
+    }
+}
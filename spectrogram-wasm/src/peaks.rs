@@ -0,0 +1,296 @@
+// ============================================================
+// 每幀多峰值檢測
+// 在線性幅度頻譜上找出前 K 個局部峰值，
+// 以拋物線或高斯插值得到亞 bin 精度的頻率 (Hz)，並計算峰值突出度。
+// ============================================================
+
+use wasm_bindgen::prelude::*;
+
+/// 防止 log10(0) 的最小幅度
+const MIN_MAGNITUDE: f32 = 1e-10;
+
+/// 閾值參考模式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ThresholdMode {
+    /// 相對於所有幀的全局最大值
+    Global,
+    /// 相對於當前幀的最大值（弱信號幀中的峰值同樣可見）
+    Local,
+}
+
+impl ThresholdMode {
+    /// 根據名稱解析模式，未知名稱視為 "global"
+    pub(crate) fn from_name(name: &str) -> ThresholdMode {
+        match name {
+            "local" => ThresholdMode::Local,
+            _ => ThresholdMode::Global,
+        }
+    }
+}
+
+/// 亞 bin 插值方法
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Interpolation {
+    /// 對線性幅度做拋物線擬合
+    Parabolic,
+    /// 對對數幅度做拋物線擬合（等價於對線性幅度做高斯擬合，對窗函數主瓣更準確）
+    Gaussian,
+    /// 不插值，使用 bin 中心頻率
+    None,
+}
+
+impl Interpolation {
+    /// 根據名稱解析方法，未知名稱視為 "parabolic"
+    pub(crate) fn from_name(name: &str) -> Interpolation {
+        match name {
+            "gaussian" => Interpolation::Gaussian,
+            "none" => Interpolation::None,
+            _ => Interpolation::Parabolic,
+        }
+    }
+}
+
+/// 峰值檢測參數
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PeakParams {
+    pub(crate) sample_rate: f32,
+    pub(crate) fft_size: usize,
+    pub(crate) max_peaks: usize,
+    /// 相對於參考最大值的閾值 (dB，通常為負值，例如 -30)
+    pub(crate) threshold_db: f32,
+    pub(crate) threshold_mode: ThresholdMode,
+    pub(crate) interpolation: Interpolation,
+    /// 最小突出度 (dB)
+    pub(crate) min_prominence_db: f32,
+}
+
+/// 單個頻譜峰值
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Peak {
+    pub(crate) frame: usize,
+    pub(crate) freq_hz: f32,
+    pub(crate) magnitude_db: f32,
+    pub(crate) prominence_db: f32,
+}
+
+/// 線性幅度 -> dB
+pub(crate) fn magnitude_to_db(magnitude: f32) -> f32 {
+    20.0 * magnitude.max(MIN_MAGNITUDE).log10()
+}
+
+/// 計算第 `idx` 個 bin 的峰值突出度
+///
+/// 向兩側搜索直到遇到更高的值或邊界，各側的最低點中較高者為基準，
+/// 突出度 = 峰值 - 基準。
+pub(crate) fn prominence(db: &[f32], idx: usize) -> f32 {
+    let peak = db[idx];
+
+    let mut left_min = peak;
+    for &v in db[..idx].iter().rev() {
+        if v > peak {
+            break;
+        }
+        left_min = left_min.min(v);
+    }
+
+    let mut right_min = peak;
+    for &v in &db[idx + 1..] {
+        if v > peak {
+            break;
+        }
+        right_min = right_min.min(v);
+    }
+
+    peak - left_min.max(right_min)
+}
+
+/// 拋物線頂點偏移量與頂點值: 給定 (y[-1], y[0], y[1])
+fn parabolic_vertex(alpha: f32, beta: f32, gamma: f32) -> (f32, f32) {
+    let denom = alpha - 2.0 * beta + gamma;
+    if denom.abs() < 1e-12 {
+        return (0.0, beta);
+    }
+    let offset = (0.5 * (alpha - gamma) / denom).clamp(-0.5, 0.5);
+    (offset, beta - 0.25 * (alpha - gamma) * offset)
+}
+
+/// 對第 `i` 個 bin 的峰值做亞 bin 插值
+///
+/// # Returns
+/// (相對於 bin `i` 的偏移量 -0.5..0.5, 插值後的幅度 dB)；位於邊界的 bin 不插值
+pub(crate) fn interpolate_peak(magnitudes: &[f32], i: usize, interpolation: Interpolation) -> (f32, f32) {
+    if i == 0 || i + 1 >= magnitudes.len() {
+        return (0.0, magnitude_to_db(magnitudes[i]));
+    }
+
+    match interpolation {
+        Interpolation::Parabolic => {
+            let (offset, value) = parabolic_vertex(magnitudes[i - 1], magnitudes[i], magnitudes[i + 1]);
+            (offset, magnitude_to_db(value))
+        }
+        Interpolation::Gaussian => {
+            let ln = |m: f32| m.max(MIN_MAGNITUDE).ln();
+            let (offset, value) = parabolic_vertex(ln(magnitudes[i - 1]), ln(magnitudes[i]), ln(magnitudes[i + 1]));
+            (offset, magnitude_to_db(value.exp()))
+        }
+        Interpolation::None => (0.0, magnitude_to_db(magnitudes[i])),
+    }
+}
+
+/// 在單幀幅度頻譜中尋找峰值
+///
+/// `reference_db` 為閾值參考（全局模式下為全局最大值的 dB）。
+/// 返回的峰值按幅度從強到弱排序，最多 `params.max_peaks` 個。
+pub(crate) fn find_frame_peaks(
+    magnitudes: &[f32],
+    frame: usize,
+    reference_db: Option<f32>,
+    params: &PeakParams,
+) -> Vec<Peak> {
+    let n = magnitudes.len();
+    if n < 3 || params.max_peaks == 0 {
+        return Vec::new();
+    }
+
+    let db: Vec<f32> = magnitudes.iter().map(|&m| magnitude_to_db(m)).collect();
+    let reference = match (params.threshold_mode, reference_db) {
+        (ThresholdMode::Global, Some(reference)) => reference,
+        _ => db.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+    };
+    let threshold = reference + params.threshold_db;
+    let bin_hz = params.sample_rate / params.fft_size as f32;
+
+    let mut peaks = Vec::new();
+    for i in 1..n - 1 {
+        // 局部最大值（平頂取最左側的點）
+        if !(db[i] > db[i - 1] && db[i] >= db[i + 1]) || db[i] < threshold {
+            continue;
+        }
+
+        let prominence_db = prominence(&db, i);
+        if prominence_db < params.min_prominence_db {
+            continue;
+        }
+
+        let (offset, magnitude_db) = interpolate_peak(magnitudes, i, params.interpolation);
+        let bin = i as f32 + offset;
+        peaks.push(Peak {
+            frame,
+            freq_hz: bin * bin_hz,
+            magnitude_db,
+            prominence_db,
+        });
+    }
+
+    peaks.sort_by(|a, b| b.magnitude_db.total_cmp(&a.magnitude_db));
+    peaks.truncate(params.max_peaks);
+    peaks
+}
+
+/// 在所有幀中尋找峰值
+///
+/// `magnitudes` 為 num_frames x freq_bins 的行優先幅度矩陣。
+pub(crate) fn find_peaks(
+    magnitudes: &[f32],
+    freq_bins: usize,
+    num_frames: usize,
+    global_max: f32,
+    params: &PeakParams,
+) -> Vec<Vec<Peak>> {
+    let reference_db = Some(magnitude_to_db(global_max));
+    (0..num_frames)
+        .map(|frame| {
+            let start = frame * freq_bins;
+            match magnitudes.get(start..start + freq_bins) {
+                Some(row) => find_frame_peaks(row, frame, reference_db, params),
+                None => Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// SpectralPeaks: 每幀前 K 個頻譜峰值
+///
+/// 所有峰值按幀順序平鋪存儲；第 f 幀的峰值位於
+/// [frame_offsets[f], frame_offsets[f + 1]) 區間，幀內按幅度從強到弱排序。
+#[wasm_bindgen]
+pub struct SpectralPeaks {
+    num_frames: usize,
+    frame_offsets: Vec<u32>,
+    frame_indices: Vec<u32>,
+    frequencies: Vec<f32>,
+    magnitudes_db: Vec<f32>,
+    prominences_db: Vec<f32>,
+}
+
+impl SpectralPeaks {
+    pub(crate) fn from_frames(frames: &[Vec<Peak>]) -> SpectralPeaks {
+        let total: usize = frames.iter().map(|f| f.len()).sum();
+        let mut result = SpectralPeaks {
+            num_frames: frames.len(),
+            frame_offsets: Vec::with_capacity(frames.len() + 1),
+            frame_indices: Vec::with_capacity(total),
+            frequencies: Vec::with_capacity(total),
+            magnitudes_db: Vec::with_capacity(total),
+            prominences_db: Vec::with_capacity(total),
+        };
+
+        result.frame_offsets.push(0);
+        for frame_peaks in frames {
+            for peak in frame_peaks {
+                result.frame_indices.push(peak.frame as u32);
+                result.frequencies.push(peak.freq_hz);
+                result.magnitudes_db.push(peak.magnitude_db);
+                result.prominences_db.push(peak.prominence_db);
+            }
+            result.frame_offsets.push(result.frame_indices.len() as u32);
+        }
+
+        result
+    }
+}
+
+#[wasm_bindgen]
+impl SpectralPeaks {
+    /// 幀數
+    #[wasm_bindgen]
+    pub fn get_num_frames(&self) -> usize {
+        self.num_frames
+    }
+
+    /// 峰值總數
+    #[wasm_bindgen]
+    pub fn get_num_peaks(&self) -> usize {
+        self.frame_indices.len()
+    }
+
+    /// 每幀峰值區間的起始位置 (Uint32Array，長度 num_frames + 1)
+    #[wasm_bindgen]
+    pub fn get_frame_offsets(&self) -> Vec<u32> {
+        self.frame_offsets.clone()
+    }
+
+    /// 每個峰值所在的幀索引 (Uint32Array)
+    #[wasm_bindgen]
+    pub fn get_frame_indices(&self) -> Vec<u32> {
+        self.frame_indices.clone()
+    }
+
+    /// 每個峰值的插值頻率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_frequencies(&self) -> Vec<f32> {
+        self.frequencies.clone()
+    }
+
+    /// 每個峰值的插值幅度 (dB，相對於幅度 1.0)
+    #[wasm_bindgen]
+    pub fn get_magnitudes_db(&self) -> Vec<f32> {
+        self.magnitudes_db.clone()
+    }
+
+    /// 每個峰值的突出度 (dB)
+    #[wasm_bindgen]
+    pub fn get_prominences_db(&self) -> Vec<f32> {
+        self.prominences_db.clone()
+    }
+}
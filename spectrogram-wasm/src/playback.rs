@@ -0,0 +1,466 @@
+// ============================================================
+// 可聽化回放渲染
+// 將超聲波錄音轉換為可在瀏覽器中以實時速度播放的音頻：
+// - 外差 (heterodyne) 混頻：與本振相乘 -> 低通 -> 重採樣到 44.1 / 48 kHz，
+//   效果與野外外差式蝙蝠探測器相同；
+// - 分頻 (frequency division)：過零計數除以 N，輸出方波；
+// - 變調 (pitch shift)：相位聲碼器按比例降低所有頻率，時長不變。
+// ============================================================
+
+use std::f32::consts::PI as PI_F32;
+use std::f64::consts::PI;
+use std::fmt;
+
+use num_complex::Complex;
+use rustfft::FftPlanner;
+use wasm_bindgen::prelude::*;
+
+use crate::create_window;
+use crate::iir::{BandType, FilterError, FilterFamily, IirFilter};
+use crate::resample::{ResampleError, ResampleQuality, Resampler};
+use crate::stft::{frame_count, frame_spectrum, FrameScratch, OverlapAdd};
+
+/// 外差低通濾波器的階數
+const HETERODYNE_FILTER_ORDER: usize = 6;
+/// 分頻前置高通濾波器的階數
+const DIVISION_HIGHPASS_ORDER: usize = 4;
+/// 分頻包絡跟隨器的釋放時間 (秒)
+const ENVELOPE_RELEASE_SECONDS: f64 = 0.001;
+/// 歸一化後的峰值電平
+const NORMALIZED_PEAK: f32 = 0.9;
+
+/// 回放渲染錯誤
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlaybackError {
+    /// 採樣率必須為正數
+    InvalidSampleRate { sample_rate: f32 },
+    /// 本振頻率必須在 (0, Nyquist) 之間
+    InvalidLocalOscillator { lo_hz: f32, nyquist_hz: f32 },
+    /// 分頻比必須至少為 1
+    InvalidDivisionRatio { ratio: usize },
+    /// 變調比例必須為有限正數
+    InvalidPitchFactor { factor: f32 },
+    /// FFT 大小必須為不小於 16 的 2 的冪
+    InvalidFftSize { fft_size: usize },
+    /// 濾波器設計失敗
+    Filter(FilterError),
+    /// 重採樣失敗
+    Resample(ResampleError),
+}
+
+impl fmt::Display for PlaybackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlaybackError::InvalidSampleRate { sample_rate } => {
+                write!(f, "sample rate must be positive, got {}", sample_rate)
+            }
+            PlaybackError::InvalidLocalOscillator { lo_hz, nyquist_hz } => write!(
+                f,
+                "local oscillator {} Hz must be between 0 and the Nyquist frequency {} Hz",
+                lo_hz, nyquist_hz
+            ),
+            PlaybackError::InvalidDivisionRatio { ratio } => {
+                write!(f, "division ratio must be at least 1, got {}", ratio)
+            }
+            PlaybackError::InvalidPitchFactor { factor } => {
+                write!(f, "pitch factor must be a positive finite number, got {}", factor)
+            }
+            PlaybackError::InvalidFftSize { fft_size } => {
+                write!(f, "FFT size must be a power of two of at least 16, got {}", fft_size)
+            }
+            PlaybackError::Filter(err) => write!(f, "filter: {}", err),
+            PlaybackError::Resample(err) => write!(f, "resampling: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PlaybackError {}
+
+impl From<FilterError> for PlaybackError {
+    fn from(err: FilterError) -> Self {
+        PlaybackError::Filter(err)
+    }
+}
+
+impl From<ResampleError> for PlaybackError {
+    fn from(err: ResampleError) -> Self {
+        PlaybackError::Resample(err)
+    }
+}
+
+fn check_sample_rate(sample_rate: f32) -> Result<(), PlaybackError> {
+    if sample_rate.is_finite() && sample_rate > 0.0 {
+        Ok(())
+    } else {
+        Err(PlaybackError::InvalidSampleRate { sample_rate })
+    }
+}
+
+/// 將峰值縮放到 NORMALIZED_PEAK (靜音時不變)
+fn normalize_peak(samples: &mut [f32]) {
+    let peak = samples.iter().fold(0.0f32, |acc, &x| acc.max(x.abs()));
+    if peak > 0.0 {
+        let gain = NORMALIZED_PEAK / peak;
+        samples.iter_mut().for_each(|x| *x *= gain);
+    }
+}
+
+/// 外差混頻
+///
+/// 輸入與頻率為 `lo_hz` 的本振相乘，|f - lo_hz| 落入可聽範圍；
+/// 和頻分量與頻帶外的信號由 Butterworth 低通濾除，再重採樣到 `output_rate`。
+/// 混頻後乘以 2 以補償本振相乘造成的 6 dB 損失。
+///
+/// # Arguments
+/// * `audio` - 輸入音頻 (所選範圍)
+/// * `sample_rate` - 輸入採樣率 (Hz)
+/// * `lo_hz` - 本振頻率 (Hz)，即探測器的調諧頻率
+/// * `bandwidth_hz` - 可聽頻寬 (Hz)：只保留 lo_hz ± bandwidth_hz 內的信號
+/// * `output_rate` - 輸出採樣率 (Hz)
+/// * `normalize` - 是否將輸出峰值歸一化
+pub fn heterodyne(
+    audio: &[f32],
+    sample_rate: f32,
+    lo_hz: f32,
+    bandwidth_hz: f32,
+    output_rate: f32,
+    normalize: bool,
+) -> Result<Vec<f32>, PlaybackError> {
+    check_sample_rate(sample_rate)?;
+    let nyquist_hz = sample_rate / 2.0;
+    if !(lo_hz > 0.0 && lo_hz < nyquist_hz) {
+        return Err(PlaybackError::InvalidLocalOscillator { lo_hz, nyquist_hz });
+    }
+
+    let lowpass = IirFilter::design(
+        FilterFamily::Butterworth,
+        BandType::Lowpass,
+        HETERODYNE_FILTER_ORDER,
+        sample_rate,
+        0.0,
+        bandwidth_hz,
+        0.0,
+        0.0,
+    )?;
+    let resampler = Resampler::new(sample_rate, output_rate, ResampleQuality::Medium)?;
+
+    let omega = 2.0 * PI * lo_hz as f64 / sample_rate as f64;
+    let mixed: Vec<f32> = audio
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| 2.0 * x * (omega * i as f64).cos() as f32)
+        .collect();
+
+    let mut output = resampler.process(&lowpass.apply(&mixed));
+    if normalize {
+        normalize_peak(&mut output);
+    }
+    Ok(output)
+}
+
+/// 外差混頻回放 (JavaScript 接口)
+///
+/// 傳入所選範圍的樣本 (例如 Float32Array.subarray)，返回可直接寫入 AudioBuffer 的音頻。
+///
+/// # Arguments
+/// * `audio_data` - 輸入音頻 (Float32Array)
+/// * `sample_rate` - 輸入採樣率 (Hz)
+/// * `lo_hz` - 本振頻率 (Hz，例如 CF 蝙蝠的 83000)
+/// * `bandwidth_hz` - 可聽頻寬 (Hz，典型值: 5000-10000)
+/// * `output_rate` - 輸出採樣率 (Hz，44100 或 48000)
+/// * `normalize` - 是否將輸出峰值歸一化到 0.9
+///
+/// # Returns
+/// 輸出採樣率下的音頻 (長度與所選範圍的時長相同)；參數無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+pub fn render_heterodyne(
+    audio_data: &[f32],
+    sample_rate: f32,
+    lo_hz: f32,
+    bandwidth_hz: f32,
+    output_rate: f32,
+    normalize: bool,
+) -> Result<Vec<f32>, JsError> {
+    Ok(heterodyne(audio_data, sample_rate, lo_hz, bandwidth_hz, output_rate, normalize)?)
+}
+
+/// 過零分頻
+///
+/// 與分頻式探測器相同：信號超過 ±threshold 的過零 (帶遲滯) 每計滿 N 次翻轉一次輸出，
+/// 得到頻率為輸入 1/N 的方波。包絡低於閾值時輸出靜音。
+///
+/// # Arguments
+/// * `audio` - 輸入音頻 (所選範圍)
+/// * `sample_rate` - 輸入採樣率 (Hz)
+/// * `ratio` - 分頻比 N (典型值: 10)
+/// * `threshold` - 觸發閾值 (線性幅度)
+/// * `highpass_hz` - 前置高通截止頻率 (Hz)，<= 0 表示不濾波
+/// * `retain_amplitude` - 是否以輸入包絡調制方波 (保留幅度信息)
+/// * `output_rate` - 輸出採樣率 (Hz)
+/// * `normalize` - 是否將輸出峰值歸一化
+#[allow(clippy::too_many_arguments)]
+pub fn frequency_division(
+    audio: &[f32],
+    sample_rate: f32,
+    ratio: usize,
+    threshold: f32,
+    highpass_hz: f32,
+    retain_amplitude: bool,
+    output_rate: f32,
+    normalize: bool,
+) -> Result<Vec<f32>, PlaybackError> {
+    check_sample_rate(sample_rate)?;
+    if ratio == 0 {
+        return Err(PlaybackError::InvalidDivisionRatio { ratio });
+    }
+    let resampler = Resampler::new(sample_rate, output_rate, ResampleQuality::Medium)?;
+
+    let filtered = if highpass_hz > 0.0 {
+        IirFilter::design(
+            FilterFamily::Butterworth,
+            BandType::Highpass,
+            DIVISION_HIGHPASS_ORDER,
+            sample_rate,
+            highpass_hz,
+            0.0,
+            0.0,
+            0.0,
+        )?
+        .apply(audio)
+    } else {
+        audio.to_vec()
+    };
+
+    let threshold = threshold.max(0.0);
+    let decay = (-1.0 / (ENVELOPE_RELEASE_SECONDS * sample_rate as f64)).exp() as f32;
+    let mut envelope = 0.0f32;
+    let mut armed_sign = 0.0f32;
+    let mut crossings = 0usize;
+    let mut output_sign = 1.0f32;
+
+    let divided: Vec<f32> = filtered
+        .iter()
+        .map(|&x| {
+            envelope = x.abs().max(envelope * decay);
+
+            // 遲滯過零: 只有從 -threshold 以下到 +threshold 以上 (或相反) 才計數
+            let sign = if x > threshold {
+                1.0
+            } else if x < -threshold {
+                -1.0
+            } else {
+                armed_sign
+            };
+            if sign != armed_sign {
+                if armed_sign != 0.0 {
+                    crossings += 1;
+                    if crossings == ratio {
+                        crossings = 0;
+                        output_sign = -output_sign;
+                    }
+                }
+                armed_sign = sign;
+            }
+
+            if envelope <= threshold {
+                0.0
+            } else if retain_amplitude {
+                output_sign * envelope
+            } else {
+                output_sign
+            }
+        })
+        .collect();
+
+    let mut output = resampler.process(&divided);
+    if normalize {
+        normalize_peak(&mut output);
+    }
+    Ok(output)
+}
+
+/// 將相位差折疊到 [-π, π)
+fn wrap_phase(phase: f32) -> f32 {
+    (phase + PI_F32).rem_euclid(2.0 * PI_F32) - PI_F32
+}
+
+/// 相位聲碼器變調
+///
+/// 採用峰值鎖定的頻域平移 (Laroche-Dolson)：每幀找出幅度峰值，
+/// 以相位差估計其真實頻率 ω，將峰值周圍的整個區域平移到 factor · ω 附近的 bin，
+/// 並累積相位旋轉以保持幀間相位連續。時長與幅度保持不變。
+///
+/// # Arguments
+/// * `audio` - 輸入音頻 (所選範圍)
+/// * `sample_rate` - 輸入採樣率 (Hz)
+/// * `factor` - 頻率比例 (輸出頻率 / 輸入頻率，例如 0.1 表示降低到 1/10)
+/// * `fft_size` - FFT 大小 (2 的冪，步長為 fft_size / 4)
+/// * `output_rate` - 輸出採樣率 (Hz)
+/// * `normalize` - 是否將輸出峰值歸一化
+pub fn pitch_shift(
+    audio: &[f32],
+    sample_rate: f32,
+    factor: f32,
+    fft_size: usize,
+    output_rate: f32,
+    normalize: bool,
+) -> Result<Vec<f32>, PlaybackError> {
+    check_sample_rate(sample_rate)?;
+    if !factor.is_finite() || factor <= 0.0 {
+        return Err(PlaybackError::InvalidPitchFactor { factor });
+    }
+    if fft_size < 16 || !fft_size.is_power_of_two() {
+        return Err(PlaybackError::InvalidFftSize { fft_size });
+    }
+    let resampler = Resampler::new(sample_rate, output_rate, ResampleQuality::Medium)?;
+
+    let hop = fft_size / 4;
+    let num_bins = fft_size / 2 + 1;
+    let bin_omega = 2.0 * PI_F32 / fft_size as f32;
+
+    // 前端補 fft_size - hop 個零、後端補 fft_size 個零，使每個樣本都被完整覆蓋
+    let pad = fft_size - hop;
+    let mut padded = vec![0.0f32; pad + audio.len() + fft_size];
+    padded[pad..pad + audio.len()].copy_from_slice(audio);
+
+    let window = create_window("hann", fft_size, 0.16);
+    let mut planner = FftPlanner::new();
+    let fft = planner.plan_fft_forward(fft_size);
+    let mut scratch = FrameScratch::new(fft.as_ref());
+    let mut synthesis = OverlapAdd::new(&mut planner, window.clone(), padded.len());
+
+    let mut spectrum = vec![Complex::new(0.0f32, 0.0); num_bins];
+    let mut shifted = vec![Complex::new(0.0f32, 0.0); num_bins];
+    let mut magnitudes = vec![0.0f32; num_bins];
+    let mut phases = vec![0.0f32; num_bins];
+    let mut prev_phases = vec![0.0f32; num_bins];
+    let mut rotations = vec![0.0f32; num_bins];
+    let mut prev_rotations = vec![0.0f32; num_bins];
+    let mut strongest = vec![0.0f32; num_bins];
+    let mut peaks = Vec::new();
+
+    for frame in 0..frame_count(padded.len(), fft_size, hop) {
+        let start = frame * hop;
+        frame_spectrum(fft.as_ref(), &window, &padded[start..start + fft_size], &mut scratch, &mut spectrum);
+        for ((c, m), p) in spectrum.iter().zip(magnitudes.iter_mut()).zip(phases.iter_mut()) {
+            *m = c.norm();
+            *p = c.arg();
+        }
+
+        // 幅度峰值
+        peaks.clear();
+        peaks.extend((1..num_bins - 1).filter(|&k| {
+            magnitudes[k] > 0.0 && magnitudes[k] > magnitudes[k - 1] && magnitudes[k] >= magnitudes[k + 1]
+        }));
+
+        shifted.iter_mut().for_each(|c| *c = Complex::new(0.0, 0.0));
+        rotations.iter_mut().for_each(|r| *r = 0.0);
+        strongest.iter_mut().for_each(|m| *m = 0.0);
+        for (idx, &peak) in peaks.iter().enumerate() {
+            // 峰值的影響區域: 與相鄰峰值之間的最低點為邊界
+            let lowest = |from: usize, to: usize| {
+                (from..=to).min_by(|&a, &b| magnitudes[a].total_cmp(&magnitudes[b])).unwrap_or(from)
+            };
+            let lo = if idx == 0 { 0 } else { lowest(peaks[idx - 1], peak) + 1 };
+            let hi = if idx + 1 == peaks.len() { num_bins - 1 } else { lowest(peak, peaks[idx + 1]) };
+
+            let expected = bin_omega * peak as f32 * hop as f32;
+            let deviation = if frame == 0 { 0.0 } else { wrap_phase(phases[peak] - prev_phases[peak] - expected) };
+            let omega = bin_omega * peak as f32 + deviation / hop as f32;
+            let target = omega * factor;
+            let shift = ((target - omega) / bin_omega).round() as isize;
+
+            let Some(destination) = peak.checked_add_signed(shift).filter(|&k| k < num_bins) else {
+                continue;
+            };
+            let rotation = wrap_phase(prev_rotations[destination] + (target - omega) * hop as f32);
+            let phasor = Complex::from_polar(1.0, rotation);
+            for k in lo..=hi {
+                if let Some(k_out) = k.checked_add_signed(shift).filter(|&k| k < num_bins) {
+                    shifted[k_out] += spectrum[k] * phasor;
+                    // 多個區域落在同一 bin 時，記錄最強分量的旋轉供下一幀延續
+                    if magnitudes[k] > strongest[k_out] {
+                        strongest[k_out] = magnitudes[k];
+                        rotations[k_out] = rotation;
+                    }
+                }
+            }
+        }
+        // DC 與 Nyquist 必須為實數
+        shifted[0].im = 0.0;
+        shifted[num_bins - 1].im = 0.0;
+
+        synthesis.add_frame(&shifted, start);
+        std::mem::swap(&mut prev_phases, &mut phases);
+        std::mem::swap(&mut prev_rotations, &mut rotations);
+    }
+
+    let rendered = synthesis.finish();
+    let mut output = resampler.process(&rendered[pad..pad + audio.len()]);
+    if normalize {
+        normalize_peak(&mut output);
+    }
+    Ok(output)
+}
+
+/// 分頻回放 (JavaScript 接口)
+///
+/// # Arguments
+/// * `audio_data` - 輸入音頻 (Float32Array，所選範圍)
+/// * `sample_rate` - 輸入採樣率 (Hz)
+/// * `ratio` - 分頻比 (典型值: 10)
+/// * `threshold` - 觸發閾值 (線性幅度，例如 0.01)
+/// * `highpass_hz` - 前置高通截止頻率 (Hz，典型值: 15000)；<= 0 表示不濾波
+/// * `retain_amplitude` - 是否保留幅度包絡 (否則輸出恆定幅度方波)
+/// * `output_rate` - 輸出採樣率 (Hz，44100 或 48000)
+/// * `normalize` - 是否將輸出峰值歸一化到 0.9
+///
+/// # Returns
+/// 實時速度的可聽音頻；參數無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn render_frequency_division(
+    audio_data: &[f32],
+    sample_rate: f32,
+    ratio: usize,
+    threshold: f32,
+    highpass_hz: f32,
+    retain_amplitude: bool,
+    output_rate: f32,
+    normalize: bool,
+) -> Result<Vec<f32>, JsError> {
+    Ok(frequency_division(
+        audio_data,
+        sample_rate,
+        ratio,
+        threshold,
+        highpass_hz,
+        retain_amplitude,
+        output_rate,
+        normalize,
+    )?)
+}
+
+/// 相位聲碼器變調回放 (JavaScript 接口)
+///
+/// # Arguments
+/// * `audio_data` - 輸入音頻 (Float32Array，所選範圍)
+/// * `sample_rate` - 輸入採樣率 (Hz)
+/// * `factor` - 頻率比例 (例如 0.1 表示所有頻率除以 10)
+/// * `fft_size` - FFT 大小 (2 的冪，典型值: 1024)
+/// * `output_rate` - 輸出採樣率 (Hz，44100 或 48000)
+/// * `normalize` - 是否將輸出峰值歸一化到 0.9
+///
+/// # Returns
+/// 實時速度 (時長不變) 的可聽音頻；參數無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+pub fn render_pitch_shift(
+    audio_data: &[f32],
+    sample_rate: f32,
+    factor: f32,
+    fft_size: usize,
+    output_rate: f32,
+    normalize: bool,
+) -> Result<Vec<f32>, JsError> {
+    Ok(pitch_shift(audio_data, sample_rate, factor, fft_size, output_rate, normalize)?)
+}
@@ -0,0 +1,322 @@
+// ============================================================
+// 錄音時間推斷
+// 依次嘗試 GUANO、廠商元數據 (wamd、AudioMoth 注釋、Anabat 文件頭)
+// 與常見文件名格式 (`PREFIX_YYYYMMDD_HHMMSS`、AudioMoth 十六進制
+// Unix 時間)，返回帶時區的時間戳、可信度與來源。
+// ============================================================
+
+use wasm_bindgen::prelude::*;
+
+use crate::timestamp::{now_unix_seconds, Timestamp};
+use crate::vendor_meta::RecordingMetadata;
+use crate::zc::ZcRecording;
+
+/// 文件名中接受的年份範圍 (排除序列號等數字串的誤匹配)
+const FILENAME_YEARS: std::ops::RangeInclusive<i32> = 1990..=2100;
+/// AudioMoth 十六進制文件名的最早時間 (2015-01-01，設備發布之前)
+const AUDIOMOTH_HEX_EARLIEST: f64 = 1_420_070_400.0;
+/// AudioMoth 十六進制文件名允許晚於當前時間的秒數 (一年，容忍設備時鐘偏差)
+const AUDIOMOTH_HEX_FUTURE_SECONDS: f64 = 366.0 * 86_400.0;
+
+/// 時間戳可信度
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TimestampConfidence {
+    /// 文件名中的本地時間 (時區為假定值)，或全為十進制數字的 AudioMoth 十六進制文件名
+    Low,
+    /// 元數據中沒有時區的時間、帶 UTC 標記的文件名或 AudioMoth 十六進制文件名
+    Medium,
+    /// 元數據中帶 UTC 偏移的時間
+    High,
+}
+
+impl TimestampConfidence {
+    /// 名稱 ("low"、"medium"、"high")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimestampConfidence::Low => "low",
+            TimestampConfidence::Medium => "medium",
+            TimestampConfidence::High => "high",
+        }
+    }
+}
+
+/// 推斷得到的錄音開始時間
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedTimestamp {
+    timestamp: Timestamp,
+    source: String,
+    confidence: TimestampConfidence,
+    offset_assumed: bool,
+}
+
+impl ResolvedTimestamp {
+    /// 構造結果；時間沒有時區時使用 `default_utc_offset_minutes` (未指定時為 UTC)
+    fn new(
+        timestamp: Timestamp,
+        source: &str,
+        confidence: TimestampConfidence,
+        default_utc_offset_minutes: Option<i16>,
+    ) -> ResolvedTimestamp {
+        let offset_assumed = timestamp.utc_offset_minutes.is_none();
+        ResolvedTimestamp {
+            timestamp: Timestamp {
+                utc_offset_minutes: Some(timestamp.utc_offset_minutes.or(default_utc_offset_minutes).unwrap_or(0)),
+                ..timestamp
+            },
+            source: source.to_string(),
+            confidence,
+            offset_assumed,
+        }
+    }
+
+    /// 從文件內容與文件名推斷錄音開始時間
+    ///
+    /// 依次嘗試 GUANO、`wamd`、AudioMoth 注釋 (WAV / FLAC)、Anabat 過零文件頭，
+    /// 最後是文件名；都沒有可用時間時返回 None。
+    ///
+    /// # Arguments
+    /// * `bytes` - 文件內容 (元數據塊可能位於音頻之後，需傳入完整文件)；可為空
+    /// * `file_name` - 文件名或路徑
+    /// * `default_utc_offset_minutes` - 來源沒有時區時假定的 UTC 偏移，None 表示 UTC
+    pub fn resolve(
+        bytes: &[u8],
+        file_name: &str,
+        default_utc_offset_minutes: Option<i16>,
+    ) -> Option<ResolvedTimestamp> {
+        let from_metadata = |timestamp: Timestamp, source: &str| {
+            let confidence = if timestamp.utc_offset_minutes.is_some() {
+                TimestampConfidence::High
+            } else {
+                TimestampConfidence::Medium
+            };
+            ResolvedTimestamp::new(timestamp, source, confidence, default_utc_offset_minutes)
+        };
+
+        if let Some(sources) = RecordingMetadata::sources_from_file(bytes) {
+            let found = sources
+                .iter()
+                .find_map(|meta| Some((meta.timestamp()?, meta.sources.first()?.clone())));
+            if let Some((timestamp, source)) = found {
+                return Some(from_metadata(timestamp, &source));
+            }
+        } else if let Some(timestamp) = ZcRecording::parse(bytes).ok().and_then(|zc| zc.timestamp()) {
+            return Some(from_metadata(timestamp, "Anabat"));
+        }
+        ResolvedTimestamp::from_filename(file_name, default_utc_offset_minutes)
+    }
+
+    /// 只從文件名推斷錄音開始時間
+    ///
+    /// 支持 `YYYYMMDD_HHMMSS` (分隔符可為 `_`、`-`、`T`、空格或省略)、
+    /// `YYYY-MM-DD_HH-MM-SS` (時間分隔符也可為 `.` 或 `:`)，可選 3 位毫秒與 `Z` 後綴；
+    /// 以及 AudioMoth 舊固件的 8 位十六進制 Unix 時間文件名 (如 `5EC4A2B0.WAV`，UTC；
+    /// 時間須在 2015 年至當前時間之後一年之間，全為十進制數字時可信度為 low)。
+    pub fn from_filename(file_name: &str, default_utc_offset_minutes: Option<i16>) -> Option<ResolvedTimestamp> {
+        let stem = file_name.rsplit(['/', '\\']).next().unwrap_or(file_name);
+        let stem = stem.rsplit_once('.').map_or(stem, |(stem, _)| stem);
+
+        // `YYYYMMDD` 日期主幹優先按日期解析
+        if stem.len() == 8 && stem.bytes().all(|b| b.is_ascii_hexdigit()) && !is_filename_date(stem) {
+            let seconds = u32::from_str_radix(stem, 16).ok()? as f64;
+            if (AUDIOMOTH_HEX_EARLIEST..=now_unix_seconds() + AUDIOMOTH_HEX_FUTURE_SECONDS).contains(&seconds) {
+                // 全為十進制數字的主幹 (如 `60000000`) 也可能是序列號
+                let confidence = if stem.bytes().all(|b| b.is_ascii_digit()) {
+                    TimestampConfidence::Low
+                } else {
+                    TimestampConfidence::Medium
+                };
+                let timestamp = Timestamp::from_unix_seconds(seconds, Some(0));
+                return Some(ResolvedTimestamp::new(timestamp, "AudioMoth filename", confidence, None));
+            }
+        }
+
+        let timestamp = parse_filename_datetime(stem)?;
+        let confidence = if timestamp.utc_offset_minutes.is_some() {
+            TimestampConfidence::Medium
+        } else {
+            TimestampConfidence::Low
+        };
+        Some(ResolvedTimestamp::new(timestamp, "filename", confidence, default_utc_offset_minutes))
+    }
+
+    /// 時間戳 (總是帶 UTC 偏移)
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// 可信度
+    pub fn confidence(&self) -> TimestampConfidence {
+        self.confidence
+    }
+}
+
+/// 8 位數字是否為有效的 `YYYYMMDD` 日期
+fn is_filename_date(text: &str) -> bool {
+    let number = |range: std::ops::Range<usize>| text.get(range)?.parse::<u32>().ok();
+    let (Some(year), Some(month), Some(day)) = (number(0..4), number(4..6), number(6..8)) else {
+        return false;
+    };
+    text.bytes().all(|b| b.is_ascii_digit())
+        && FILENAME_YEARS.contains(&(year as i32))
+        && Timestamp::new(year as i32, month as u8, day as u8, 0, 0, 0, 0, None).is_some()
+}
+
+/// 文件名中的數字串 (起始字節偏移, 數字)
+fn digit_runs(text: &str) -> Vec<(usize, &str)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (i, b) in text.bytes().chain(std::iter::once(b' ')).enumerate() {
+        match (b.is_ascii_digit(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                runs.push((s, &text[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+/// 在文件名主幹中查找第一個有效的日期時間
+fn parse_filename_datetime(stem: &str) -> Option<Timestamp> {
+    let runs = digit_runs(stem);
+    let bytes = stem.as_bytes();
+    // 第 i 個數字串與前一個之間恰好隔一個屬於 `separators` 的字符
+    let joined = |i: usize, separators: &[u8]| {
+        i > 0 && i < runs.len() && {
+            let previous_end = runs[i - 1].0 + runs[i - 1].1.len();
+            runs[i].0 == previous_end + 1 && separators.contains(&bytes[previous_end])
+        }
+    };
+    let number = |text: &str| text.parse::<u32>().ok();
+
+    for i in 0..runs.len() {
+        // (日期, 時間, 時間的最後一個數字串索引)
+        let parsed = match runs[i].1.len() {
+            14 => Some((&runs[i].1[..8], runs[i].1[8..].to_string(), i)),
+            8 if joined(i + 1, b"_-T ") && runs[i + 1].1.len() == 6 => {
+                Some((runs[i].1, runs[i + 1].1.to_string(), i + 1))
+            }
+            4 if (1..=5).all(|k| joined(i + k, if k == 3 { b"_-T " } else { b"-_.:" }))
+                && runs[i + 1..=i + 5].iter().all(|run| run.1.len() == 2) =>
+            {
+                let date = &stem[runs[i].0..runs[i + 2].0 + 2];
+                let time: String = runs[i + 3..=i + 5].iter().map(|run| run.1).collect();
+                Some((date, time, i + 5))
+            }
+            _ => None,
+        };
+        let Some((date, time, last)) = parsed else {
+            continue;
+        };
+        let date: String = date.chars().filter(char::is_ascii_digit).collect();
+
+        let year = number(&date[..4])? as i32;
+        if !FILENAME_YEARS.contains(&year) {
+            continue;
+        }
+        let microsecond = if joined(last + 1, b"_.") && runs[last + 1].1.len() == 3 {
+            number(runs[last + 1].1)? * 1000
+        } else {
+            0
+        };
+        let end = runs[last].0 + runs[last].1.len();
+        let utc = matches!(bytes.get(end), Some(b'Z' | b'z'))
+            && !bytes.get(end + 1).is_some_and(|b| b.is_ascii_alphanumeric());
+
+        let timestamp = Timestamp::new(
+            year,
+            number(&date[4..6])? as u8,
+            number(&date[6..8])? as u8,
+            number(&time[..2])? as u8,
+            number(&time[2..4])? as u8,
+            number(&time[4..6])? as u8,
+            microsecond,
+            utc.then_some(0),
+        );
+        if timestamp.is_some() {
+            return timestamp;
+        }
+    }
+    None
+}
+
+#[wasm_bindgen]
+impl ResolvedTimestamp {
+    /// ISO 8601 時間 (帶 UTC 偏移)
+    #[wasm_bindgen]
+    pub fn get_iso(&self) -> String {
+        self.timestamp.to_iso8601()
+    }
+
+    /// Unix 時間 (秒)
+    #[wasm_bindgen]
+    pub fn get_unix(&self) -> f64 {
+        self.timestamp.to_unix_seconds()
+    }
+
+    /// UTC 偏移 (分鐘)
+    #[wasm_bindgen]
+    pub fn get_utc_offset_minutes(&self) -> i16 {
+        self.timestamp.utc_offset_minutes.unwrap_or(0)
+    }
+
+    /// 時區是否為假定值 (來源中沒有時區)
+    #[wasm_bindgen]
+    pub fn get_offset_assumed(&self) -> bool {
+        self.offset_assumed
+    }
+
+    /// 來源 ("GUANO"、"wamd"、"AudioMoth"、"Anabat"、"filename" 或 "AudioMoth filename")
+    #[wasm_bindgen]
+    pub fn get_source(&self) -> String {
+        self.source.clone()
+    }
+
+    /// 可信度 ("high"、"medium" 或 "low")
+    #[wasm_bindgen]
+    pub fn get_confidence(&self) -> String {
+        self.confidence.as_str().to_string()
+    }
+
+    /// 本地日期 (`YYYY-MM-DD`)
+    #[wasm_bindgen]
+    pub fn get_date(&self) -> String {
+        format!("{:04}-{:02}-{:02}", self.timestamp.year, self.timestamp.month, self.timestamp.day)
+    }
+
+    /// 本地時間 (`HH:MM:SS`)
+    #[wasm_bindgen]
+    pub fn get_time(&self) -> String {
+        format!("{:02}:{:02}:{:02}", self.timestamp.hour, self.timestamp.minute, self.timestamp.second)
+    }
+}
+
+/// 推斷錄音開始時間
+///
+/// # Arguments
+/// * `bytes` - 文件內容 (Uint8Array)，只有文件名時可傳入空數組
+/// * `file_name` - 文件名或路徑
+/// * `default_utc_offset_minutes` - 來源沒有時區時假定的 UTC 偏移 (分鐘)，undefined 表示 UTC
+///
+/// # Returns
+/// ResolvedTimestamp (時間、來源與可信度)；沒有可用時間時返回 undefined
+#[wasm_bindgen]
+pub fn resolve_recording_timestamp(
+    bytes: &[u8],
+    file_name: &str,
+    default_utc_offset_minutes: Option<i16>,
+) -> Option<ResolvedTimestamp> {
+    ResolvedTimestamp::resolve(bytes, file_name, default_utc_offset_minutes)
+}
+
+/// 只從文件名推斷錄音開始時間
+///
+/// # Returns
+/// ResolvedTimestamp；文件名中沒有可識別的時間時返回 undefined
+#[wasm_bindgen]
+pub fn timestamp_from_filename(file_name: &str, default_utc_offset_minutes: Option<i16>) -> Option<ResolvedTimestamp> {
+    ResolvedTimestamp::from_filename(file_name, default_utc_offset_minutes)
+}
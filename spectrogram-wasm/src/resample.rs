@@ -0,0 +1,404 @@
+// ============================================================
+// 採樣率轉換與抽取
+// 以 Kaiser 窗 sinc 核進行帶限插值：
+// 整數採樣率且比例可約分為 L/M 時使用多相 (polyphase) 濾波器，
+// 否則使用查表插值的任意比例 sinc 重採樣。
+// 截止頻率設在較低採樣率的 Nyquist 以下，使阻帶恰好從 Nyquist 開始，避免混疊。
+// ============================================================
+
+use std::f64::consts::PI;
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::parallel::for_each_row;
+
+/// 多相濾波器允許的最大相位數 (插值因子 L)
+const MAX_PHASES: usize = 1024;
+/// 任意比例重採樣的核查找表解析度 (每個過零點間隔的樣本數)
+const TABLE_RESOLUTION: usize = 512;
+/// 並行處理時每塊輸出的樣本數
+const CHUNK_SIZE: usize = 4096;
+
+/// 重採樣質量
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResampleQuality {
+    /// 16 個過零點，約 60 dB 阻帶衰減
+    Fast,
+    /// 32 個過零點，約 80 dB
+    Medium,
+    /// 64 個過零點，約 100 dB
+    High,
+    /// 128 個過零點，約 120 dB
+    Best,
+}
+
+impl ResampleQuality {
+    /// 根據名稱解析質量等級
+    pub fn from_name(name: &str) -> Option<ResampleQuality> {
+        match name {
+            "fast" => Some(ResampleQuality::Fast),
+            "medium" => Some(ResampleQuality::Medium),
+            "high" => Some(ResampleQuality::High),
+            "best" => Some(ResampleQuality::Best),
+            _ => None,
+        }
+    }
+
+    /// (單側過零點數, 阻帶衰減 dB)
+    fn parameters(self) -> (usize, f64) {
+        match self {
+            ResampleQuality::Fast => (16, 60.0),
+            ResampleQuality::Medium => (32, 80.0),
+            ResampleQuality::High => (64, 100.0),
+            ResampleQuality::Best => (128, 120.0),
+        }
+    }
+}
+
+/// 重採樣錯誤
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResampleError {
+    /// 未知的質量名稱
+    UnknownQuality(String),
+    /// 採樣率必須為有限正數
+    InvalidSampleRate { sample_rate: f32 },
+    /// 抽取因子必須至少為 1
+    InvalidFactor { factor: usize },
+    /// 通道索引越界
+    InvalidChannel { channel_idx: usize },
+}
+
+impl fmt::Display for ResampleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResampleError::UnknownQuality(name) => write!(f, "unknown resampling quality '{}'", name),
+            ResampleError::InvalidSampleRate { sample_rate } => {
+                write!(f, "sample rate must be a positive finite number, got {}", sample_rate)
+            }
+            ResampleError::InvalidFactor { factor } => write!(f, "decimation factor must be at least 1, got {}", factor),
+            ResampleError::InvalidChannel { channel_idx } => write!(f, "channel {} does not exist", channel_idx),
+        }
+    }
+}
+
+impl std::error::Error for ResampleError {}
+
+/// 第一類零階修正貝塞爾函數 I0 (級數展開)
+fn bessel_i0(x: f64) -> f64 {
+    let half = x / 2.0;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for k in 1..64 {
+        term *= (half / k as f64) * (half / k as f64);
+        sum += term;
+        if term < sum * 1e-16 {
+            break;
+        }
+    }
+    sum
+}
+
+/// Kaiser 窗 sinc 低通核
+///
+/// 自變量 u 以較低採樣率的樣本為單位；截止頻率 `cutoff` 以較低採樣率歸一化。
+#[derive(Clone, Copy, Debug)]
+struct Kernel {
+    cutoff: f64,
+    zero_crossings: f64,
+    beta: f64,
+    beta_i0: f64,
+}
+
+impl Kernel {
+    fn new(quality: ResampleQuality) -> Kernel {
+        let (zero_crossings, attenuation) = quality.parameters();
+        // Kaiser 公式: 過渡帶寬 Δf ≈ (A - 7.95) / (14.36 · 核長)，核長為 2 · 過零點數
+        let transition = (attenuation - 7.95) / (14.36 * 2.0 * zero_crossings as f64);
+        let beta = 0.1102 * (attenuation - 8.7);
+        Kernel {
+            cutoff: 0.5 - transition / 2.0,
+            zero_crossings: zero_crossings as f64,
+            beta,
+            beta_i0: bessel_i0(beta),
+        }
+    }
+
+    fn eval(&self, u: f64) -> f64 {
+        let r = u / self.zero_crossings;
+        if r.abs() >= 1.0 {
+            return 0.0;
+        }
+        let x = 2.0 * self.cutoff * u;
+        let sinc = if x.abs() < 1e-12 { 1.0 } else { (PI * x).sin() / (PI * x) };
+        let window = bessel_i0(self.beta * (1.0 - r * r).sqrt()) / self.beta_i0;
+        2.0 * self.cutoff * sinc * window
+    }
+}
+
+/// 重採樣方法
+#[derive(Clone, Debug)]
+enum Method {
+    /// 採樣率相同，直接複製
+    Identity,
+    /// 有理比例 L/M: 第 p 相的係數為 g(j + p / L)，j = -half..=half
+    Polyphase {
+        up: usize,
+        down: usize,
+        half: usize,
+        phases: Vec<Vec<f32>>,
+    },
+    /// 任意比例: 核在 [0, 過零點數] 上以 TABLE_RESOLUTION 採樣，線性插值
+    Arbitrary {
+        ratio: f64,
+        /// 輸出相對輸入的帶寬比例 min(1, ratio)
+        rho: f64,
+        half_width: f64,
+        table: Vec<f32>,
+    },
+}
+
+/// Resampler: 採樣率轉換器
+///
+/// 創建時預先計算濾波器係數，可對多個通道重複使用。
+/// 輸出與輸入在時間上對齊 (無群延遲)，輸出長度為 ceil(輸入長度 · 比例)。
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Resampler {
+    method: Method,
+    ratio: f64,
+}
+
+impl Resampler {
+    /// 創建採樣率轉換器
+    ///
+    /// # Arguments
+    /// * `input_rate` - 輸入採樣率 (Hz)
+    /// * `output_rate` - 輸出採樣率 (Hz)
+    /// * `quality` - 重採樣質量
+    pub fn new(input_rate: f32, output_rate: f32, quality: ResampleQuality) -> Result<Resampler, ResampleError> {
+        for sample_rate in [input_rate, output_rate] {
+            if !sample_rate.is_finite() || sample_rate <= 0.0 {
+                return Err(ResampleError::InvalidSampleRate { sample_rate });
+            }
+        }
+
+        let (input, output) = (input_rate as f64, output_rate as f64);
+        if input.fract() == 0.0 && output.fract() == 0.0 {
+            let (input, output) = (input as u64, output as u64);
+            let g = gcd(input, output);
+            if let (Ok(up), Ok(down)) = (usize::try_from(output / g), usize::try_from(input / g)) {
+                if up <= MAX_PHASES {
+                    return Ok(Resampler::rational(up, down, quality));
+                }
+            }
+        }
+        Ok(Resampler::arbitrary(output / input, quality))
+    }
+
+    /// 創建整數抽取器: 先以抗混疊低通濾波，再每 `factor` 個樣本保留一個
+    pub fn decimator(factor: usize, quality: ResampleQuality) -> Result<Resampler, ResampleError> {
+        if factor == 0 {
+            return Err(ResampleError::InvalidFactor { factor });
+        }
+        Ok(Resampler::rational(1, factor, quality))
+    }
+
+    fn rational(up: usize, down: usize, quality: ResampleQuality) -> Resampler {
+        let ratio = up as f64 / down as f64;
+        if up == down {
+            return Resampler { method: Method::Identity, ratio };
+        }
+
+        // 以輸入樣本為單位: g(x) = ρ · k(ρ · x)，ρ = min(1, L / M)
+        let kernel = Kernel::new(quality);
+        let rho = ratio.min(1.0);
+        let half = (kernel.zero_crossings / rho).ceil() as usize;
+        let phases = (0..up)
+            .map(|p| {
+                let frac = p as f64 / up as f64;
+                (-(half as isize)..=half as isize)
+                    .map(|j| (rho * kernel.eval(rho * (j as f64 + frac))) as f32)
+                    .collect()
+            })
+            .collect();
+
+        Resampler {
+            method: Method::Polyphase { up, down, half, phases },
+            ratio,
+        }
+    }
+
+    fn arbitrary(ratio: f64, quality: ResampleQuality) -> Resampler {
+        let kernel = Kernel::new(quality);
+        let rho = ratio.min(1.0);
+        let size = kernel.zero_crossings as usize * TABLE_RESOLUTION + 2;
+        let table = (0..size)
+            .map(|i| kernel.eval(i as f64 / TABLE_RESOLUTION as f64) as f32)
+            .collect();
+
+        Resampler {
+            method: Method::Arbitrary {
+                ratio,
+                rho,
+                half_width: kernel.zero_crossings / rho,
+                table,
+            },
+            ratio,
+        }
+    }
+
+    /// 輸入 `input_len` 個樣本時的輸出長度
+    pub fn output_length(&self, input_len: usize) -> usize {
+        match &self.method {
+            Method::Identity => input_len,
+            // 在 u64 中相乘：wasm32 上 usize 只有 32 位，幾分鐘的錄音乘以 L 就會溢出
+            Method::Polyphase { up, down, .. } => {
+                (input_len as u64 * *up as u64).div_ceil(*down as u64).min(usize::MAX as u64) as usize
+            }
+            Method::Arbitrary { ratio, .. } => (input_len as f64 * ratio - 1e-9).ceil().max(0.0) as usize,
+        }
+    }
+
+    /// 重採樣一段完整的信號 (兩端以零延拓)
+    pub fn process(&self, input: &[f32]) -> Vec<f32> {
+        let mut output = vec![0.0f32; self.output_length(input.len())];
+        let sample = |k: isize| -> f32 {
+            if k >= 0 && (k as usize) < input.len() {
+                input[k as usize]
+            } else {
+                0.0
+            }
+        };
+
+        match &self.method {
+            Method::Identity => output.copy_from_slice(input),
+            Method::Polyphase { up, down, half, phases } => {
+                for_each_row(&mut output, CHUNK_SIZE, || (), |_, chunk_idx, chunk| {
+                    for (i, y) in chunk.iter_mut().enumerate() {
+                        // 輸出 m 對應輸入時間 m · M / L = q + p / L
+                        let pos = (chunk_idx * CHUNK_SIZE + i) as u64 * *down as u64;
+                        let (q, p) = ((pos / *up as u64) as isize, (pos % *up as u64) as usize);
+                        *y = phases[p]
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, &h)| h * sample(q - (idx as isize - *half as isize)))
+                            .sum();
+                    }
+                });
+            }
+            Method::Arbitrary { ratio, rho, half_width, table } => {
+                let lookup = |u: f64| -> f32 {
+                    let pos = u * TABLE_RESOLUTION as f64;
+                    let idx = pos as usize;
+                    if idx + 1 >= table.len() {
+                        return 0.0;
+                    }
+                    let frac = (pos - idx as f64) as f32;
+                    table[idx] + frac * (table[idx + 1] - table[idx])
+                };
+                let scale = *rho as f32;
+                for_each_row(&mut output, CHUNK_SIZE, || (), |_, chunk_idx, chunk| {
+                    for (i, y) in chunk.iter_mut().enumerate() {
+                        let t = (chunk_idx * CHUNK_SIZE + i) as f64 / ratio;
+                        let first = (t - half_width).floor() as isize + 1;
+                        let last = (t + half_width).ceil() as isize - 1;
+                        let sum: f32 = (first..=last)
+                            .map(|k| lookup((t - k as f64).abs() * rho) * sample(k))
+                            .sum();
+                        *y = sum * scale;
+                    }
+                });
+            }
+        }
+        output
+    }
+}
+
+#[wasm_bindgen]
+impl Resampler {
+    /// 輸出 / 輸入採樣率比例
+    #[wasm_bindgen]
+    pub fn get_ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    /// 是否使用多相 (有理比例) 濾波器
+    #[wasm_bindgen]
+    pub fn is_rational(&self) -> bool {
+        !matches!(self.method, Method::Arbitrary { .. })
+    }
+
+    /// 輸入 `input_len` 個樣本時的輸出長度
+    #[wasm_bindgen]
+    pub fn get_output_length(&self, input_len: usize) -> usize {
+        self.output_length(input_len)
+    }
+
+    /// 重採樣
+    ///
+    /// # Arguments
+    /// * `input` - 輸入信號 (Float32Array)
+    ///
+    /// # Returns
+    /// 重採樣後的信號 (Float32Array)
+    #[wasm_bindgen]
+    pub fn resample(&self, input: &[f32]) -> Vec<f32> {
+        self.process(input)
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn parse_quality(quality: &str) -> Result<ResampleQuality, ResampleError> {
+    ResampleQuality::from_name(quality).ok_or_else(|| ResampleError::UnknownQuality(quality.to_string()))
+}
+
+/// 創建採樣率轉換器 (JavaScript 接口)，用於以相同設置處理多個通道
+///
+/// # Arguments
+/// * `input_rate` - 輸入採樣率 (Hz)
+/// * `output_rate` - 輸出採樣率 (Hz)
+/// * `quality` - "fast"、"medium"、"high" 或 "best"
+///
+/// # Returns
+/// Resampler 對象；參數無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+pub fn create_resampler(input_rate: f32, output_rate: f32, quality: &str) -> Result<Resampler, JsError> {
+    Ok(Resampler::new(input_rate, output_rate, parse_quality(quality)?)?)
+}
+
+/// 重採樣音頻 (例如 500 kHz -> 256 kHz)
+///
+/// # Arguments
+/// * `audio_data` - 音頻數據 (Float32Array)
+/// * `input_rate` - 輸入採樣率 (Hz)
+/// * `output_rate` - 輸出採樣率 (Hz)
+/// * `quality` - "fast"、"medium"、"high" 或 "best"
+///
+/// # Returns
+/// 重採樣後的音頻；參數無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+pub fn resample_audio(audio_data: &[f32], input_rate: f32, output_rate: f32, quality: &str) -> Result<Vec<f32>, JsError> {
+    Ok(Resampler::new(input_rate, output_rate, parse_quality(quality)?)?.process(audio_data))
+}
+
+/// 抗混疊整數抽取
+///
+/// # Arguments
+/// * `audio_data` - 音頻數據 (Float32Array)
+/// * `factor` - 抽取因子 (輸出採樣率 = 輸入採樣率 / factor)
+/// * `quality` - "fast"、"medium"、"high" 或 "best"
+///
+/// # Returns
+/// 抽取後的音頻；參數無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+pub fn decimate_audio(audio_data: &[f32], factor: usize, quality: &str) -> Result<Vec<f32>, JsError> {
+    Ok(Resampler::decimator(factor, parse_quality(quality)?)?.process(audio_data))
+}
@@ -0,0 +1,306 @@
+// ============================================================
+// 脊線 / 輪廓追蹤
+// 將每幀的頻譜峰值沿時間軸連接成連續的叫聲輪廓，
+// 支持貪婪連接與動態規劃 (Viterbi) 兩種路徑搜索方式。
+// ============================================================
+
+use wasm_bindgen::prelude::*;
+
+use crate::peaks::Peak;
+
+/// 追蹤時每幀參與連接的最大峰值數
+pub(crate) const MAX_PEAKS_PER_FRAME: usize = 8;
+
+/// 動態規劃中頻率跳變的懲罰 (dB)：跳變達到 max_jump_hz 時扣除的分數
+const JUMP_PENALTY_DB: f32 = 6.0;
+/// 動態規劃中每跳過一幀的懲罰 (dB)
+const GAP_PENALTY_DB: f32 = 3.0;
+
+/// 路徑搜索方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RidgeMethod {
+    /// 逐幀將峰值分配給預測頻率最接近的活動輪廓
+    Greedy,
+    /// 以峰值突出度為分數、頻率跳變為懲罰的全局最優路徑搜索
+    Viterbi,
+}
+
+impl RidgeMethod {
+    /// 根據名稱解析方式，未知名稱視為 "greedy"
+    pub(crate) fn from_name(name: &str) -> RidgeMethod {
+        match name {
+            "viterbi" | "dp" => RidgeMethod::Viterbi,
+            _ => RidgeMethod::Greedy,
+        }
+    }
+}
+
+/// 連接約束
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RidgeParams {
+    /// 每幀允許的最大頻率變化 (Hz)
+    pub(crate) max_jump_hz: f32,
+    /// 相鄰兩段斜率 (Hz/幀) 之間允許的最大變化；<= 0 表示不限制
+    pub(crate) max_slope_change_hz: f32,
+    /// 允許跳過的最大幀數（容忍短暫的信號丟失）
+    pub(crate) max_gap_frames: usize,
+    /// 輪廓的最少點數，較短的輪廓會被丟棄
+    pub(crate) min_length: usize,
+}
+
+impl RidgeParams {
+    /// 檢查從 `last` 連接到 `next` 是否滿足頻率連續性與斜率約束
+    ///
+    /// `slope` 為輪廓末段的斜率 (Hz/幀)，輪廓只有一個點時為 None。
+    fn can_link(&self, last: &Peak, slope: Option<f32>, next: &Peak) -> bool {
+        if next.frame <= last.frame || next.frame - last.frame > self.max_gap_frames + 1 {
+            return false;
+        }
+
+        let dt = (next.frame - last.frame) as f32;
+        let delta = next.freq_hz - last.freq_hz;
+        if delta.abs() > self.max_jump_hz * dt {
+            return false;
+        }
+
+        match slope {
+            Some(slope) if self.max_slope_change_hz > 0.0 => {
+                (delta / dt - slope).abs() <= self.max_slope_change_hz
+            }
+            _ => true,
+        }
+    }
+}
+
+/// 輪廓末段斜率 (Hz/幀)
+fn tail_slope(points: &[Peak]) -> Option<f32> {
+    match points {
+        [.., prev, last] => Some((last.freq_hz - prev.freq_hz) / (last.frame - prev.frame) as f32),
+        _ => None,
+    }
+}
+
+/// 貪婪連接
+///
+/// 每一幀先計算所有 (輪廓, 峰值) 候選對與預測頻率的偏差，
+/// 按偏差從小到大分配，每個輪廓與峰值最多使用一次；未分配的峰值開啟新輪廓。
+fn link_greedy(frames: &[Vec<Peak>], params: &RidgeParams) -> Vec<Vec<Peak>> {
+    let mut active: Vec<Vec<Peak>> = Vec::new();
+    let mut finished = Vec::new();
+
+    for (frame_idx, peaks) in frames.iter().enumerate() {
+        let mut candidates = Vec::new();
+        for (track_idx, track) in active.iter().enumerate() {
+            let last = &track[track.len() - 1];
+            let slope = tail_slope(track);
+            let dt = (frame_idx - last.frame) as f32;
+            let predicted = last.freq_hz + slope.unwrap_or(0.0) * dt;
+
+            for (peak_idx, peak) in peaks.iter().enumerate() {
+                if params.can_link(last, slope, peak) {
+                    candidates.push(((peak.freq_hz - predicted).abs(), track_idx, peak_idx));
+                }
+            }
+        }
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut track_used = vec![false; active.len()];
+        let mut peak_used = vec![false; peaks.len()];
+        for &(_, track_idx, peak_idx) in &candidates {
+            if track_used[track_idx] || peak_used[peak_idx] {
+                continue;
+            }
+            track_used[track_idx] = true;
+            peak_used[peak_idx] = true;
+            active[track_idx].push(peaks[peak_idx]);
+        }
+
+        for (peak_idx, peak) in peaks.iter().enumerate() {
+            if !peak_used[peak_idx] {
+                active.push(vec![*peak]);
+            }
+        }
+
+        // 下一幀已超出允許間隔的輪廓結束
+        let (open, closed): (Vec<_>, Vec<_>) = active
+            .into_iter()
+            .partition(|track| frame_idx + 1 - track[track.len() - 1].frame <= params.max_gap_frames + 1);
+        active = open;
+        finished.extend(closed);
+    }
+
+    finished.extend(active);
+    finished.retain(|track| track.len() >= params.min_length.max(1));
+    finished.sort_by_key(|track| track[0].frame);
+    finished
+}
+
+/// 動態規劃 (Viterbi) 連接
+///
+/// 每個峰值節點的分數 = 自身突出度 + max(0, 最佳前驅分數 - 跳變懲罰 - 間隔懲罰)，
+/// 即每條路徑可以在任意節點開始。計算完成後按分數從高到低回溯，
+/// 已被使用的節點不會被重複分配。
+fn link_viterbi(frames: &[Vec<Peak>], params: &RidgeParams) -> Vec<Vec<Peak>> {
+    let nodes: Vec<Peak> = frames.iter().flatten().copied().collect();
+    let mut frame_start = Vec::with_capacity(frames.len() + 1);
+    frame_start.push(0);
+    for peaks in frames {
+        frame_start.push(frame_start[frame_start.len() - 1] + peaks.len());
+    }
+
+    let jump_scale = if params.max_jump_hz > 0.0 { 1.0 / params.max_jump_hz } else { 0.0 };
+    let mut score = vec![0.0f32; nodes.len()];
+    let mut back: Vec<Option<usize>> = vec![None; nodes.len()];
+
+    for n in 0..nodes.len() {
+        let node = &nodes[n];
+        let first_frame = node.frame.saturating_sub(params.max_gap_frames + 1);
+        let mut best = 0.0f32;
+
+        for p in frame_start[first_frame]..frame_start[node.frame] {
+            let prev = &nodes[p];
+            let slope = back[p].map(|pp| {
+                let before = &nodes[pp];
+                (prev.freq_hz - before.freq_hz) / (prev.frame - before.frame) as f32
+            });
+            if !params.can_link(prev, slope, node) {
+                continue;
+            }
+
+            let dt = (node.frame - prev.frame) as f32;
+            let jump = (node.freq_hz - prev.freq_hz).abs() * jump_scale / dt;
+            let candidate = score[p] - JUMP_PENALTY_DB * jump * jump - GAP_PENALTY_DB * (dt - 1.0);
+            if candidate > best {
+                best = candidate;
+                back[n] = Some(p);
+            }
+        }
+
+        score[n] = node.prominence_db.max(0.0) + best;
+    }
+
+    let mut order: Vec<usize> = (0..nodes.len()).collect();
+    order.sort_by(|&a, &b| score[b].total_cmp(&score[a]));
+
+    let mut used = vec![false; nodes.len()];
+    let mut contours = Vec::new();
+    for end in order {
+        if used[end] {
+            continue;
+        }
+
+        let mut path = Vec::new();
+        let mut current = Some(end);
+        while let Some(n) = current {
+            if used[n] {
+                break;
+            }
+            used[n] = true;
+            path.push(nodes[n]);
+            current = back[n];
+        }
+
+        if path.len() >= params.min_length.max(1) {
+            path.reverse();
+            contours.push(path);
+        }
+    }
+
+    contours.sort_by_key(|track| track[0].frame);
+    contours
+}
+
+/// 將每幀峰值連接成輪廓
+///
+/// `frames[f]` 為第 f 幀的峰值列表；返回的輪廓按起始幀排序，輪廓內的點按幀排序。
+pub(crate) fn track_ridges(frames: &[Vec<Peak>], params: &RidgeParams, method: RidgeMethod) -> Vec<Vec<Peak>> {
+    match method {
+        RidgeMethod::Greedy => link_greedy(frames, params),
+        RidgeMethod::Viterbi => link_viterbi(frames, params),
+    }
+}
+
+/// RidgeContours: 脊線追蹤得到的叫聲輪廓
+///
+/// 所有輪廓的點按順序平鋪存儲；第 c 個輪廓的點位於
+/// [contour_offsets[c], contour_offsets[c + 1]) 區間。
+#[wasm_bindgen]
+pub struct RidgeContours {
+    contour_offsets: Vec<u32>,
+    frame_indices: Vec<u32>,
+    times: Vec<f32>,
+    frequencies: Vec<f32>,
+    magnitudes_db: Vec<f32>,
+}
+
+impl RidgeContours {
+    /// 由輪廓列表構建結果；`frame_time` 將幀索引轉換為秒
+    pub(crate) fn from_contours(contours: &[Vec<Peak>], frame_time: impl Fn(usize) -> f32) -> RidgeContours {
+        let total: usize = contours.iter().map(|c| c.len()).sum();
+        let mut result = RidgeContours {
+            contour_offsets: Vec::with_capacity(contours.len() + 1),
+            frame_indices: Vec::with_capacity(total),
+            times: Vec::with_capacity(total),
+            frequencies: Vec::with_capacity(total),
+            magnitudes_db: Vec::with_capacity(total),
+        };
+
+        result.contour_offsets.push(0);
+        for contour in contours {
+            for point in contour {
+                result.frame_indices.push(point.frame as u32);
+                result.times.push(frame_time(point.frame));
+                result.frequencies.push(point.freq_hz);
+                result.magnitudes_db.push(point.magnitude_db);
+            }
+            result.contour_offsets.push(result.frame_indices.len() as u32);
+        }
+
+        result
+    }
+}
+
+#[wasm_bindgen]
+impl RidgeContours {
+    /// 輪廓數量
+    #[wasm_bindgen]
+    pub fn get_num_contours(&self) -> usize {
+        self.contour_offsets.len().saturating_sub(1)
+    }
+
+    /// 所有輪廓的點總數
+    #[wasm_bindgen]
+    pub fn get_num_points(&self) -> usize {
+        self.frame_indices.len()
+    }
+
+    /// 每個輪廓的點區間起始位置 (Uint32Array，長度 num_contours + 1)
+    #[wasm_bindgen]
+    pub fn get_contour_offsets(&self) -> Vec<u32> {
+        self.contour_offsets.clone()
+    }
+
+    /// 每個點的幀索引 (Uint32Array)
+    #[wasm_bindgen]
+    pub fn get_frame_indices(&self) -> Vec<u32> {
+        self.frame_indices.clone()
+    }
+
+    /// 每個點的時間 (秒，幀中心相對於音頻起點)
+    #[wasm_bindgen]
+    pub fn get_times(&self) -> Vec<f32> {
+        self.times.clone()
+    }
+
+    /// 每個點的頻率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_frequencies(&self) -> Vec<f32> {
+        self.frequencies.clone()
+    }
+
+    /// 每個點的幅度 (dB)
+    #[wasm_bindgen]
+    pub fn get_magnitudes_db(&self) -> Vec<f32> {
+        self.magnitudes_db.clone()
+    }
+}
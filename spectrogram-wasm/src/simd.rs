@@ -0,0 +1,758 @@
+// ============================================================
+// SIMD 計算核心：幅度、快速 dB 轉換、u8 量化與稀疏濾波器組
+//
+// 後端選擇：
+// - wasm32: 以 `-C target-feature=+simd128` 編譯時使用 simd128（編譯期決定）
+// - x86_64: 運行時檢測，優先 AVX2，否則使用 SSE2（x86_64 基線）
+// - 其他平台或調用 set_simd_enabled(false) 時使用標量實現
+//
+// 與標量路徑的誤差（標量路徑為參考實現）：
+// - 幅度：逐元素運算順序相同，結果完全一致
+// - dB 轉換：SIMD 使用快速對數近似，對 >= 1e-10 的輸入誤差 <= 0.001 dB
+// - u8 量化：同一 dB 輸入結果一致；因 dB 近似，個別值最多相差 1 級
+// - 濾波器組：SIMD 累加順序不同，相對誤差 <= 1e-5
+// ============================================================
+
+use num_complex::Complex;
+
+/// 20 * log10(2)
+const DB_PER_OCTAVE: f32 = 6.020_6;
+/// 20 / ln(10)
+const DB_PER_NEPER: f32 = 8.685_889;
+
+/// 可用的計算後端
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Backend {
+    Scalar,
+    #[cfg(target_arch = "x86_64")]
+    Sse2,
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    Simd128,
+}
+
+impl Backend {
+    /// 檢測當前平台可用的最佳後端
+    pub(crate) fn detect() -> Backend {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return Backend::Avx2;
+            }
+            Backend::Sse2
+        }
+
+        #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+        {
+            Backend::Simd128
+        }
+
+        #[cfg(not(any(target_arch = "x86_64", all(target_arch = "wasm32", target_feature = "simd128"))))]
+        {
+            Backend::Scalar
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Backend::Scalar => "scalar",
+            #[cfg(target_arch = "x86_64")]
+            Backend::Sse2 => "sse2",
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => "avx2",
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            Backend::Simd128 => "simd128",
+        }
+    }
+}
+
+/// 稀疏濾波器組：每個濾波器只保存第一個到最後一個非零權重之間的區段
+///
+/// JS 端生成的濾波器每行通常只有 1-2 個非零權重，
+/// 稀疏存儲避免了對整行 fft_size / 2 + 1 個權重做乘加。
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SparseFilterBank {
+    /// 每個濾波器在頻率軸上的起始 bin
+    starts: Vec<usize>,
+    /// 每個濾波器在 `weights` 中的區段 [offsets[i], offsets[i + 1])
+    offsets: Vec<usize>,
+    weights: Vec<f32>,
+}
+
+impl SparseFilterBank {
+    /// 從行優先的稠密矩陣構建
+    ///
+    /// `row_len` 為稠密矩陣每行長度；`max_bins` 為可用幅度 bin 數，
+    /// 超出部分的權重被忽略。
+    pub(crate) fn from_dense(flat: &[f32], num_filters: usize, row_len: usize, max_bins: usize) -> SparseFilterBank {
+        let mut bank = SparseFilterBank {
+            starts: Vec::with_capacity(num_filters),
+            offsets: Vec::with_capacity(num_filters + 1),
+            weights: Vec::new(),
+        };
+        bank.offsets.push(0);
+
+        for i in 0..num_filters {
+            let row_start = (i * row_len).min(flat.len());
+            let row_end = (row_start + row_len.min(max_bins)).min(flat.len());
+            let row = &flat[row_start..row_end];
+
+            match row.iter().position(|&w| w != 0.0) {
+                Some(first) => {
+                    let last = row.iter().rposition(|&w| w != 0.0).unwrap_or(first);
+                    bank.starts.push(first);
+                    bank.weights.extend_from_slice(&row[first..=last]);
+                }
+                None => bank.starts.push(0),
+            }
+            bank.offsets.push(bank.weights.len());
+        }
+
+        bank
+    }
+
+    pub(crate) fn num_filters(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    /// 第 `i` 個濾波器的 (起始 bin, 權重區段)
+    fn filter(&self, i: usize) -> (usize, &[f32]) {
+        (self.starts[i], &self.weights[self.offsets[i]..self.offsets[i + 1]])
+    }
+}
+
+/// 計算核心分派器
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Kernels {
+    backend: Backend,
+}
+
+impl Kernels {
+    /// 使用自動檢測的最佳後端
+    pub(crate) fn detect() -> Kernels {
+        Kernels { backend: Backend::detect() }
+    }
+
+    /// 強制使用標量實現
+    pub(crate) fn scalar() -> Kernels {
+        Kernels { backend: Backend::Scalar }
+    }
+
+    pub(crate) fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// 複數頻譜 -> 線性幅度: out[i] = |spectrum[i]| * scale
+    pub(crate) fn magnitudes(&self, spectrum: &[Complex<f32>], scale: f32, out: &mut [f32]) {
+        let n = spectrum.len().min(out.len());
+        let (spectrum, out) = (&spectrum[..n], &mut out[..n]);
+
+        let done = match self.backend {
+            Backend::Scalar => 0,
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: SSE2 是 x86_64 基線指令集
+            Backend::Sse2 => unsafe { x86::magnitudes_sse2(spectrum, scale, out) },
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: 僅在運行時檢測到 AVX2 時選用此後端
+            Backend::Avx2 => unsafe { x86::magnitudes_avx2(spectrum, scale, out) },
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            Backend::Simd128 => wasm::magnitudes(spectrum, scale, out),
+        };
+
+        for (dst, c) in out[done..].iter_mut().zip(spectrum[done..].iter()) {
+            *dst = (c.re * c.re + c.im * c.im).sqrt() * scale;
+        }
+    }
+
+    /// 線性幅度 -> dB: out[i] = 20 * log10(max(amplitude[i], floor))
+    ///
+    /// `floor` 必須為正的正規浮點數。標量後端使用精確的 log10，
+    /// SIMD 後端使用快速近似（見模塊說明的誤差界）。
+    pub(crate) fn amplitude_to_db(&self, amplitude: &[f32], floor: f32, out: &mut [f32]) {
+        let n = amplitude.len().min(out.len());
+        let (amplitude, out) = (&amplitude[..n], &mut out[..n]);
+
+        if self.backend == Backend::Scalar {
+            for (dst, &a) in out.iter_mut().zip(amplitude.iter()) {
+                let safe = if a > floor { a } else { floor };
+                *dst = 20.0 * safe.log10();
+            }
+            return;
+        }
+
+        let done = match self.backend {
+            Backend::Scalar => 0,
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: SSE2 是 x86_64 基線指令集
+            Backend::Sse2 => unsafe { x86::amplitude_to_db_sse2(amplitude, floor, out) },
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: 僅在運行時檢測到 AVX2 時選用此後端
+            Backend::Avx2 => unsafe { x86::amplitude_to_db_avx2(amplitude, floor, out) },
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            Backend::Simd128 => wasm::amplitude_to_db(amplitude, floor, out),
+        };
+
+        // 尾部元素使用相同的近似公式，保證同一後端內結果一致
+        for (dst, &a) in out[done..].iter_mut().zip(amplitude[done..].iter()) {
+            let safe = if a > floor { a } else { floor };
+            *dst = fast_db(safe);
+        }
+    }
+
+    /// dB -> u8 量化
+    ///
+    /// db < lo 映射為 0，db > hi 映射為 255，其餘為 ((db - lo) * scale) 截斷。
+    pub(crate) fn quantize(&self, db: &[f32], lo: f32, hi: f32, scale: f32, out: &mut [u8]) {
+        let n = db.len().min(out.len());
+        let (db, out) = (&db[..n], &mut out[..n]);
+
+        let done = match self.backend {
+            Backend::Scalar => 0,
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: SSE2 是 x86_64 基線指令集（AVX2 後端沿用 SSE2 量化）
+            Backend::Sse2 | Backend::Avx2 => unsafe { x86::quantize_sse2(db, lo, hi, scale, out) },
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            Backend::Simd128 => wasm::quantize(db, lo, hi, scale, out),
+        };
+
+        for (dst, &d) in out[done..].iter_mut().zip(db[done..].iter()) {
+            *dst = if d < lo {
+                0
+            } else if d > hi {
+                255
+            } else {
+                ((d - lo) * scale) as u8
+            };
+        }
+    }
+
+    /// 應用稀疏濾波器組: out[i] = sum(magnitude[start + j] * weights[j])
+    pub(crate) fn apply_filters(&self, bank: &SparseFilterBank, magnitude: &[f32], out: &mut [f32]) {
+        out.fill(0.0);
+        for (i, dst) in out.iter_mut().enumerate().take(bank.num_filters()) {
+            let (start, weights) = bank.filter(i);
+            let start = start.min(magnitude.len());
+            let len = weights.len().min(magnitude.len() - start);
+            let (m, w) = (&magnitude[start..start + len], &weights[..len]);
+
+            *dst = match self.backend {
+                Backend::Scalar => dot_scalar(m, w),
+                #[cfg(target_arch = "x86_64")]
+                // SAFETY: SSE2 是 x86_64 基線指令集
+                Backend::Sse2 => unsafe { x86::dot_sse2(m, w) },
+                #[cfg(target_arch = "x86_64")]
+                // SAFETY: 僅在運行時檢測到 AVX2 時選用此後端
+                Backend::Avx2 => unsafe { x86::dot_avx2(m, w) },
+                #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+                Backend::Simd128 => wasm::dot(m, w),
+            };
+        }
+    }
+}
+
+fn dot_scalar(a: &[f32], b: &[f32]) -> f32 {
+    let mut sum = 0.0f32;
+    for (x, y) in a.iter().zip(b.iter()) {
+        sum += x * y;
+    }
+    sum
+}
+
+/// 快速 dB 近似的標量版本（與 SIMD 版本使用相同公式）
+///
+/// x = m * 2^e，m 約化到 [sqrt(0.5), sqrt(2))，
+/// ln(m) = 2 * atanh(t)，t = (m - 1) / (m + 1)，以五項級數展開。
+fn fast_db(x: f32) -> f32 {
+    let bits = x.to_bits();
+    let mut e = ((bits >> 23) & 0xFF) as i32 - 127;
+    let mut m = f32::from_bits((bits & 0x007F_FFFF) | 0x3F80_0000);
+    if m > std::f32::consts::SQRT_2 {
+        m *= 0.5;
+        e += 1;
+    }
+    let t = (m - 1.0) / (m + 1.0);
+    let t2 = t * t;
+    let p = (((t2 * (1.0 / 9.0) + 1.0 / 7.0) * t2 + 1.0 / 5.0) * t2 + 1.0 / 3.0) * t2 + 1.0;
+    let ln_m = 2.0 * t * p;
+    e as f32 * DB_PER_OCTAVE + ln_m * DB_PER_NEPER
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use super::{DB_PER_NEPER, DB_PER_OCTAVE};
+    use num_complex::Complex;
+    use std::arch::x86_64::*;
+
+    /// 返回已處理的元素數，剩餘元素由調用方以標量方式處理
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn magnitudes_sse2(spectrum: &[Complex<f32>], scale: f32, out: &mut [f32]) -> usize {
+        let n = spectrum.len() / 4 * 4;
+        let src = spectrum.as_ptr() as *const f32;
+        let vscale = _mm_set1_ps(scale);
+        let mut i = 0;
+        while i < n {
+            let a = _mm_loadu_ps(src.add(i * 2));
+            let b = _mm_loadu_ps(src.add(i * 2 + 4));
+            let re = _mm_shuffle_ps::<0b10_00_10_00>(a, b);
+            let im = _mm_shuffle_ps::<0b11_01_11_01>(a, b);
+            let power = _mm_add_ps(_mm_mul_ps(re, re), _mm_mul_ps(im, im));
+            _mm_storeu_ps(out.as_mut_ptr().add(i), _mm_mul_ps(_mm_sqrt_ps(power), vscale));
+            i += 4;
+        }
+        n
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn magnitudes_avx2(spectrum: &[Complex<f32>], scale: f32, out: &mut [f32]) -> usize {
+        let n = spectrum.len() / 8 * 8;
+        let src = spectrum.as_ptr() as *const f32;
+        let vscale = _mm256_set1_ps(scale);
+        let mut i = 0;
+        while i < n {
+            let a = _mm256_loadu_ps(src.add(i * 2));
+            let b = _mm256_loadu_ps(src.add(i * 2 + 8));
+            // 128 位通道內洗牌後順序為 [0 1 4 5 | 2 3 6 7]，最後以 64 位置換還原
+            let re = _mm256_shuffle_ps::<0b10_00_10_00>(a, b);
+            let im = _mm256_shuffle_ps::<0b11_01_11_01>(a, b);
+            let power = _mm256_add_ps(_mm256_mul_ps(re, re), _mm256_mul_ps(im, im));
+            let mag = _mm256_mul_ps(_mm256_sqrt_ps(power), vscale);
+            let ordered = _mm256_castpd_ps(_mm256_permute4x64_pd::<0b11_01_10_00>(_mm256_castps_pd(mag)));
+            _mm256_storeu_ps(out.as_mut_ptr().add(i), ordered);
+            i += 8;
+        }
+        n
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn fast_db_sse2(x: __m128) -> __m128 {
+        let bits = _mm_castps_si128(x);
+        let exponent = _mm_sub_epi32(
+            _mm_and_si128(_mm_srli_epi32::<23>(bits), _mm_set1_epi32(0xFF)),
+            _mm_set1_epi32(127),
+        );
+        let mut e = _mm_cvtepi32_ps(exponent);
+        let mut m = _mm_castsi128_ps(_mm_or_si128(
+            _mm_and_si128(bits, _mm_set1_epi32(0x007F_FFFF)),
+            _mm_set1_epi32(0x3F80_0000),
+        ));
+
+        let one = _mm_set1_ps(1.0);
+        let above = _mm_cmpgt_ps(m, _mm_set1_ps(std::f32::consts::SQRT_2));
+        m = _mm_or_ps(_mm_and_ps(above, _mm_mul_ps(m, _mm_set1_ps(0.5))), _mm_andnot_ps(above, m));
+        e = _mm_add_ps(e, _mm_and_ps(above, one));
+
+        let t = _mm_div_ps(_mm_sub_ps(m, one), _mm_add_ps(m, one));
+        let t2 = _mm_mul_ps(t, t);
+        let mut p = _mm_add_ps(_mm_mul_ps(t2, _mm_set1_ps(1.0 / 9.0)), _mm_set1_ps(1.0 / 7.0));
+        p = _mm_add_ps(_mm_mul_ps(p, t2), _mm_set1_ps(1.0 / 5.0));
+        p = _mm_add_ps(_mm_mul_ps(p, t2), _mm_set1_ps(1.0 / 3.0));
+        p = _mm_add_ps(_mm_mul_ps(p, t2), one);
+        let ln_m = _mm_mul_ps(_mm_mul_ps(_mm_set1_ps(2.0), t), p);
+
+        _mm_add_ps(_mm_mul_ps(e, _mm_set1_ps(DB_PER_OCTAVE)), _mm_mul_ps(ln_m, _mm_set1_ps(DB_PER_NEPER)))
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn amplitude_to_db_sse2(amplitude: &[f32], floor: f32, out: &mut [f32]) -> usize {
+        let n = amplitude.len() / 4 * 4;
+        let vfloor = _mm_set1_ps(floor);
+        let mut i = 0;
+        while i < n {
+            let x = _mm_max_ps(_mm_loadu_ps(amplitude.as_ptr().add(i)), vfloor);
+            _mm_storeu_ps(out.as_mut_ptr().add(i), fast_db_sse2(x));
+            i += 4;
+        }
+        n
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn fast_db_avx2(x: __m256) -> __m256 {
+        let bits = _mm256_castps_si256(x);
+        let exponent = _mm256_sub_epi32(
+            _mm256_and_si256(_mm256_srli_epi32::<23>(bits), _mm256_set1_epi32(0xFF)),
+            _mm256_set1_epi32(127),
+        );
+        let mut e = _mm256_cvtepi32_ps(exponent);
+        let mut m = _mm256_castsi256_ps(_mm256_or_si256(
+            _mm256_and_si256(bits, _mm256_set1_epi32(0x007F_FFFF)),
+            _mm256_set1_epi32(0x3F80_0000),
+        ));
+
+        let one = _mm256_set1_ps(1.0);
+        let above = _mm256_cmp_ps::<_CMP_GT_OQ>(m, _mm256_set1_ps(std::f32::consts::SQRT_2));
+        m = _mm256_blendv_ps(m, _mm256_mul_ps(m, _mm256_set1_ps(0.5)), above);
+        e = _mm256_add_ps(e, _mm256_and_ps(above, one));
+
+        let t = _mm256_div_ps(_mm256_sub_ps(m, one), _mm256_add_ps(m, one));
+        let t2 = _mm256_mul_ps(t, t);
+        let mut p = _mm256_add_ps(_mm256_mul_ps(t2, _mm256_set1_ps(1.0 / 9.0)), _mm256_set1_ps(1.0 / 7.0));
+        p = _mm256_add_ps(_mm256_mul_ps(p, t2), _mm256_set1_ps(1.0 / 5.0));
+        p = _mm256_add_ps(_mm256_mul_ps(p, t2), _mm256_set1_ps(1.0 / 3.0));
+        p = _mm256_add_ps(_mm256_mul_ps(p, t2), one);
+        let ln_m = _mm256_mul_ps(_mm256_mul_ps(_mm256_set1_ps(2.0), t), p);
+
+        _mm256_add_ps(
+            _mm256_mul_ps(e, _mm256_set1_ps(DB_PER_OCTAVE)),
+            _mm256_mul_ps(ln_m, _mm256_set1_ps(DB_PER_NEPER)),
+        )
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn amplitude_to_db_avx2(amplitude: &[f32], floor: f32, out: &mut [f32]) -> usize {
+        let n = amplitude.len() / 8 * 8;
+        let vfloor = _mm256_set1_ps(floor);
+        let mut i = 0;
+        while i < n {
+            let x = _mm256_max_ps(_mm256_loadu_ps(amplitude.as_ptr().add(i)), vfloor);
+            _mm256_storeu_ps(out.as_mut_ptr().add(i), fast_db_avx2(x));
+            i += 8;
+        }
+        n
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn quantize4_sse2(db: __m128, lo: __m128, hi: __m128, scale: __m128) -> __m128i {
+        let zero = _mm_setzero_ps();
+        let max = _mm_set1_ps(255.0);
+        let v = _mm_min_ps(_mm_max_ps(_mm_mul_ps(_mm_sub_ps(db, lo), scale), zero), max);
+        // db > hi 一律為 255（與標量分支一致）
+        let v = _mm_or_ps(_mm_and_ps(_mm_cmpgt_ps(db, hi), max), _mm_andnot_ps(_mm_cmpgt_ps(db, hi), v));
+        _mm_cvttps_epi32(v)
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn quantize_sse2(db: &[f32], lo: f32, hi: f32, scale: f32, out: &mut [u8]) -> usize {
+        let n = db.len() / 16 * 16;
+        let (vlo, vhi, vscale) = (_mm_set1_ps(lo), _mm_set1_ps(hi), _mm_set1_ps(scale));
+        let src = db.as_ptr();
+        let mut i = 0;
+        while i < n {
+            let q0 = quantize4_sse2(_mm_loadu_ps(src.add(i)), vlo, vhi, vscale);
+            let q1 = quantize4_sse2(_mm_loadu_ps(src.add(i + 4)), vlo, vhi, vscale);
+            let q2 = quantize4_sse2(_mm_loadu_ps(src.add(i + 8)), vlo, vhi, vscale);
+            let q3 = quantize4_sse2(_mm_loadu_ps(src.add(i + 12)), vlo, vhi, vscale);
+            let packed = _mm_packus_epi16(_mm_packs_epi32(q0, q1), _mm_packs_epi32(q2, q3));
+            _mm_storeu_si128(out.as_mut_ptr().add(i) as *mut __m128i, packed);
+            i += 16;
+        }
+        n
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn dot_sse2(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len() / 4 * 4;
+        let mut acc = _mm_setzero_ps();
+        let mut i = 0;
+        while i < n {
+            acc = _mm_add_ps(acc, _mm_mul_ps(_mm_loadu_ps(a.as_ptr().add(i)), _mm_loadu_ps(b.as_ptr().add(i))));
+            i += 4;
+        }
+        let mut lanes = [0.0f32; 4];
+        _mm_storeu_ps(lanes.as_mut_ptr(), acc);
+        let mut sum = (lanes[0] + lanes[1]) + (lanes[2] + lanes[3]);
+        for j in n..a.len() {
+            sum += a[j] * b[j];
+        }
+        sum
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn dot_avx2(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len() / 8 * 8;
+        let mut acc = _mm256_setzero_ps();
+        let mut i = 0;
+        while i < n {
+            acc = _mm256_add_ps(
+                acc,
+                _mm256_mul_ps(_mm256_loadu_ps(a.as_ptr().add(i)), _mm256_loadu_ps(b.as_ptr().add(i))),
+            );
+            i += 8;
+        }
+        let mut lanes = [0.0f32; 8];
+        _mm256_storeu_ps(lanes.as_mut_ptr(), acc);
+        let mut sum = lanes.iter().sum::<f32>();
+        for j in n..a.len() {
+            sum += a[j] * b[j];
+        }
+        sum
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod wasm {
+    use super::{DB_PER_NEPER, DB_PER_OCTAVE};
+    use core::arch::wasm32::*;
+    use num_complex::Complex;
+
+    pub(super) fn magnitudes(spectrum: &[Complex<f32>], scale: f32, out: &mut [f32]) -> usize {
+        let n = spectrum.len() / 4 * 4;
+        let src = spectrum.as_ptr() as *const v128;
+        let vscale = f32x4_splat(scale);
+        let mut i = 0;
+        while i < n {
+            // SAFETY: i + 4 <= n，讀取 4 個複數（8 個 f32）不越界
+            let (a, b) = unsafe { (v128_load(src.add(i / 2)), v128_load(src.add(i / 2 + 1))) };
+            let re = i32x4_shuffle::<0, 2, 4, 6>(a, b);
+            let im = i32x4_shuffle::<1, 3, 5, 7>(a, b);
+            let power = f32x4_add(f32x4_mul(re, re), f32x4_mul(im, im));
+            // SAFETY: i + 4 <= n <= out.len()
+            unsafe { v128_store(out.as_mut_ptr().add(i) as *mut v128, f32x4_mul(f32x4_sqrt(power), vscale)) };
+            i += 4;
+        }
+        n
+    }
+
+    fn fast_db(x: v128) -> v128 {
+        let exponent = i32x4_sub(v128_and(u32x4_shr(x, 23), i32x4_splat(0xFF)), i32x4_splat(127));
+        let mut e = f32x4_convert_i32x4(exponent);
+        let mut m = v128_or(v128_and(x, i32x4_splat(0x007F_FFFF)), i32x4_splat(0x3F80_0000));
+
+        let one = f32x4_splat(1.0);
+        let above = f32x4_gt(m, f32x4_splat(std::f32::consts::SQRT_2));
+        m = v128_bitselect(f32x4_mul(m, f32x4_splat(0.5)), m, above);
+        e = f32x4_add(e, v128_and(above, one));
+
+        let t = f32x4_div(f32x4_sub(m, one), f32x4_add(m, one));
+        let t2 = f32x4_mul(t, t);
+        let mut p = f32x4_add(f32x4_mul(t2, f32x4_splat(1.0 / 9.0)), f32x4_splat(1.0 / 7.0));
+        p = f32x4_add(f32x4_mul(p, t2), f32x4_splat(1.0 / 5.0));
+        p = f32x4_add(f32x4_mul(p, t2), f32x4_splat(1.0 / 3.0));
+        p = f32x4_add(f32x4_mul(p, t2), one);
+        let ln_m = f32x4_mul(f32x4_mul(f32x4_splat(2.0), t), p);
+
+        f32x4_add(f32x4_mul(e, f32x4_splat(DB_PER_OCTAVE)), f32x4_mul(ln_m, f32x4_splat(DB_PER_NEPER)))
+    }
+
+    pub(super) fn amplitude_to_db(amplitude: &[f32], floor: f32, out: &mut [f32]) -> usize {
+        let n = amplitude.len() / 4 * 4;
+        let vfloor = f32x4_splat(floor);
+        let mut i = 0;
+        while i < n {
+            // SAFETY: i + 4 <= n 不越界
+            let x = unsafe { v128_load(amplitude.as_ptr().add(i) as *const v128) };
+            let db = fast_db(f32x4_pmax(x, vfloor));
+            // SAFETY: 同上
+            unsafe { v128_store(out.as_mut_ptr().add(i) as *mut v128, db) };
+            i += 4;
+        }
+        n
+    }
+
+    fn quantize4(db: v128, lo: v128, hi: v128, scale: v128) -> v128 {
+        let max = f32x4_splat(255.0);
+        let v = f32x4_pmin(f32x4_pmax(f32x4_mul(f32x4_sub(db, lo), scale), f32x4_splat(0.0)), max);
+        let v = v128_bitselect(max, v, f32x4_gt(db, hi));
+        i32x4_trunc_sat_f32x4(v)
+    }
+
+    pub(super) fn quantize(db: &[f32], lo: f32, hi: f32, scale: f32, out: &mut [u8]) -> usize {
+        let n = db.len() / 16 * 16;
+        let (vlo, vhi, vscale) = (f32x4_splat(lo), f32x4_splat(hi), f32x4_splat(scale));
+        let src = db.as_ptr();
+        let mut i = 0;
+        while i < n {
+            // SAFETY: i + 16 <= n 不越界
+            let (q0, q1, q2, q3) = unsafe {
+                (
+                    quantize4(v128_load(src.add(i) as *const v128), vlo, vhi, vscale),
+                    quantize4(v128_load(src.add(i + 4) as *const v128), vlo, vhi, vscale),
+                    quantize4(v128_load(src.add(i + 8) as *const v128), vlo, vhi, vscale),
+                    quantize4(v128_load(src.add(i + 12) as *const v128), vlo, vhi, vscale),
+                )
+            };
+            let packed = u8x16_narrow_i16x8(i16x8_narrow_i32x4(q0, q1), i16x8_narrow_i32x4(q2, q3));
+            // SAFETY: 同上
+            unsafe { v128_store(out.as_mut_ptr().add(i) as *mut v128, packed) };
+            i += 16;
+        }
+        n
+    }
+
+    pub(super) fn dot(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len() / 4 * 4;
+        let mut acc = f32x4_splat(0.0);
+        let mut i = 0;
+        while i < n {
+            // SAFETY: i + 4 <= n 不越界
+            let (x, y) = unsafe {
+                (
+                    v128_load(a.as_ptr().add(i) as *const v128),
+                    v128_load(b.as_ptr().add(i) as *const v128),
+                )
+            };
+            acc = f32x4_add(acc, f32x4_mul(x, y));
+            i += 4;
+        }
+        let mut sum = (f32x4_extract_lane::<0>(acc) + f32x4_extract_lane::<1>(acc))
+            + (f32x4_extract_lane::<2>(acc) + f32x4_extract_lane::<3>(acc));
+        for j in n..a.len() {
+            sum += a[j] * b[j];
+        }
+        sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 可重現的偽隨機數 (xorshift32)，返回 [0, 1)
+    struct Rng(u32);
+
+    impl Rng {
+        fn next(&mut self) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            (self.0 >> 8) as f32 / (1u32 << 24) as f32
+        }
+
+        fn range(&mut self, lo: f32, hi: f32) -> f32 {
+            lo + (hi - lo) * self.next()
+        }
+    }
+
+    /// 所有可用的 SIMD 後端 (包括檢測到的最佳後端)
+    fn simd_kernels() -> Vec<Kernels> {
+        #[allow(unused_mut)]
+        let mut kernels = vec![Kernels::detect()];
+        #[cfg(target_arch = "x86_64")]
+        kernels.push(Kernels { backend: Backend::Sse2 });
+        kernels
+    }
+
+    /// 包含 SIMD 主循環與尾部元素的長度
+    const LENGTHS: &[usize] = &[0, 1, 3, 4, 7, 8, 15, 16, 17, 63, 257, 1025];
+
+    #[test]
+    fn magnitudes_match_scalar_exactly() {
+        let mut rng = Rng(0x1234_5678);
+        for &n in LENGTHS {
+            let spectrum: Vec<Complex<f32>> =
+                (0..n).map(|_| Complex::new(rng.range(-100.0, 100.0), rng.range(-100.0, 100.0))).collect();
+            let mut expected = vec![0.0; n];
+            Kernels::scalar().magnitudes(&spectrum, 0.37, &mut expected);
+            for kernels in simd_kernels() {
+                let mut actual = vec![0.0; n];
+                kernels.magnitudes(&spectrum, 0.37, &mut actual);
+                assert_eq!(actual, expected, "backend {}", kernels.backend().name());
+            }
+        }
+    }
+
+    #[test]
+    fn amplitude_to_db_within_documented_error() {
+        let mut rng = Rng(0x9e37_79b9);
+        let floor = 1e-10;
+        for &n in LENGTHS {
+            // 對數均勻分佈於 1e-12..1e4，包括低於 floor 的值
+            let amplitude: Vec<f32> = (0..n).map(|_| 10f32.powf(rng.range(-12.0, 4.0))).collect();
+            let mut expected = vec![0.0; n];
+            Kernels::scalar().amplitude_to_db(&amplitude, floor, &mut expected);
+            for kernels in simd_kernels() {
+                let mut actual = vec![0.0; n];
+                kernels.amplitude_to_db(&amplitude, floor, &mut actual);
+                for (i, (&a, &e)) in actual.iter().zip(&expected).enumerate() {
+                    assert!(
+                        (a - e).abs() <= 0.001,
+                        "backend {}: amplitude {} -> {} dB, expected {} dB",
+                        kernels.backend().name(),
+                        amplitude[i],
+                        a,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn quantize_matches_scalar() {
+        let mut rng = Rng(0x0bad_f00d);
+        let (lo, hi) = (-100.0, -20.0);
+        let scale = 255.0 / (hi - lo);
+        for &n in LENGTHS {
+            // 同一 dB 輸入：結果完全一致
+            let db: Vec<f32> = (0..n).map(|_| rng.range(-120.0, 0.0)).collect();
+            let mut expected = vec![0u8; n];
+            Kernels::scalar().quantize(&db, lo, hi, scale, &mut expected);
+            for kernels in simd_kernels() {
+                let mut actual = vec![0u8; n];
+                kernels.quantize(&db, lo, hi, scale, &mut actual);
+                assert_eq!(actual, expected, "backend {}", kernels.backend().name());
+            }
+
+            // 經過各自的 dB 轉換：最多相差 1 級
+            let amplitude: Vec<f32> = (0..n).map(|_| 10f32.powf(rng.range(-6.0, 0.0))).collect();
+            let quantized = |kernels: Kernels| {
+                let mut db = vec![0.0; n];
+                let mut out = vec![0u8; n];
+                kernels.amplitude_to_db(&amplitude, 1e-10, &mut db);
+                kernels.quantize(&db, lo, hi, scale, &mut out);
+                out
+            };
+            let expected = quantized(Kernels::scalar());
+            for kernels in simd_kernels() {
+                for (&a, &e) in quantized(kernels).iter().zip(&expected) {
+                    assert!(a.abs_diff(e) <= 1, "backend {}: {} vs {}", kernels.backend().name(), a, e);
+                }
+            }
+        }
+    }
+
+    /// 稀疏化之前的稠密矩陣乘法 (行步長 `stride`，每行使用前 magnitude.len() 個權重)
+    fn dense_filters(dense: &[f32], num_filters: usize, stride: usize, magnitude: &[f32]) -> Vec<f32> {
+        (0..num_filters)
+            .map(|i| {
+                let row_start = (i * stride).min(dense.len());
+                let row_end = (row_start + magnitude.len()).min(dense.len());
+                dense[row_start..row_end].iter().zip(magnitude).map(|(w, m)| w * m).sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn apply_filters_within_documented_error() {
+        let mut rng = Rng(0xdead_beef);
+        let (num_filters, freq_bins) = (64, 512);
+        // compute_spectrogram_u8 與 compute_spectrogram_image 的行步長
+        for stride in [freq_bins, freq_bins + 1] {
+            // 每個濾波器為隨機位置、隨機寬度的非負權重區段
+            let mut dense = vec![0.0f32; num_filters * stride];
+            for row in dense.chunks_exact_mut(stride) {
+                let start = (rng.next() * stride as f32) as usize;
+                let width = 1 + (rng.next() * 80.0) as usize;
+                for w in row.iter_mut().skip(start).take(width) {
+                    *w = rng.next();
+                }
+            }
+            let bank = SparseFilterBank::from_dense(&dense, num_filters, stride, freq_bins);
+            let magnitude: Vec<f32> = (0..freq_bins).map(|_| rng.range(0.0, 10.0)).collect();
+            let expected = dense_filters(&dense, num_filters, stride, &magnitude);
+
+            let mut scalar = vec![0.0; num_filters];
+            Kernels::scalar().apply_filters(&bank, &magnitude, &mut scalar);
+            assert_eq!(scalar, expected, "scalar, stride {}", stride);
+
+            for kernels in simd_kernels() {
+                let mut actual = vec![0.0; num_filters];
+                kernels.apply_filters(&bank, &magnitude, &mut actual);
+                for (&a, &e) in actual.iter().zip(&expected) {
+                    assert!(
+                        (a - e).abs() <= 1e-5 * e.abs().max(f32::MIN_POSITIVE),
+                        "backend {}, stride {}: {} vs {}",
+                        kernels.backend().name(),
+                        stride,
+                        a,
+                        e
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+// ============================================================
+// 功率譜峰值分析
+// 列出 flow_hz..fhigh_hz 內所有超過突出度閾值的峰值，
+// 並計算每個峰值在 -3/-6/-10/-20 dB 處的上下邊界、帶寬與 Q 值。
+// 輸入無效時返回明確的錯誤，而不是 0.0。
+// ============================================================
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::peaks::prominence;
+
+/// 計算邊界的相對電平 (dB，相對於峰值)
+pub const EDGE_LEVELS_DB: [f32; 4] = [3.0, 6.0, 10.0, 20.0];
+
+/// 峰值分析錯誤
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpectrumError {
+    /// 頻譜少於 3 個 bin
+    SpectrumTooShort { len: usize },
+    /// 採樣率必須為正數
+    InvalidSampleRate,
+    /// FFT 大小與頻譜長度不匹配 (頻譜最多 fft_size / 2 + 1 個 bin)
+    FftSizeMismatch { fft_size: usize, spectrum_len: usize },
+    /// 頻帶無效或不與頻譜重疊
+    InvalidBand { flow_hz: f32, fhigh_hz: f32 },
+    /// 頻譜包含 NaN 或無窮大
+    NonFiniteValue { bin: usize },
+}
+
+impl fmt::Display for SpectrumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpectrumError::SpectrumTooShort { len } => {
+                write!(f, "spectrum has {} bins, at least 3 are required", len)
+            }
+            SpectrumError::InvalidSampleRate => write!(f, "sample rate must be positive"),
+            SpectrumError::FftSizeMismatch { fft_size, spectrum_len } => write!(
+                f,
+                "spectrum has {} bins but fft_size {} allows at most {}",
+                spectrum_len,
+                fft_size,
+                fft_size / 2 + 1
+            ),
+            SpectrumError::InvalidBand { flow_hz, fhigh_hz } => {
+                write!(f, "frequency band {}..{} Hz does not overlap the spectrum", flow_hz, fhigh_hz)
+            }
+            SpectrumError::NonFiniteValue { bin } => write!(f, "spectrum value at bin {} is not finite", bin),
+        }
+    }
+}
+
+impl std::error::Error for SpectrumError {}
+
+/// 單個功率譜峰值
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpectrumPeak {
+    /// 插值後的峰值頻率 (Hz)
+    pub frequency_hz: f32,
+    /// 插值後的峰值電平 (dB)
+    pub level_db: f32,
+    /// 突出度 (dB)
+    pub prominence_db: f32,
+    /// 與 EDGE_LEVELS_DB 對應的下邊界頻率 (Hz)；未降到該電平時為 NaN
+    pub lower_edges_hz: [f32; 4],
+    /// 與 EDGE_LEVELS_DB 對應的上邊界頻率 (Hz)；未降到該電平時為 NaN
+    pub upper_edges_hz: [f32; 4],
+}
+
+impl SpectrumPeak {
+    /// 第 `level` 個邊界電平的帶寬 (Hz)
+    pub fn bandwidth_hz(&self, level: usize) -> f32 {
+        self.upper_edges_hz[level] - self.lower_edges_hz[level]
+    }
+
+    /// 第 `level` 個邊界電平的 Q 值 (峰值頻率 / 帶寬)
+    pub fn q_factor(&self, level: usize) -> f32 {
+        self.frequency_hz / self.bandwidth_hz(level)
+    }
+}
+
+/// 從峰值 bin 向一側搜索降到 `target` dB 的位置，並線性插值得到頻率
+///
+/// 遇到高於峰值的 bin (屬於另一個更高的峰) 或頻譜邊界時返回 NaN。
+fn find_edge(spectrum: &[f32], peak: usize, peak_db: f32, target: f32, step: isize, bin_hz: f32) -> f32 {
+    let mut prev = peak;
+    loop {
+        let next = prev as isize + step;
+        if next < 0 || next as usize >= spectrum.len() {
+            return f32::NAN;
+        }
+        let next = next as usize;
+        let value = spectrum[next];
+        if value > peak_db {
+            return f32::NAN;
+        }
+        if value <= target {
+            let prev_value = spectrum[prev];
+            let t = if prev_value > value { (prev_value - target) / (prev_value - value) } else { 0.0 };
+            return (prev as f32 + t * step as f32) * bin_hz;
+        }
+        prev = next;
+    }
+}
+
+/// 分析功率譜中的所有峰值
+///
+/// # Arguments
+/// * `spectrum_db` - 功率譜 (dB)，第 k 個 bin 對應 k * sample_rate / fft_size Hz
+/// * `sample_rate` - 採樣率 (Hz)
+/// * `fft_size` - FFT 大小
+/// * `flow_hz` - 峰值搜索下限 (Hz)
+/// * `fhigh_hz` - 峰值搜索上限 (Hz)
+/// * `min_prominence_db` - 最小突出度 (dB)
+///
+/// # Returns
+/// 按電平從高到低排序的峰值；頻帶內沒有峰值時為空列表
+pub fn find_spectrum_peaks(
+    spectrum_db: &[f32],
+    sample_rate: f32,
+    fft_size: usize,
+    flow_hz: f32,
+    fhigh_hz: f32,
+    min_prominence_db: f32,
+) -> Result<Vec<SpectrumPeak>, SpectrumError> {
+    if spectrum_db.len() < 3 {
+        return Err(SpectrumError::SpectrumTooShort { len: spectrum_db.len() });
+    }
+    if sample_rate.is_nan() || sample_rate <= 0.0 {
+        return Err(SpectrumError::InvalidSampleRate);
+    }
+    if fft_size == 0 || spectrum_db.len() > fft_size / 2 + 1 {
+        return Err(SpectrumError::FftSizeMismatch { fft_size, spectrum_len: spectrum_db.len() });
+    }
+    if let Some(bin) = spectrum_db.iter().position(|v| !v.is_finite()) {
+        return Err(SpectrumError::NonFiniteValue { bin });
+    }
+
+    let bin_hz = sample_rate / fft_size as f32;
+    let last_bin = spectrum_db.len() - 1;
+    let min_bin = (flow_hz.max(0.0) / bin_hz).ceil() as usize;
+    let max_bin = ((fhigh_hz / bin_hz).floor() as usize).min(last_bin);
+    if flow_hz.is_nan() || fhigh_hz.is_nan() || flow_hz >= fhigh_hz || fhigh_hz < 0.0 || min_bin > max_bin {
+        return Err(SpectrumError::InvalidBand { flow_hz, fhigh_hz });
+    }
+
+    let mut peaks = Vec::new();
+    for i in min_bin.max(1)..=max_bin.min(last_bin - 1) {
+        let db = spectrum_db[i];
+        if !(db > spectrum_db[i - 1] && db >= spectrum_db[i + 1]) {
+            continue;
+        }
+
+        let prominence_db = prominence(spectrum_db, i);
+        if prominence_db < min_prominence_db {
+            continue;
+        }
+
+        // 對 dB 值做拋物線插值 (與 find_peak_frequency_from_spectrum 一致)
+        let (alpha, beta, gamma) = (spectrum_db[i - 1], db, spectrum_db[i + 1]);
+        let denom = alpha - 2.0 * beta + gamma;
+        let offset = if denom.abs() > 1e-10 { (0.5 * (alpha - gamma) / denom).clamp(-0.5, 0.5) } else { 0.0 };
+        let level_db = beta - 0.25 * (alpha - gamma) * offset;
+
+        let mut lower_edges_hz = [f32::NAN; 4];
+        let mut upper_edges_hz = [f32::NAN; 4];
+        for (level, &drop) in EDGE_LEVELS_DB.iter().enumerate() {
+            let target = level_db - drop;
+            lower_edges_hz[level] = find_edge(spectrum_db, i, db, target, -1, bin_hz);
+            upper_edges_hz[level] = find_edge(spectrum_db, i, db, target, 1, bin_hz);
+        }
+
+        peaks.push(SpectrumPeak {
+            frequency_hz: (i as f32 + offset) * bin_hz,
+            level_db,
+            prominence_db,
+            lower_edges_hz,
+            upper_edges_hz,
+        });
+    }
+
+    peaks.sort_by(|a, b| b.level_db.total_cmp(&a.level_db));
+    Ok(peaks)
+}
+
+/// SpectrumPeakList: 功率譜峰值分析結果
+///
+/// 邊界、帶寬與 Q 值數組為 num_peaks x 4 (行優先)，
+/// 各列對應 -3、-6、-10、-20 dB；邊界未能確定時為 NaN。
+#[wasm_bindgen]
+pub struct SpectrumPeakList {
+    peaks: Vec<SpectrumPeak>,
+}
+
+impl SpectrumPeakList {
+    fn per_level(&self, f: impl Fn(&SpectrumPeak, usize) -> f32) -> Vec<f32> {
+        self.peaks
+            .iter()
+            .flat_map(|peak| (0..EDGE_LEVELS_DB.len()).map(move |level| (peak, level)))
+            .map(|(peak, level)| f(peak, level))
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl SpectrumPeakList {
+    /// 峰值數量
+    #[wasm_bindgen]
+    pub fn get_num_peaks(&self) -> usize {
+        self.peaks.len()
+    }
+
+    /// 邊界電平 (dB，相對於峰值): [3, 6, 10, 20]
+    #[wasm_bindgen]
+    pub fn get_edge_levels_db(&self) -> Vec<f32> {
+        EDGE_LEVELS_DB.to_vec()
+    }
+
+    /// 峰值頻率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_frequencies(&self) -> Vec<f32> {
+        self.peaks.iter().map(|p| p.frequency_hz).collect()
+    }
+
+    /// 峰值電平 (dB)
+    #[wasm_bindgen]
+    pub fn get_levels_db(&self) -> Vec<f32> {
+        self.peaks.iter().map(|p| p.level_db).collect()
+    }
+
+    /// 峰值突出度 (dB)
+    #[wasm_bindgen]
+    pub fn get_prominences_db(&self) -> Vec<f32> {
+        self.peaks.iter().map(|p| p.prominence_db).collect()
+    }
+
+    /// 下邊界頻率 (Hz，num_peaks x 4)
+    #[wasm_bindgen]
+    pub fn get_lower_edges(&self) -> Vec<f32> {
+        self.per_level(|p, level| p.lower_edges_hz[level])
+    }
+
+    /// 上邊界頻率 (Hz，num_peaks x 4)
+    #[wasm_bindgen]
+    pub fn get_upper_edges(&self) -> Vec<f32> {
+        self.per_level(|p, level| p.upper_edges_hz[level])
+    }
+
+    /// 帶寬 (Hz，num_peaks x 4)
+    #[wasm_bindgen]
+    pub fn get_bandwidths(&self) -> Vec<f32> {
+        self.per_level(|p, level| p.bandwidth_hz(level))
+    }
+
+    /// Q 值 (峰值頻率 / 帶寬，num_peaks x 4)
+    #[wasm_bindgen]
+    pub fn get_q_factors(&self) -> Vec<f32> {
+        self.per_level(|p, level| p.q_factor(level))
+    }
+}
+
+/// 分析功率譜中的所有峰值 (JavaScript 接口)
+///
+/// # Arguments
+/// * `spectrum_db` - 功率譜 (dB 值，例如 compute_power_spectrum 的輸出)
+/// * `sample_rate` - 採樣率 (Hz)
+/// * `fft_size` - FFT 大小
+/// * `flow_hz` - 峰值搜索下限 (Hz)
+/// * `fhigh_hz` - 峰值搜索上限 (Hz)
+/// * `min_prominence_db` - 最小突出度 (dB，典型值: 6)
+///
+/// # Returns
+/// SpectrumPeakList 對象；輸入無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+pub fn analyze_spectrum_peaks(
+    spectrum_db: &[f32],
+    sample_rate: f32,
+    fft_size: usize,
+    flow_hz: f32,
+    fhigh_hz: f32,
+    min_prominence_db: f32,
+) -> Result<SpectrumPeakList, JsError> {
+    let peaks = find_spectrum_peaks(spectrum_db, sample_rate, fft_size, flow_hz, fhigh_hz, min_prominence_db)?;
+    Ok(SpectrumPeakList { peaks })
+}
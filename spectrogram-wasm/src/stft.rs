@@ -0,0 +1,132 @@
+// ============================================================
+// 逐幀 STFT / ISTFT 輔助函數
+// 供 SpectrogramEngine 的各個計算路徑共用（順序或並行執行），
+// 以及回放渲染所需的重疊相加 (overlap-add) 逆變換
+// ============================================================
+
+use std::sync::Arc;
+
+use num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+
+use crate::simd::Kernels;
+
+/// 每個工作線程私有的 FFT 緩衝區
+pub(crate) struct FrameScratch {
+    buffer: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl FrameScratch {
+    pub(crate) fn new(fft: &dyn Fft<f32>) -> FrameScratch {
+        FrameScratch {
+            buffer: vec![Complex::default(); fft.len()],
+            scratch: vec![Complex::default(); fft.get_inplace_scratch_len()],
+        }
+    }
+}
+
+/// 計算給定步長下的完整幀數
+pub(crate) fn frame_count(data_len: usize, fft_size: usize, step: usize) -> usize {
+    if step == 0 || data_len < fft_size {
+        0
+    } else {
+        (data_len - fft_size) / step + 1
+    }
+}
+
+/// 對單幀應用窗函數、執行 FFT 並寫入線性幅度
+///
+/// `frame` 長度不足 FFT 大小時以零填充；`out` 長度為 fft_size / 2。
+pub(crate) fn frame_magnitudes(
+    fft: &dyn Fft<f32>,
+    window: &[f32],
+    frame: &[f32],
+    state: &mut FrameScratch,
+    kernels: &Kernels,
+    out: &mut [f32],
+) {
+    let fft_size = fft.len();
+
+    for (i, slot) in state.buffer.iter_mut().enumerate() {
+        let sample = frame.get(i).copied().unwrap_or(0.0);
+        *slot = Complex::new(sample * window[i], 0.0);
+    }
+
+    fft.process_with_scratch(&mut state.buffer, &mut state.scratch);
+
+    let scale = 2.0 / fft_size as f32;
+    kernels.magnitudes(&state.buffer, scale, out);
+}
+
+/// 對單幀應用窗函數、執行 FFT 並寫入複數頻譜
+///
+/// `frame` 長度不足 FFT 大小時以零填充；`out` 長度為 fft_size / 2 + 1 (未縮放)。
+pub(crate) fn frame_spectrum(
+    fft: &dyn Fft<f32>,
+    window: &[f32],
+    frame: &[f32],
+    state: &mut FrameScratch,
+    out: &mut [Complex<f32>],
+) {
+    for (i, slot) in state.buffer.iter_mut().enumerate() {
+        let sample = frame.get(i).copied().unwrap_or(0.0);
+        *slot = Complex::new(sample * window[i], 0.0);
+    }
+
+    fft.process_with_scratch(&mut state.buffer, &mut state.scratch);
+    out.copy_from_slice(&state.buffer[..out.len()]);
+}
+
+/// 加權重疊相加逆 STFT
+///
+/// 每幀以共軛對稱補全頻譜、逆 FFT、乘以合成窗後累加，
+/// 最後除以窗函數平方和，使分析 / 合成窗組合在任何步長下都能完美重建。
+pub(crate) struct OverlapAdd {
+    ifft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    state: FrameScratch,
+    output: Vec<f32>,
+    norm: Vec<f32>,
+}
+
+impl OverlapAdd {
+    pub(crate) fn new(planner: &mut FftPlanner<f32>, window: Vec<f32>, output_len: usize) -> OverlapAdd {
+        let ifft = planner.plan_fft_inverse(window.len());
+        let state = FrameScratch::new(ifft.as_ref());
+        OverlapAdd {
+            ifft,
+            window,
+            state,
+            output: vec![0.0; output_len],
+            norm: vec![0.0; output_len],
+        }
+    }
+
+    /// 將單邊頻譜 (fft_size / 2 + 1 個 bin，與 frame_spectrum 相同縮放) 合成並累加到 `offset` 處
+    pub(crate) fn add_frame(&mut self, spectrum: &[Complex<f32>], offset: usize) {
+        let fft_size = self.window.len();
+        let buffer = &mut self.state.buffer;
+        buffer[..spectrum.len()].copy_from_slice(spectrum);
+        for k in spectrum.len()..fft_size {
+            buffer[k] = buffer[fft_size - k].conj();
+        }
+        self.ifft.process_with_scratch(buffer, &mut self.state.scratch);
+
+        let scale = 1.0 / fft_size as f32;
+        for (i, (c, &w)) in buffer.iter().zip(&self.window).enumerate() {
+            let Some(slot) = self.output.get_mut(offset + i) else { break };
+            *slot += c.re * scale * w;
+            self.norm[offset + i] += w * w;
+        }
+    }
+
+    /// 完成合成，返回重建信號
+    pub(crate) fn finish(self) -> Vec<f32> {
+        self.output
+            .into_iter()
+            .zip(self.norm)
+            .map(|(y, n)| if n > 1e-6 { y / n } else { 0.0 })
+            .collect()
+    }
+}
@@ -0,0 +1,232 @@
+// ============================================================
+// 錄音時間戳
+// ISO 8601 日期時間的解析與格式化 (可選 UTC 偏移與微秒)，
+// 以及與 Unix 時間的互相轉換，供 GUANO 元數據與文件編輯使用。
+// ============================================================
+
+/// 日期時間 (公曆)
+///
+/// `utc_offset_minutes` 為 None 時表示未知時區的本地時間；
+/// 轉換為 Unix 時間時按 UTC 處理。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timestamp {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub microsecond: u32,
+    pub utc_offset_minutes: Option<i16>,
+}
+
+/// 公曆日期 -> 距 1970-01-01 的天數 (Howard Hinnant 算法)
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let y = year as i64 - if month <= 2 { 1 } else { 0 };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// 距 1970-01-01 的天數 -> 公曆日期
+fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+    (year, month, day)
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        _ if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        _ => 28,
+    }
+}
+
+/// 當前 Unix 時間 (秒)；wasm 中取自 JavaScript `Date.now()`
+pub fn now_unix_seconds() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now() / 1000.0
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0.0, |elapsed| elapsed.as_secs_f64())
+    }
+}
+
+/// 解析固定位數的十進制數字
+fn digits(s: &str, range: std::ops::Range<usize>) -> Option<u32> {
+    let part = s.get(range)?;
+    if part.bytes().all(|b| b.is_ascii_digit()) {
+        part.parse().ok()
+    } else {
+        None
+    }
+}
+
+impl Timestamp {
+    /// 由各字段構造並檢查範圍
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        year: i32,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+        microsecond: u32,
+        utc_offset_minutes: Option<i16>,
+    ) -> Option<Timestamp> {
+        let valid = (1..=12).contains(&month)
+            && day >= 1
+            && day <= days_in_month(year, month)
+            && hour < 24
+            && minute < 60
+            && second < 61
+            && microsecond < 1_000_000
+            && utc_offset_minutes.is_none_or(|m| m.abs() < 24 * 60);
+        valid.then_some(Timestamp {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            microsecond,
+            utc_offset_minutes,
+        })
+    }
+
+    /// 解析 ISO 8601 日期時間
+    ///
+    /// 接受 `YYYY-MM-DDTHH:MM:SS`，日期與時間之間也可以用空格分隔，
+    /// 可選小數秒 (最多取 6 位)，以及 `Z`、`±HH:MM`、`±HHMM` 或 `±HH` 時區偏移。
+    pub fn parse(text: &str) -> Option<Timestamp> {
+        let s = text.trim();
+        if s.len() < 19 || !matches!(s.as_bytes()[10], b'T' | b't' | b' ') {
+            return None;
+        }
+        let separators = [(4, b'-'), (7, b'-'), (13, b':'), (16, b':')];
+        if separators.iter().any(|&(i, c)| s.as_bytes()[i] != c) {
+            return None;
+        }
+
+        let year = digits(s, 0..4)? as i32;
+        let month = digits(s, 5..7)? as u8;
+        let day = digits(s, 8..10)? as u8;
+        let hour = digits(s, 11..13)? as u8;
+        let minute = digits(s, 14..16)? as u8;
+        let second = digits(s, 17..19)? as u8;
+
+        let mut rest = &s[19..];
+        let mut microsecond = 0;
+        if let Some(fraction) = rest.strip_prefix(['.', ',']) {
+            let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+            if len == 0 {
+                return None;
+            }
+            let kept = &fraction[..len.min(6)];
+            microsecond = kept.parse::<u32>().ok()? * 10u32.pow(6 - kept.len() as u32);
+            rest = &fraction[len..];
+        }
+
+        let utc_offset_minutes = match rest {
+            "" => None,
+            "Z" | "z" => Some(0),
+            _ => {
+                let sign = match rest.as_bytes()[0] {
+                    b'+' => 1,
+                    b'-' => -1,
+                    _ => return None,
+                };
+                let offset = &rest[1..];
+                let (hours, minutes) = match offset.len() {
+                    2 => (digits(offset, 0..2)?, 0),
+                    4 => (digits(offset, 0..2)?, digits(offset, 2..4)?),
+                    5 if offset.as_bytes()[2] == b':' => (digits(offset, 0..2)?, digits(offset, 3..5)?),
+                    _ => return None,
+                };
+                if minutes >= 60 {
+                    return None;
+                }
+                Some(sign * (hours * 60 + minutes) as i16)
+            }
+        };
+
+        Timestamp::new(year, month, day, hour, minute, second, microsecond, utc_offset_minutes)
+    }
+
+    /// 由 Unix 時間 (秒) 構造，並以指定 UTC 偏移表示為本地時間
+    pub fn from_unix_seconds(seconds: f64, utc_offset_minutes: Option<i16>) -> Timestamp {
+        let local = seconds + utc_offset_minutes.unwrap_or(0) as f64 * 60.0;
+        let total_micros = (local * 1e6).round() as i64;
+        let micros_per_day = 86_400_000_000i64;
+        let days = total_micros.div_euclid(micros_per_day);
+        let of_day = total_micros.rem_euclid(micros_per_day);
+        let (year, month, day) = civil_from_days(days);
+        let secs = of_day / 1_000_000;
+
+        Timestamp {
+            year,
+            month,
+            day,
+            hour: (secs / 3600) as u8,
+            minute: (secs / 60 % 60) as u8,
+            second: (secs % 60) as u8,
+            microsecond: (of_day % 1_000_000) as u32,
+            utc_offset_minutes,
+        }
+    }
+
+    /// Unix 時間 (秒)；未知時區時按 UTC 計算
+    pub fn to_unix_seconds(&self) -> f64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let secs = days * 86_400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+            - self.utc_offset_minutes.unwrap_or(0) as i64 * 60;
+        secs as f64 + self.microsecond as f64 / 1e6
+    }
+
+    /// 加上若干秒 (可為負)，保留時區偏移
+    pub fn add_seconds(&self, seconds: f64) -> Timestamp {
+        Timestamp::from_unix_seconds(self.to_unix_seconds() + seconds, self.utc_offset_minutes)
+    }
+
+    /// 格式化為 ISO 8601 (`YYYY-MM-DDTHH:MM:SS[.ffffff][Z|±HH:MM]`)
+    ///
+    /// 小數秒只在非零時輸出，並去掉末尾的 0。
+    pub fn to_iso8601(&self) -> String {
+        let mut text = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        );
+        if self.microsecond > 0 {
+            let fraction = format!("{:06}", self.microsecond);
+            text.push('.');
+            text.push_str(fraction.trim_end_matches('0'));
+        }
+        match self.utc_offset_minutes {
+            None => {}
+            Some(0) => text.push('Z'),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let abs = offset.unsigned_abs();
+                text.push_str(&format!("{}{:02}:{:02}", sign, abs / 60, abs % 60));
+            }
+        }
+        text
+    }
+}
@@ -0,0 +1,209 @@
+// ============================================================
+// 單頻能量追蹤
+// Goertzel 濾波器組與滑動 DFT，返回指定頻率的能量隨時間變化曲線，
+// 用於在整個文件中快速掃描恆頻 (CF) 蝙蝠的叫聲。
+// ============================================================
+
+use std::f64::consts::PI;
+
+use num_complex::Complex;
+use wasm_bindgen::prelude::*;
+
+use crate::create_window;
+use crate::parallel::for_each_row;
+use crate::stft::frame_count;
+
+/// 幅度下限 (約 -200 dB)，防止 log10(0)
+const MIN_AMPLITUDE: f32 = 1e-10;
+
+/// ToneTracks: 各頻率的電平-時間曲線
+///
+/// 電平為正弦波幅度的 dB 值 (幅度 1.0 的正弦波約為 0 dB)，
+/// 數組為 num_frequencies x num_frames (行優先)。
+#[wasm_bindgen]
+pub struct ToneTracks {
+    frequencies: Vec<f32>,
+    times: Vec<f32>,
+    levels_db: Vec<f32>,
+}
+
+impl ToneTracks {
+    /// 按 (幀長, 步長) 分幀並為每個頻率填充一行電平
+    fn compute(
+        audio_len: usize,
+        sample_rate: f32,
+        frequencies: &[f32],
+        frame_size: usize,
+        hop_size: usize,
+        track: impl Fn(f64, &mut [f32]) + Sync + Send,
+    ) -> ToneTracks {
+        let num_frames = if sample_rate > 0.0 { frame_count(audio_len, frame_size, hop_size) } else { 0 };
+        let times = (0..num_frames)
+            .map(|frame| (frame * hop_size) as f32 / sample_rate + frame_size as f32 / (2.0 * sample_rate))
+            .collect();
+
+        let mut levels_db = vec![0.0f32; frequencies.len() * num_frames];
+        for_each_row(&mut levels_db, num_frames, || (), |_, freq_idx, row| {
+            let omega = 2.0 * PI * frequencies[freq_idx] as f64 / sample_rate as f64;
+            track(omega, row);
+        });
+
+        ToneTracks {
+            frequencies: frequencies.to_vec(),
+            times,
+            levels_db,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl ToneTracks {
+    /// 追蹤的頻率數量
+    #[wasm_bindgen]
+    pub fn get_num_frequencies(&self) -> usize {
+        self.frequencies.len()
+    }
+
+    /// 每條曲線的幀數
+    #[wasm_bindgen]
+    pub fn get_num_frames(&self) -> usize {
+        self.times.len()
+    }
+
+    /// 追蹤的頻率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_frequencies(&self) -> Vec<f32> {
+        self.frequencies.clone()
+    }
+
+    /// 每幀中心的時間 (秒)
+    #[wasm_bindgen]
+    pub fn get_times(&self) -> Vec<f32> {
+        self.times.clone()
+    }
+
+    /// 電平 (dB，num_frequencies x num_frames)
+    #[wasm_bindgen]
+    pub fn get_levels_db(&self) -> Vec<f32> {
+        self.levels_db.clone()
+    }
+
+    /// 第 `index` 個頻率的電平曲線 (dB)；索引越界時返回空數組
+    #[wasm_bindgen]
+    pub fn get_track(&self, index: usize) -> Vec<f32> {
+        let num_frames = self.times.len();
+        self.levels_db
+            .get(index * num_frames..(index + 1) * num_frames)
+            .map(|row| row.to_vec())
+            .unwrap_or_default()
+    }
+}
+
+/// 幅度 -> dB
+fn amplitude_db(amplitude: f64) -> f32 {
+    20.0 * (amplitude as f32).max(MIN_AMPLITUDE).log10()
+}
+
+/// 使用 Goertzel 濾波器組計算指定頻率的電平曲線
+///
+/// 每個頻率使用廣義 Goertzel 算法 (頻率不必落在 bin 中心)，
+/// 每幀複雜度為 O(block_size)，適合頻率數量少、步長接近塊大小的情況。
+///
+/// # Arguments
+/// * `audio_data` - 音頻數據 (Float32Array)
+/// * `sample_rate` - 採樣率 (Hz)
+/// * `frequencies` - 要追蹤的頻率 (Hz)
+/// * `block_size` - 每塊樣本數 (決定頻率解析度 ≈ sample_rate / block_size)
+/// * `hop_size` - 相鄰塊之間的步長
+/// * `window_type` - 窗函數類型 (hann, hamming, blackman, gauss, rectangular, triangular)
+///
+/// # Returns
+/// ToneTracks 對象；參數無效時幀數為 0
+#[wasm_bindgen]
+pub fn compute_goertzel_tracks(
+    audio_data: &[f32],
+    sample_rate: f32,
+    frequencies: &[f32],
+    block_size: usize,
+    hop_size: usize,
+    window_type: &str,
+) -> ToneTracks {
+    let window = create_window(window_type, block_size, 0.16);
+    let window_sum: f64 = window.iter().map(|&w| w as f64).sum();
+
+    ToneTracks::compute(audio_data.len(), sample_rate, frequencies, block_size, hop_size, |omega, row| {
+        let coeff = 2.0 * omega.cos();
+        for (frame, level) in row.iter_mut().enumerate() {
+            let block = &audio_data[frame * hop_size..frame * hop_size + block_size];
+            let (mut s1, mut s2) = (0.0f64, 0.0f64);
+            for (&x, &w) in block.iter().zip(&window) {
+                let s0 = x as f64 * w as f64 + coeff * s1 - s2;
+                s2 = s1;
+                s1 = s0;
+            }
+            let power = (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0);
+            *level = amplitude_db(2.0 * power.sqrt() / window_sum);
+        }
+    })
+}
+
+/// 使用滑動 DFT 計算指定頻率的電平曲線
+///
+/// 每輸入一個樣本以 O(1) 更新 DFT 值，總複雜度與步長無關，
+/// 適合小步長 (高時間解析度) 的長文件掃描。
+/// 內部狀態使用 f64 累加以避免長文件的漂移。
+///
+/// # Arguments
+/// * `audio_data` - 音頻數據 (Float32Array)
+/// * `sample_rate` - 採樣率 (Hz)
+/// * `frequencies` - 要追蹤的頻率 (Hz)
+/// * `window_size` - 滑動窗口長度
+/// * `hop_size` - 輸出間隔 (樣本數)
+/// * `window_type` - "hann" (在頻域以相鄰兩個 bin 實現) 或 "rectangular"
+///
+/// # Returns
+/// ToneTracks 對象，分幀方式與 compute_goertzel_tracks 相同
+#[wasm_bindgen]
+pub fn compute_sliding_dft_tracks(
+    audio_data: &[f32],
+    sample_rate: f32,
+    frequencies: &[f32],
+    window_size: usize,
+    hop_size: usize,
+    window_type: &str,
+) -> ToneTracks {
+    let hann = window_type == "hann";
+    let n = window_size as f64;
+
+    ToneTracks::compute(audio_data.len(), sample_rate, frequencies, window_size, hop_size, |omega, row| {
+        // S_n(ω) = Σ_{m=0}^{N-1} x[n-m]·e^{jωm}
+        //        = x[n] + e^{jω}·S_{n-1}(ω) - e^{jωN}·x[n-N]
+        // 週期 Hann 窗: 0.5·S(ω) - 0.25·S(ω + 2π/N) - 0.25·S(ω - 2π/N)
+        let offsets: &[f64] = if hann { &[0.0, 1.0, -1.0] } else { &[0.0] };
+        let rotations: Vec<(Complex<f64>, Complex<f64>)> = offsets
+            .iter()
+            .map(|&k| {
+                let w = omega + 2.0 * PI * k / n;
+                (Complex::from_polar(1.0, w), Complex::from_polar(1.0, w * n))
+            })
+            .collect();
+        let (weights, gain): (&[f64], f64) = if hann { (&[0.5, -0.25, -0.25], 0.5 * n) } else { (&[1.0], n) };
+
+        let mut states = vec![Complex::new(0.0f64, 0.0); rotations.len()];
+        let mut next_frame = 0;
+        for (i, &x) in audio_data.iter().enumerate() {
+            let x = x as f64;
+            let old = if i >= window_size { audio_data[i - window_size] as f64 } else { 0.0 };
+            for (state, &(step, wrap)) in states.iter_mut().zip(&rotations) {
+                *state = step * *state + x - wrap * old;
+            }
+
+            // 第 f 幀覆蓋 [f·hop, f·hop + N)，在其最後一個樣本處輸出
+            if next_frame < row.len() && i + 1 == next_frame * hop_size + window_size {
+                let value: Complex<f64> = states.iter().zip(weights).map(|(s, &w)| s * w).sum();
+                row[next_frame] = amplitude_db(2.0 * value.norm() / gain);
+                next_frame += 1;
+            }
+        }
+    })
+}
@@ -0,0 +1,794 @@
+// ============================================================
+// 錄音設備元數據
+// 將 GUANO、Wildlife Acoustics `wamd` 塊與 AudioMoth 的
+// LIST/INFO `ICMT` / `IART` 注釋統一為 RecordingMetadata
+// (時間戳、位置、設備、增益、溫度、觸發設置)，並可轉換回 GUANO。
+// ============================================================
+
+use wasm_bindgen::prelude::*;
+
+use crate::flac::FlacInfo;
+use crate::guano::{GuanoMetadata, GUANO_CHUNK_ID};
+use crate::timestamp::Timestamp;
+use crate::wav::WavInfo;
+
+/// Wildlife Acoustics 元數據塊 ID
+pub const WAMD_CHUNK_ID: &[u8; 4] = b"wamd";
+
+/// AudioMoth 字段使用的 GUANO 命名空間
+const AUDIOMOTH_NAMESPACE: &str = "AudioMoth";
+/// Wildlife Acoustics 字段使用的 GUANO 命名空間
+const WA_NAMESPACE: &str = "WA";
+
+/// `wamd` 子塊 ID 與名稱
+const WAMD_FIELDS: &[(u16, &str)] = &[
+    (0x00, "Version"),
+    (0x01, "Model"),
+    (0x02, "Serial"),
+    (0x03, "Firmware"),
+    (0x04, "Prefix"),
+    (0x05, "Timestamp"),
+    (0x06, "GPS First"),
+    (0x07, "GPS Track"),
+    (0x08, "Software"),
+    (0x09, "License"),
+    (0x0a, "Notes"),
+    (0x0b, "Auto ID"),
+    (0x0c, "Manual ID"),
+    (0x0d, "Voice Notes"),
+    (0x0e, "Auto ID Stats"),
+    (0x0f, "Time Expansion"),
+    (0x10, "Program"),
+    (0x11, "Run State"),
+    (0x12, "Microphone"),
+    (0x13, "Sensitivity"),
+];
+
+/// 統一的錄音元數據
+///
+/// 各字段缺失時為 None；`fields` 保存其他廠商字段 (帶命名空間的 GUANO 鍵)。
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecordingMetadata {
+    /// 元數據來源 ("GUANO"、"wamd"、"AudioMoth")，按優先級排列
+    pub(crate) sources: Vec<String>,
+    pub(crate) timestamp: Option<Timestamp>,
+    pub(crate) latitude: Option<f64>,
+    pub(crate) longitude: Option<f64>,
+    pub(crate) elevation_m: Option<f64>,
+    pub(crate) make: Option<String>,
+    pub(crate) model: Option<String>,
+    pub(crate) serial: Option<String>,
+    pub(crate) firmware: Option<String>,
+    /// 增益設置 (如 "12 dB"、"medium")
+    pub(crate) gain: Option<String>,
+    pub(crate) temperature_int_c: Option<f64>,
+    pub(crate) temperature_ext_c: Option<f64>,
+    pub(crate) battery_v: Option<f64>,
+    /// 觸發設置描述
+    pub(crate) trigger: Option<String>,
+    pub(crate) filter_hp_khz: Option<f64>,
+    pub(crate) filter_lp_khz: Option<f64>,
+    pub(crate) time_expansion: Option<f64>,
+    pub(crate) notes: Option<String>,
+    pub(crate) fields: Vec<(String, String)>,
+}
+
+/// 在 JSON 風格文本 (如 `WA|Song Meter|Audio settings`) 中查找 `"key":value`
+fn json_value<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    let pattern = format!("\"{}\":", key);
+    let start = text.find(&pattern)? + pattern.len();
+    let rest = text[start..].trim_start();
+    let end = rest.find([',', '}', ']']).unwrap_or(rest.len());
+    Some(rest[..end].trim().trim_matches('"'))
+}
+
+/// 解析 `wamd` 的 GPS 字段
+///
+/// SM3 / SM4：`WGS84,緯度,N|S,經度,E|W[,海拔]`；EMTouch：`WGS84,[-]緯度,[-]經度[,海拔]`。
+fn parse_wamd_gps(text: &str) -> Option<(f64, f64, Option<f64>)> {
+    let parts: Vec<&str> = text.split(',').map(str::trim).collect();
+    if parts.first() != Some(&"WGS84") {
+        return None;
+    }
+    let number = |s: &str| s.parse::<f64>().ok();
+    let hemisphere = |s: &str, negative: &str| if s.eq_ignore_ascii_case(negative) { -1.0 } else { 1.0 };
+    match parts.len() {
+        5 | 6 if parts[2].len() == 1 && parts[4].len() == 1 => Some((
+            number(parts[1])? * hemisphere(parts[2], "S"),
+            number(parts[3])? * hemisphere(parts[4], "W"),
+            parts.get(5).and_then(|s| number(s)),
+        )),
+        3 | 4 => Some((number(parts[1])?, number(parts[2])?, parts.get(3).and_then(|s| number(s)))),
+        _ => None,
+    }
+}
+
+/// 解析 LIST/INFO 塊為 (ID, 文本)
+pub(crate) fn parse_list_info(data: &[u8]) -> Vec<([u8; 4], String)> {
+    let mut entries = Vec::new();
+    if data.len() < 4 || &data[0..4] != b"INFO" {
+        return entries;
+    }
+    let mut offset = 4;
+    while offset + 8 <= data.len() {
+        let id: [u8; 4] = data[offset..offset + 4].try_into().unwrap();
+        let size = u32::from_le_bytes(data[offset + 4..offset + 8].try_into().unwrap()) as usize;
+        let end = (offset + 8 + size).min(data.len());
+        let text = String::from_utf8_lossy(&data[offset + 8..end]);
+        entries.push((id, text.trim_end_matches('\0').trim().to_string()));
+        offset = end + (size & 1);
+    }
+    entries
+}
+
+/// 解析 AudioMoth 的時間與時區：`HH:MM:SS[.fff] DD/MM/YYYY (UTC[±H[:MM]])`
+fn parse_audiomoth_time(text: &str) -> Option<Timestamp> {
+    let mut parts = text.split_whitespace();
+    let time = parts.next()?;
+    let date = parts.next()?;
+    let zone = parts.next()?.strip_prefix("(UTC")?.strip_suffix(')')?;
+
+    let (hms, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let hms: Vec<u8> = hms.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let dmy: Vec<i32> = date.split('/').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    if hms.len() != 3 || dmy.len() != 3 {
+        return None;
+    }
+    let microsecond = if fraction.is_empty() {
+        0
+    } else {
+        // 只接受 ASCII 數字 (最多取 6 位)
+        let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 || len != fraction.len() {
+            return None;
+        }
+        let kept = &fraction[..len.min(6)];
+        kept.parse::<u32>().ok()? * 10u32.pow(6 - kept.len() as u32)
+    };
+    let month = u8::try_from(dmy[1]).ok()?;
+    let day = u8::try_from(dmy[0]).ok()?;
+
+    let offset = if zone.is_empty() {
+        0
+    } else {
+        let sign = match zone.as_bytes()[0] {
+            b'+' => 1,
+            b'-' => -1,
+            _ => return None,
+        };
+        let (hours, minutes) = zone[1..].split_once(':').unwrap_or((&zone[1..], "0"));
+        let (hours, minutes) = (hours.parse::<i32>().ok()?, minutes.parse::<i32>().ok()?);
+        if !(0..=14).contains(&hours) || !(0..=59).contains(&minutes) {
+            return None;
+        }
+        i16::try_from(sign * (hours * 60 + minutes)).ok()?
+    };
+    Timestamp::new(dmy[2], month, day, hms[0], hms[1], hms[2], microsecond, Some(offset))
+}
+
+/// 將 AudioMoth 注釋中的錄音時間後移 `seconds` 秒 (保留時區、小數位數與其餘文本)；
+/// 不是 AudioMoth 注釋或時間無法解析時返回 None
+pub(crate) fn shift_audiomoth_comment(comment: &str, seconds: f64) -> Option<String> {
+    let trimmed = comment.trim_start();
+    let rest = trimmed.strip_prefix("Recorded at ")?;
+    let prefix = &comment[..comment.len() - rest.len()];
+    let zone_start = rest.find(" (")?;
+    let time_end = zone_start + rest[zone_start..].find(')')? + 1;
+    let timestamp = parse_audiomoth_time(&rest[..time_end])?.add_seconds(seconds);
+
+    let time_text = rest[..zone_start].split(' ').next()?;
+    let fraction_digits = time_text.split_once('.').map_or(0, |(_, fraction)| fraction.len());
+    let mut time = format!("{:02}:{:02}:{:02}", timestamp.hour, timestamp.minute, timestamp.second);
+    if fraction_digits > 0 {
+        let fraction = format!("{:06}", timestamp.microsecond);
+        time.push('.');
+        time.push_str(&fraction[..fraction_digits.min(6)]);
+    }
+    Some(format!(
+        "{}{} {:02}/{:02}/{:04}{}",
+        prefix,
+        time,
+        timestamp.day,
+        timestamp.month,
+        timestamp.year,
+        &rest[zone_start..]
+    ))
+}
+
+/// 將 LIST/INFO 塊中 AudioMoth `ICMT` 注釋的錄音時間後移 `seconds` 秒
+///
+/// 其他條目原樣複製；沒有可更新的注釋時返回 None。
+pub(crate) fn shift_list_info(data: &[u8], seconds: f64) -> Option<Vec<u8>> {
+    if data.len() < 4 || &data[0..4] != b"INFO" {
+        return None;
+    }
+    let mut output = data[..4].to_vec();
+    let mut shifted = false;
+    let mut offset = 4;
+    while offset + 8 <= data.len() {
+        let id: [u8; 4] = data[offset..offset + 4].try_into().unwrap();
+        let size = u32::from_le_bytes(data[offset + 4..offset + 8].try_into().unwrap()) as usize;
+        let end = (offset + 8 + size).min(data.len());
+        let value = &data[offset + 8..end];
+        offset = end + (size & 1);
+
+        let text = String::from_utf8_lossy(value);
+        let comment = if &id == b"ICMT" { shift_audiomoth_comment(text.trim_end_matches('\0'), seconds) } else { None };
+        let value = match &comment {
+            Some(comment) => {
+                shifted = true;
+                let mut bytes = comment.as_bytes().to_vec();
+                bytes.push(0);
+                bytes
+            }
+            None => value.to_vec(),
+        };
+        output.extend_from_slice(&id);
+        output.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        output.extend_from_slice(&value);
+        if value.len() % 2 == 1 {
+            output.push(0);
+        }
+    }
+    shifted.then_some(output)
+}
+
+/// 將 `wamd` 塊的 Timestamp 子塊後移 `seconds` 秒 (保留日期與時間之間的分隔符與時區)
+///
+/// 其他子塊原樣複製；沒有可解析的時間時返回 None。
+pub(crate) fn shift_wamd_timestamp(data: &[u8], seconds: f64) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(data.len() + 8);
+    let mut shifted = false;
+    let mut offset = 0;
+    while offset + 6 <= data.len() {
+        let id = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let size = u32::from_le_bytes(data[offset + 2..offset + 6].try_into().unwrap()) as usize;
+        let end = (offset + 6 + size).min(data.len());
+        let value = &data[offset + 6..end];
+        offset = end;
+
+        let text = String::from_utf8_lossy(value);
+        let text = text.trim_end_matches('\0').trim();
+        let timestamp = if id == 0x05 { Timestamp::parse(text) } else { None };
+        let value = match timestamp {
+            Some(timestamp) => {
+                shifted = true;
+                let mut iso = timestamp.add_seconds(seconds).to_iso8601();
+                if text.as_bytes()[10] == b' ' {
+                    iso.replace_range(10..11, " ");
+                }
+                iso.into_bytes()
+            }
+            None => value.to_vec(),
+        };
+        output.extend_from_slice(&id.to_le_bytes());
+        output.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        output.extend_from_slice(&value);
+    }
+    shifted.then_some(output)
+}
+
+/// 取 `text` 中 `prefix` 之後直到 `suffix` 的數字
+fn number_after(text: &str, prefix: &str, suffix: char) -> Option<f64> {
+    let start = text.find(prefix)? + prefix.len();
+    let rest = &text[start..];
+    let rest = rest.trim_start_matches(|c: char| c.is_alphabetic() || c == ' ');
+    rest[..rest.find(suffix)?].trim().parse().ok()
+}
+
+/// 取 `xxkHz` 中的數值 (kHz)
+fn khz_values(text: &str) -> Vec<f64> {
+    text.split("kHz")
+        .filter_map(|part| part.rsplit(|c: char| !(c.is_ascii_digit() || c == '.')).next()?.parse().ok())
+        .collect()
+}
+
+impl RecordingMetadata {
+    fn with_source(source: &str) -> RecordingMetadata {
+        RecordingMetadata { sources: vec![source.to_string()], ..Default::default() }
+    }
+
+    fn push_field(&mut self, key: String, value: &str) {
+        if !value.is_empty() {
+            self.fields.push((key, value.to_string()));
+        }
+    }
+
+    /// 由 GUANO 元數據轉換
+    ///
+    /// 規範字段映射到對應屬性，其他字段 (包括廠商命名空間) 原樣保存在 `fields` 中。
+    /// Wildlife Acoustics 的 `WA|Song Meter|Audio settings` 中的增益與觸發參數也被提取。
+    pub fn from_guano(guano: &GuanoMetadata) -> RecordingMetadata {
+        let mut meta = RecordingMetadata::with_source("GUANO");
+        meta.timestamp = guano.timestamp();
+        if let Some((lat, lon)) = guano.position() {
+            meta.latitude = Some(lat);
+            meta.longitude = Some(lon);
+        }
+        meta.elevation_m = guano.float("Loc Elevation");
+        meta.make = guano.get("Make").map(str::to_string);
+        meta.model = guano.get("Model").map(str::to_string);
+        meta.serial = guano.get("Serial").map(str::to_string);
+        meta.firmware = guano.get("Firmware Version").map(str::to_string);
+        meta.temperature_int_c = guano.float("Temperature Int");
+        meta.temperature_ext_c = guano.float("Temperature Ext");
+        meta.filter_hp_khz = guano.float("Filter HP");
+        meta.filter_lp_khz = guano.float("Filter LP");
+        meta.time_expansion = guano.float("TE");
+        meta.notes = guano.get("Note").map(str::to_string);
+
+        const MAPPED: &[&str] = &[
+            "Timestamp",
+            "Loc Position",
+            "Loc Elevation",
+            "Make",
+            "Model",
+            "Serial",
+            "Firmware Version",
+            "Temperature Int",
+            "Temperature Ext",
+            "Filter HP",
+            "Filter LP",
+            "TE",
+            "Note",
+        ];
+        for field in guano.fields() {
+            if field.namespace.is_some() || !MAPPED.contains(&field.key.as_str()) {
+                meta.push_field(field.full_key(), &field.value);
+            }
+        }
+
+        if let Some(settings) = guano.get("WA|Song Meter|Audio settings") {
+            meta.gain = json_value(settings, "gain").map(|g| format!("{} dB", g));
+            let trigger: Vec<String> = settings
+                .split(',')
+                .filter_map(|pair| pair.trim_start_matches(['[', '{', ' ']).strip_prefix("\"trig "))
+                .filter_map(|pair| pair.split_once("\":"))
+                .map(|(key, value)| format!("{} {}", key, value.trim_end_matches(['}', ']'])))
+                .collect();
+            if !trigger.is_empty() {
+                meta.trigger = Some(trigger.join(", "));
+            }
+        }
+        meta
+    }
+
+    /// 由 `wamd` 塊數據轉換
+    ///
+    /// 數據由子塊組成：u16 ID、u32 大小 (小端) 與內容；
+    /// 版本與時間擴展為 u16，其他為 UTF-8 文本。未知 ID 以十六進制鍵保存。
+    pub fn from_wamd(data: &[u8]) -> RecordingMetadata {
+        let mut meta = RecordingMetadata::with_source("wamd");
+        meta.make = Some("Wildlife Acoustics".to_string());
+        let mut offset = 0;
+        while offset + 6 <= data.len() {
+            let id = u16::from_le_bytes([data[offset], data[offset + 1]]);
+            let size = u32::from_le_bytes(data[offset + 2..offset + 6].try_into().unwrap()) as usize;
+            let end = (offset + 6 + size).min(data.len());
+            let value = &data[offset + 6..end];
+            offset = end;
+
+            let name = WAMD_FIELDS.iter().find(|&&(field_id, _)| field_id == id).map(|&(_, name)| name);
+            if matches!(id, 0x00 | 0x0f) && value.len() >= 2 {
+                let number = u16::from_le_bytes([value[0], value[1]]);
+                if id == 0x0f {
+                    meta.time_expansion = Some(number as f64);
+                }
+                meta.push_field(format!("{}|{}", WA_NAMESPACE, name.unwrap()), &number.to_string());
+                continue;
+            }
+
+            let text = String::from_utf8_lossy(value);
+            let text = text.trim_end_matches('\0').trim();
+            match id {
+                0x01 => meta.model = Some(text.to_string()),
+                0x02 => meta.serial = Some(text.to_string()),
+                0x03 => meta.firmware = Some(text.to_string()),
+                0x05 => meta.timestamp = Timestamp::parse(text),
+                0x06 => {
+                    if let Some((lat, lon, elevation)) = parse_wamd_gps(text) {
+                        meta.latitude = Some(lat);
+                        meta.longitude = Some(lon);
+                        meta.elevation_m = elevation;
+                    }
+                }
+                0x0a => meta.notes = Some(text.to_string()),
+                _ => {}
+            }
+            let key = match name {
+                Some(name) => format!("{}|{}", WA_NAMESPACE, name),
+                None => format!("{}|wamd 0x{:04x}", WA_NAMESPACE, id),
+            };
+            meta.push_field(key, text);
+        }
+        meta
+    }
+
+    /// 由 AudioMoth 的 ICMT 注釋 (與可選的 IART) 轉換；不是 AudioMoth 注釋時返回 None
+    ///
+    /// 注釋形如 `Recorded at 19:28:00 24/05/2020 (UTC+1) by AudioMoth 24F319055FDF2F5B at medium gain
+    /// while battery was 4.2V and temperature was 17.1C. Amplitude threshold was 10%.`，
+    /// 舊固件寫作 `at gain setting 2 while battery state was 4.6V`。
+    pub fn from_audiomoth(comment: &str, artist: Option<&str>) -> Option<RecordingMetadata> {
+        let comment = comment.trim();
+        let rest = comment.strip_prefix("Recorded at ")?;
+        let mut meta = RecordingMetadata::with_source("AudioMoth");
+        meta.make = Some("Open Acoustic Devices".to_string());
+        meta.model = Some("AudioMoth".to_string());
+        let ns = |key: &str| format!("{}|{}", AUDIOMOTH_NAMESPACE, key);
+
+        // 第一句：時間、設備、增益、電池、溫度
+        let (first, others) = match rest.find(". ") {
+            Some(end) => (&rest[..end], &rest[end + 2..]),
+            None => (rest.trim_end_matches('.'), ""),
+        };
+        let time_end = first.find(')').map_or(first.len(), |i| i + 1);
+        meta.timestamp = parse_audiomoth_time(&first[..time_end]);
+        let details = &first[time_end..];
+
+        let serial_from = |text: &str| {
+            let start = text.find("AudioMoth ")? + "AudioMoth ".len();
+            text[start..].split_whitespace().next().map(str::to_string)
+        };
+        meta.serial = serial_from(details).or_else(|| artist.and_then(serial_from));
+        if let Some(start) = details.find("with deployment ID ") {
+            let id = details[start + "with deployment ID ".len()..].split_whitespace().next().unwrap_or("");
+            meta.push_field(ns("Deployment ID"), id);
+        }
+        if let Some(start) = details.find("at gain setting ") {
+            meta.gain = details[start + "at gain setting ".len()..].split_whitespace().next().map(str::to_string);
+        } else if let Some(end) = details.find(" gain") {
+            meta.gain = details[..end].rsplit(" at ").next().map(str::to_string);
+        }
+        meta.battery_v = number_after(details, "battery", 'V');
+        meta.temperature_int_c = number_after(details, "temperature was", 'C');
+        if let Some(battery) = details.find("battery").map(|i| &details[i..]) {
+            let battery = battery.split(" and ").next().unwrap_or(battery);
+            meta.push_field(ns("Battery"), battery.rsplit(" was ").next().unwrap_or(battery).trim());
+        }
+        if let Some(gain) = meta.gain.clone() {
+            meta.push_field(ns("Gain"), &gain);
+        }
+
+        // 其他句子：觸發、濾波器與錄音狀態
+        let mut triggers = Vec::new();
+        let mut notes = Vec::new();
+        for sentence in others.split(". ").map(|s| s.trim().trim_end_matches('.')).filter(|s| !s.is_empty()) {
+            let lower = sentence.to_ascii_lowercase();
+            if lower.contains("threshold") || lower.contains("trigger") {
+                triggers.push(sentence.to_string());
+            } else if lower.contains("filter") {
+                let values = khz_values(sentence);
+                if lower.starts_with("band-pass") && values.len() >= 2 {
+                    meta.filter_hp_khz = Some(values[0]);
+                    meta.filter_lp_khz = Some(values[1]);
+                } else if lower.starts_with("low-pass") {
+                    meta.filter_lp_khz = values.first().copied();
+                } else if lower.starts_with("high-pass") {
+                    meta.filter_hp_khz = values.first().copied();
+                }
+                meta.push_field(ns("Filter"), sentence);
+            } else {
+                notes.push(sentence.to_string());
+            }
+        }
+        if !triggers.is_empty() {
+            meta.trigger = Some(triggers.join(". "));
+            meta.push_field(ns("Trigger"), &triggers.join(". "));
+        }
+        if !notes.is_empty() {
+            meta.notes = Some(notes.join(". "));
+        }
+        meta.push_field(ns("Comment"), comment);
+        Some(meta)
+    }
+
+    /// 用 `other` 補全缺失的字段 (已有的值優先)
+    pub fn merge(&mut self, other: RecordingMetadata) {
+        fn fill<T>(target: &mut Option<T>, value: Option<T>) {
+            if target.is_none() {
+                *target = value;
+            }
+        }
+        self.sources.extend(other.sources);
+        fill(&mut self.timestamp, other.timestamp);
+        if self.latitude.is_none() {
+            self.latitude = other.latitude;
+            self.longitude = other.longitude;
+        }
+        fill(&mut self.elevation_m, other.elevation_m);
+        fill(&mut self.make, other.make);
+        fill(&mut self.model, other.model);
+        fill(&mut self.serial, other.serial);
+        fill(&mut self.firmware, other.firmware);
+        fill(&mut self.gain, other.gain);
+        fill(&mut self.temperature_int_c, other.temperature_int_c);
+        fill(&mut self.temperature_ext_c, other.temperature_ext_c);
+        fill(&mut self.battery_v, other.battery_v);
+        fill(&mut self.trigger, other.trigger);
+        fill(&mut self.filter_hp_khz, other.filter_hp_khz);
+        fill(&mut self.filter_lp_khz, other.filter_lp_khz);
+        fill(&mut self.time_expansion, other.time_expansion);
+        fill(&mut self.notes, other.notes);
+        for (key, value) in other.fields {
+            if !self.fields.iter().any(|(k, _)| *k == key) {
+                self.fields.push((key, value));
+            }
+        }
+    }
+
+    /// 讀取 WAV 或 FLAC 文件中的所有元數據
+    ///
+    /// 優先級：GUANO > `wamd` > AudioMoth 注釋；高優先級來源缺失的字段由低優先級來源補全。
+    /// 不是 WAV / FLAC 文件時返回 None。
+    pub fn from_file(bytes: &[u8]) -> Option<RecordingMetadata> {
+        let mut sources = RecordingMetadata::sources_from_file(bytes)?.into_iter();
+        let mut meta = sources.next().unwrap_or_default();
+        for other in sources {
+            meta.merge(other);
+        }
+        Some(meta)
+    }
+
+    /// 按優先級分別讀取 WAV 或 FLAC 文件中的各個元數據來源 (未合併)；不是 WAV / FLAC 文件時返回 None
+    pub(crate) fn sources_from_file(bytes: &[u8]) -> Option<Vec<RecordingMetadata>> {
+        let mut sources = Vec::new();
+        if let Ok(info) = WavInfo::parse(bytes) {
+            let chunk = |id: &[u8; 4]| info.find_chunk(id).map(|c| c.data(bytes));
+            if let Some(guano) = chunk(GUANO_CHUNK_ID).and_then(|data| GuanoMetadata::parse(data).ok()) {
+                sources.push(RecordingMetadata::from_guano(&guano));
+            }
+            if let Some(data) = chunk(WAMD_CHUNK_ID) {
+                sources.push(RecordingMetadata::from_wamd(data));
+            }
+            let list = info
+                .chunks()
+                .iter()
+                .filter(|c| &c.id == b"LIST")
+                .flat_map(|c| parse_list_info(c.data(bytes)))
+                .collect::<Vec<_>>();
+            let entry = |id: &[u8; 4]| list.iter().find(|(entry_id, _)| entry_id == id).map(|(_, text)| text.as_str());
+            if let Some(comment) = entry(b"ICMT") {
+                sources.extend(RecordingMetadata::from_audiomoth(comment, entry(b"IART")));
+            }
+        } else if let Ok(info) = FlacInfo::parse(bytes) {
+            if let Some(guano) = info.guano() {
+                sources.push(RecordingMetadata::from_guano(&guano));
+            }
+            if let Some(data) = info.foreign_chunk(WAMD_CHUNK_ID) {
+                sources.push(RecordingMetadata::from_wamd(data));
+            }
+            if let Some(comment) = info.comment("COMMENT") {
+                sources.extend(RecordingMetadata::from_audiomoth(comment, info.comment("ARTIST")));
+            }
+        } else {
+            return None;
+        }
+        Some(sources)
+    }
+
+    /// 時間戳
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        self.timestamp
+    }
+
+    /// 其他廠商字段 (帶命名空間的 GUANO 鍵, 值)
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.fields
+    }
+
+    /// 轉換為 GUANO 元數據：映射字段寫為規範鍵，其他字段按原鍵寫入
+    pub fn to_guano(&self) -> GuanoMetadata {
+        let mut guano = GuanoMetadata::default();
+        if let Some(timestamp) = &self.timestamp {
+            guano.set_timestamp(timestamp);
+        }
+        if let (Some(lat), Some(lon)) = (self.latitude, self.longitude) {
+            guano.set_position(lat, lon);
+        }
+        let texts = [
+            ("Make", &self.make),
+            ("Model", &self.model),
+            ("Serial", &self.serial),
+            ("Firmware Version", &self.firmware),
+            ("Note", &self.notes),
+        ];
+        for (key, value) in texts {
+            if let Some(value) = value {
+                guano.set(key, value);
+            }
+        }
+        let numbers = [
+            ("Loc Elevation", self.elevation_m),
+            ("Temperature Int", self.temperature_int_c),
+            ("Temperature Ext", self.temperature_ext_c),
+            ("Filter HP", self.filter_hp_khz),
+            ("Filter LP", self.filter_lp_khz),
+            ("TE", self.time_expansion),
+        ];
+        for (key, value) in numbers {
+            if let Some(value) = value {
+                guano.set(key, &value.to_string());
+            }
+        }
+        for (key, value) in &self.fields {
+            if guano.get(key).is_none() {
+                guano.set(key, value);
+            }
+        }
+        guano
+    }
+}
+
+#[wasm_bindgen]
+impl RecordingMetadata {
+    /// 元數據來源 ("GUANO"、"wamd"、"AudioMoth")，按優先級排列
+    #[wasm_bindgen]
+    pub fn get_sources(&self) -> Vec<String> {
+        self.sources.clone()
+    }
+
+    /// 錄音開始時間 (ISO 8601)
+    #[wasm_bindgen]
+    pub fn get_timestamp_iso(&self) -> Option<String> {
+        self.timestamp.map(|t| t.to_iso8601())
+    }
+
+    /// 錄音開始時間 (Unix 秒)；未標明時區時按 UTC 計算
+    #[wasm_bindgen]
+    pub fn get_timestamp_unix(&self) -> Option<f64> {
+        self.timestamp.map(|t| t.to_unix_seconds())
+    }
+
+    /// 時間戳的 UTC 偏移 (分鐘)
+    #[wasm_bindgen]
+    pub fn get_utc_offset_minutes(&self) -> Option<i16> {
+        self.timestamp?.utc_offset_minutes
+    }
+
+    /// 緯度 (十進制度)
+    #[wasm_bindgen]
+    pub fn get_latitude(&self) -> Option<f64> {
+        self.latitude
+    }
+
+    /// 經度 (十進制度，西經為負)
+    #[wasm_bindgen]
+    pub fn get_longitude(&self) -> Option<f64> {
+        self.longitude
+    }
+
+    /// 海拔 (米)
+    #[wasm_bindgen]
+    pub fn get_elevation(&self) -> Option<f64> {
+        self.elevation_m
+    }
+
+    /// 設備製造商
+    #[wasm_bindgen]
+    pub fn get_make(&self) -> Option<String> {
+        self.make.clone()
+    }
+
+    /// 設備型號
+    #[wasm_bindgen]
+    pub fn get_model(&self) -> Option<String> {
+        self.model.clone()
+    }
+
+    /// 設備序列號
+    #[wasm_bindgen]
+    pub fn get_serial(&self) -> Option<String> {
+        self.serial.clone()
+    }
+
+    /// 固件版本
+    #[wasm_bindgen]
+    pub fn get_firmware(&self) -> Option<String> {
+        self.firmware.clone()
+    }
+
+    /// 增益設置 (如 "12 dB"、"medium")
+    #[wasm_bindgen]
+    pub fn get_gain(&self) -> Option<String> {
+        self.gain.clone()
+    }
+
+    /// 設備內部溫度 (°C)
+    #[wasm_bindgen]
+    pub fn get_temperature_int(&self) -> Option<f64> {
+        self.temperature_int_c
+    }
+
+    /// 外部溫度 (°C)
+    #[wasm_bindgen]
+    pub fn get_temperature_ext(&self) -> Option<f64> {
+        self.temperature_ext_c
+    }
+
+    /// 電池電壓 (V)
+    #[wasm_bindgen]
+    pub fn get_battery_voltage(&self) -> Option<f64> {
+        self.battery_v
+    }
+
+    /// 觸發設置描述
+    #[wasm_bindgen]
+    pub fn get_trigger(&self) -> Option<String> {
+        self.trigger.clone()
+    }
+
+    /// 高通濾波頻率 (kHz)
+    #[wasm_bindgen]
+    pub fn get_filter_hp_khz(&self) -> Option<f64> {
+        self.filter_hp_khz
+    }
+
+    /// 低通濾波頻率 (kHz)
+    #[wasm_bindgen]
+    pub fn get_filter_lp_khz(&self) -> Option<f64> {
+        self.filter_lp_khz
+    }
+
+    /// 時間擴展倍數
+    #[wasm_bindgen]
+    pub fn get_time_expansion(&self) -> Option<f64> {
+        self.time_expansion
+    }
+
+    /// 備註
+    #[wasm_bindgen]
+    pub fn get_notes(&self) -> Option<String> {
+        self.notes.clone()
+    }
+
+    /// 其他廠商字段的鍵 (帶命名空間)
+    #[wasm_bindgen]
+    pub fn get_field_keys(&self) -> Vec<String> {
+        self.fields.iter().map(|(k, _)| k.clone()).collect()
+    }
+
+    /// 其他廠商字段的值 (與 get_field_keys 一一對應)
+    #[wasm_bindgen]
+    pub fn get_field_values(&self) -> Vec<String> {
+        self.fields.iter().map(|(_, v)| v.clone()).collect()
+    }
+
+    /// 轉換為 GUANO 元數據 (可用 write_guano 寫回 WAV)
+    #[wasm_bindgen(js_name = to_guano)]
+    pub fn to_guano_js(&self) -> GuanoMetadata {
+        self.to_guano()
+    }
+}
+
+/// 讀取 WAV / FLAC 文件中的 GUANO、wamd 與 AudioMoth 元數據
+///
+/// # Arguments
+/// * `bytes` - 文件內容 (Uint8Array)；元數據塊可能位於音頻之後，需傳入完整文件
+///
+/// # Returns
+/// 合併後的 RecordingMetadata；不是 WAV / FLAC 文件時返回 undefined
+#[wasm_bindgen]
+pub fn read_recording_metadata(bytes: &[u8]) -> Option<RecordingMetadata> {
+    RecordingMetadata::from_file(bytes)
+}
+
+/// 解析 `wamd` 塊數據
+#[wasm_bindgen]
+pub fn parse_wamd(data: &[u8]) -> RecordingMetadata {
+    RecordingMetadata::from_wamd(data)
+}
+
+/// 解析 AudioMoth 注釋
+///
+/// # Arguments
+/// * `comment` - ICMT 文本 (以 "Recorded at" 開頭)
+/// * `artist` - IART 文本 (如 "AudioMoth 24F319055FDF2F5B")，可為 undefined
+///
+/// # Returns
+/// RecordingMetadata；不是 AudioMoth 注釋時返回 undefined
+#[wasm_bindgen]
+pub fn parse_audiomoth_comment(comment: &str, artist: Option<String>) -> Option<RecordingMetadata> {
+    RecordingMetadata::from_audiomoth(comment, artist.as_deref())
+}
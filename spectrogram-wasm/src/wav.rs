@@ -0,0 +1,725 @@
+// ============================================================
+// RIFF/WAVE 解碼
+// 支持 8/16/24/32 位 PCM、32/64 位浮點、WAVE_FORMAT_EXTENSIBLE、
+// 奇數大小塊的填充字節，以及超過 4 GB 的 RF64 / BW64 文件。
+// 返回格式信息、所有塊的位置與去交錯的 f32 通道數據。
+// ============================================================
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+/// 整數 PCM 格式標記
+pub const WAVE_FORMAT_PCM: u16 = 0x0001;
+/// IEEE 浮點格式標記
+pub const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+/// 擴展格式標記 (實際格式在子格式 GUID 中)
+pub const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// RIFF / RF64 中表示「大小見 ds64 塊」的佔位值
+const SIZE_PLACEHOLDER: u32 = 0xFFFF_FFFF;
+/// ds64 塊固定部分的大小 (不含大小表)
+const DS64_FIXED_SIZE: usize = 28;
+
+/// WAV 解析錯誤
+#[derive(Clone, Debug, PartialEq)]
+pub enum WavError {
+    /// 文件不以 RIFF / RF64 / BW64 開頭
+    NotRiff,
+    /// RIFF 類型不是 WAVE
+    NotWave,
+    /// RF64 文件缺少緊隨文件頭的 ds64 塊
+    MissingDs64,
+    /// 缺少 fmt 塊
+    MissingFormat,
+    /// 缺少 data 塊
+    MissingData,
+    /// 塊頭或 fmt 塊被截斷
+    Truncated { chunk: String },
+    /// 不支持的編碼格式 (例如 ADPCM)
+    UnsupportedFormat { format_tag: u16 },
+    /// 不支持的位深
+    UnsupportedBitDepth { bits_per_sample: u16 },
+    /// 通道數、採樣率或塊對齊無效
+    InvalidFormat { reason: String },
+    /// 寫入後的 RIFF 文件超過 4 GB
+    FileTooLarge,
+}
+
+impl fmt::Display for WavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WavError::NotRiff => write!(f, "not a RIFF, RF64 or BW64 file"),
+            WavError::NotWave => write!(f, "RIFF form type is not WAVE"),
+            WavError::MissingDs64 => write!(f, "RF64 file has no ds64 chunk"),
+            WavError::MissingFormat => write!(f, "no fmt chunk found"),
+            WavError::MissingData => write!(f, "no data chunk found"),
+            WavError::Truncated { chunk } => write!(f, "'{}' chunk is truncated", chunk),
+            WavError::UnsupportedFormat { format_tag } => {
+                write!(f, "unsupported WAV format tag 0x{:04X}", format_tag)
+            }
+            WavError::UnsupportedBitDepth { bits_per_sample } => {
+                write!(f, "unsupported bit depth {}", bits_per_sample)
+            }
+            WavError::InvalidFormat { reason } => write!(f, "invalid fmt chunk: {}", reason),
+            WavError::FileTooLarge => write!(f, "RIFF file would exceed 4 GB"),
+        }
+    }
+}
+
+impl std::error::Error for WavError {}
+
+/// 樣本編碼
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleFormat {
+    /// 整數 PCM (8 位為無符號，其餘為有符號)
+    Int,
+    /// IEEE 浮點
+    Float,
+}
+
+/// fmt 塊描述的格式信息
+#[derive(Clone, Debug, PartialEq)]
+pub struct WavFormat {
+    /// fmt 塊中的格式標記 (擴展格式為 0xFFFE)
+    pub format_tag: u16,
+    /// 解析後的樣本編碼
+    pub sample_format: SampleFormat,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub byte_rate: u32,
+    /// 每幀字節數 (所有通道)
+    pub block_align: u16,
+    /// 每個樣本的容器位數
+    pub bits_per_sample: u16,
+    /// 有效位數 (僅擴展格式，否則等於 bits_per_sample)
+    pub valid_bits_per_sample: u16,
+    /// 揚聲器位置掩碼 (僅擴展格式)
+    pub channel_mask: Option<u32>,
+}
+
+impl WavFormat {
+    /// 每個樣本的字節數
+    pub fn bytes_per_sample(&self) -> usize {
+        self.block_align as usize / self.channels as usize
+    }
+}
+
+/// 文件中的一個塊
+#[derive(Clone, Debug, PartialEq)]
+pub struct WavChunk {
+    /// 四字符塊 ID
+    pub id: [u8; 4],
+    /// 塊數據在文件中的起始偏移 (不含 8 字節塊頭)
+    pub offset: u64,
+    /// 塊數據大小 (RF64 中已替換為 ds64 給出的 64 位大小，不含填充字節)
+    pub size: u64,
+}
+
+impl WavChunk {
+    /// 塊 ID 字符串 (非 ASCII 字節以替換字符表示)
+    pub fn id_str(&self) -> String {
+        String::from_utf8_lossy(&self.id).into_owned()
+    }
+
+    /// 塊數據 (文件被截斷時只返回可用部分)
+    pub fn data<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        // 先在 u64 中截斷再轉換：wasm32 上 usize 只有 32 位
+        let start = self.offset.min(bytes.len() as u64) as usize;
+        let end = self.offset.saturating_add(self.size).min(bytes.len() as u64) as usize;
+        &bytes[start..end]
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+/// 解析 fmt 塊
+fn parse_format(data: &[u8]) -> Result<WavFormat, WavError> {
+    if data.len() < 16 {
+        return Err(WavError::Truncated { chunk: "fmt ".to_string() });
+    }
+
+    let format_tag = read_u16(data, 0);
+    let channels = read_u16(data, 2);
+    let sample_rate = read_u32(data, 4);
+    let byte_rate = read_u32(data, 8);
+    let block_align = read_u16(data, 12);
+    let bits_per_sample = read_u16(data, 14);
+
+    let (base_tag, valid_bits_per_sample, channel_mask) = if format_tag == WAVE_FORMAT_EXTENSIBLE {
+        // cbSize(2) validBits(2) channelMask(4) subFormat GUID(16)，GUID 的前兩個字節為實際格式
+        if data.len() < 40 {
+            return Err(WavError::Truncated { chunk: "fmt ".to_string() });
+        }
+        let valid = read_u16(data, 18);
+        let valid = if valid == 0 { bits_per_sample } else { valid };
+        (read_u16(data, 24), valid, Some(read_u32(data, 20)))
+    } else {
+        (format_tag, bits_per_sample, None)
+    };
+
+    let sample_format = match base_tag {
+        WAVE_FORMAT_PCM => SampleFormat::Int,
+        WAVE_FORMAT_IEEE_FLOAT => SampleFormat::Float,
+        _ => return Err(WavError::UnsupportedFormat { format_tag: base_tag }),
+    };
+
+    if channels == 0 {
+        return Err(WavError::InvalidFormat { reason: "channel count is zero".to_string() });
+    }
+    if sample_rate == 0 {
+        return Err(WavError::InvalidFormat { reason: "sample rate is zero".to_string() });
+    }
+    if block_align == 0 || !block_align.is_multiple_of(channels) {
+        return Err(WavError::InvalidFormat {
+            reason: format!("block align {} is not a multiple of {} channels", block_align, channels),
+        });
+    }
+
+    let format = WavFormat {
+        format_tag,
+        sample_format,
+        channels,
+        sample_rate,
+        byte_rate,
+        block_align,
+        bits_per_sample,
+        valid_bits_per_sample,
+        channel_mask,
+    };
+
+    // 以塊對齊推算的容器大小為準 (部分錄音機的 bits_per_sample 與容器不一致)
+    let supported = match sample_format {
+        SampleFormat::Int => matches!(format.bytes_per_sample(), 1..=4),
+        SampleFormat::Float => matches!(format.bytes_per_sample(), 4 | 8),
+    };
+    if !supported {
+        return Err(WavError::UnsupportedBitDepth { bits_per_sample });
+    }
+    Ok(format)
+}
+
+/// 解碼單個樣本到 [-1, 1)
+fn decode_sample(bytes: &[u8], sample_format: SampleFormat) -> f32 {
+    match (sample_format, bytes.len()) {
+        (SampleFormat::Int, 1) => (bytes[0] as f32 - 128.0) / 128.0,
+        (SampleFormat::Int, 2) => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+        (SampleFormat::Int, 3) => {
+            // 放到 i32 的高 24 位以完成符號擴展
+            let value = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+            value as f32 / 8_388_608.0
+        }
+        (SampleFormat::Int, 4) => i32::from_le_bytes(bytes.try_into().unwrap()) as f32 / 2_147_483_648.0,
+        (SampleFormat::Float, 4) => f32::from_le_bytes(bytes.try_into().unwrap()),
+        (SampleFormat::Float, 8) => f64::from_le_bytes(bytes.try_into().unwrap()) as f32,
+        _ => 0.0,
+    }
+}
+
+/// 將一個原始樣本原位乘以 `gain` (在原格式中計算，不經過 f32 轉換)
+pub(crate) fn scale_sample(bytes: &mut [u8], sample_format: SampleFormat, gain: f64) {
+    match (sample_format, bytes.len()) {
+        (SampleFormat::Int, 1) => {
+            let value = ((bytes[0] as f64 - 128.0) * gain).round() + 128.0;
+            bytes[0] = value.clamp(0.0, 255.0) as u8;
+        }
+        (SampleFormat::Int, 2) => {
+            let value = (i16::from_le_bytes([bytes[0], bytes[1]]) as f64 * gain).round() as i16;
+            bytes.copy_from_slice(&value.to_le_bytes());
+        }
+        (SampleFormat::Int, 3) => {
+            let value = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+            let scaled = ((value as f64 * gain).round() as i32) << 8;
+            bytes.copy_from_slice(&scaled.to_le_bytes()[1..]);
+        }
+        (SampleFormat::Int, 4) => {
+            let value = (i32::from_le_bytes(bytes.try_into().unwrap()) as f64 * gain).round() as i32;
+            bytes.copy_from_slice(&value.to_le_bytes());
+        }
+        (SampleFormat::Float, 4) => {
+            let value = (f32::from_le_bytes(bytes.try_into().unwrap()) as f64 * gain) as f32;
+            bytes.copy_from_slice(&value.to_le_bytes());
+        }
+        (SampleFormat::Float, 8) => {
+            let value = f64::from_le_bytes(bytes.try_into().unwrap()) * gain;
+            bytes.copy_from_slice(&value.to_le_bytes());
+        }
+        _ => {}
+    }
+}
+
+/// WavInfo: WAV 文件的格式與塊結構 (不解碼樣本)
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct WavInfo {
+    format: WavFormat,
+    chunks: Vec<WavChunk>,
+    data_index: usize,
+    rf64: bool,
+    num_frames: u64,
+    truncated: bool,
+}
+
+impl WavInfo {
+    /// 解析文件頭與塊列表
+    ///
+    /// 只需要包含 fmt 與 data 塊頭的前綴即可：data 塊被截斷時按塊頭聲明的大小計算時長；
+    /// data 大小為 0 或佔位值 (錄音中斷的文件) 時按實際可用的字節計算。
+    pub fn parse(bytes: &[u8]) -> Result<WavInfo, WavError> {
+        if bytes.len() < 12 {
+            return Err(WavError::NotRiff);
+        }
+        let rf64 = match &bytes[0..4] {
+            b"RIFF" => false,
+            b"RF64" | b"BW64" => true,
+            _ => return Err(WavError::NotRiff),
+        };
+        if &bytes[8..12] != b"WAVE" {
+            return Err(WavError::NotWave);
+        }
+
+        // RF64: ds64 塊給出 RIFF、data 的 64 位大小，以及其他大塊的大小表
+        let mut data_size64 = None;
+        let mut size_table: Vec<([u8; 4], u64)> = Vec::new();
+        if rf64 {
+            if bytes.len() < 20 || &bytes[12..16] != b"ds64" {
+                return Err(WavError::MissingDs64);
+            }
+            // 固定部分：RIFF 大小、data 大小、樣本數 (各 u64) 與大小表長度 (u32)
+            let size = read_u32(bytes, 16) as usize;
+            if size < DS64_FIXED_SIZE || bytes.len() < 20 + size {
+                return Err(WavError::Truncated { chunk: "ds64".to_string() });
+            }
+            data_size64 = Some(read_u64(bytes, 28));
+            let table_len = read_u32(bytes, 44) as usize;
+            for i in 0..table_len {
+                let entry = 48 + i * 12;
+                if entry + 12 > 20 + size {
+                    break;
+                }
+                let id: [u8; 4] = bytes[entry..entry + 4].try_into().unwrap();
+                size_table.push((id, read_u64(bytes, entry + 4)));
+            }
+        }
+
+        let mut chunks = Vec::new();
+        let mut offset = 12usize;
+        while offset + 8 <= bytes.len() {
+            let id: [u8; 4] = bytes[offset..offset + 4].try_into().unwrap();
+            let size32 = read_u32(bytes, offset + 4);
+            let size = if rf64 && size32 == SIZE_PLACEHOLDER {
+                if &id == b"data" {
+                    data_size64.unwrap_or(size32 as u64)
+                } else {
+                    size_table
+                        .iter()
+                        .find(|(table_id, _)| *table_id == id)
+                        .map(|&(_, size)| size)
+                        .unwrap_or(size32 as u64)
+                }
+            } else if &id == b"data" && size32 == 0 {
+                // 錄音中斷時 data 大小可能未回寫，視為延伸到文件結尾
+                (bytes.len() - offset - 8) as u64
+            } else {
+                size32 as u64
+            };
+
+            chunks.push(WavChunk { id, offset: offset as u64 + 8, size });
+            // 奇數大小的塊後有一個填充字節
+            let next = (offset as u64 + 8).saturating_add(size).saturating_add(size & 1);
+            if next > bytes.len() as u64 {
+                break;
+            }
+            offset = next as usize;
+        }
+
+        let format_chunk = chunks.iter().find(|c| &c.id == b"fmt ").ok_or(WavError::MissingFormat)?;
+        if format_chunk.offset + format_chunk.size > bytes.len() as u64 {
+            return Err(WavError::Truncated { chunk: "fmt ".to_string() });
+        }
+        let format = parse_format(format_chunk.data(bytes))?;
+
+        let data_index = chunks.iter().position(|c| &c.id == b"data").ok_or(WavError::MissingData)?;
+        let data = &chunks[data_index];
+        let available = (bytes.len() as u64).saturating_sub(data.offset);
+        let truncated = data.size > available;
+        let frame_bytes = if !rf64 && data.size == SIZE_PLACEHOLDER as u64 { available } else { data.size };
+
+        Ok(WavInfo {
+            num_frames: frame_bytes / format.block_align as u64,
+            format,
+            chunks,
+            data_index,
+            rf64,
+            truncated,
+        })
+    }
+
+    /// 格式信息
+    pub fn format(&self) -> &WavFormat {
+        &self.format
+    }
+
+    /// 按文件順序排列的所有塊
+    pub fn chunks(&self) -> &[WavChunk] {
+        &self.chunks
+    }
+
+    /// data 塊
+    pub fn data_chunk(&self) -> &WavChunk {
+        &self.chunks[self.data_index]
+    }
+
+    /// 第一個 ID 為 `id` 的塊
+    pub fn find_chunk(&self, id: &[u8; 4]) -> Option<&WavChunk> {
+        self.chunks.iter().find(|c| &c.id == id)
+    }
+
+    /// 每個通道的樣本數 (幀數)
+    pub fn num_frames(&self) -> u64 {
+        self.num_frames
+    }
+
+    /// 解碼並去交錯所有通道 (只解碼文件中實際存在的完整幀)
+    pub fn decode_channels(&self, bytes: &[u8]) -> Vec<Vec<f32>> {
+        let data = self.data_chunk().data(bytes);
+        let frame_size = self.format.block_align as usize;
+        let sample_size = self.format.bytes_per_sample();
+        let num_frames = ((data.len() / frame_size) as u64).min(self.num_frames) as usize;
+        let num_channels = self.format.channels as usize;
+
+        let mut channels = vec![Vec::with_capacity(num_frames); num_channels];
+        for frame in data.chunks_exact(frame_size).take(num_frames) {
+            for (channel, sample) in channels.iter_mut().zip(frame.chunks_exact(sample_size)) {
+                channel.push(decode_sample(sample, self.format.sample_format));
+            }
+        }
+        channels
+    }
+}
+
+#[wasm_bindgen]
+impl WavInfo {
+    /// 採樣率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_sample_rate(&self) -> u32 {
+        self.format.sample_rate
+    }
+
+    /// 通道數
+    #[wasm_bindgen]
+    pub fn get_num_channels(&self) -> u32 {
+        self.format.channels as u32
+    }
+
+    /// 每個樣本的容器位數
+    #[wasm_bindgen]
+    pub fn get_bits_per_sample(&self) -> u32 {
+        self.format.bits_per_sample as u32
+    }
+
+    /// 有效位數 (擴展格式的 wValidBitsPerSample)
+    #[wasm_bindgen]
+    pub fn get_valid_bits_per_sample(&self) -> u32 {
+        self.format.valid_bits_per_sample as u32
+    }
+
+    /// fmt 塊中的格式標記 (1 = PCM, 3 = 浮點, 0xFFFE = 擴展格式)
+    #[wasm_bindgen]
+    pub fn get_format_tag(&self) -> u32 {
+        self.format.format_tag as u32
+    }
+
+    /// 樣本是否為浮點編碼
+    #[wasm_bindgen]
+    pub fn is_float(&self) -> bool {
+        self.format.sample_format == SampleFormat::Float
+    }
+
+    /// 是否為 RF64 / BW64 文件
+    #[wasm_bindgen]
+    pub fn is_rf64(&self) -> bool {
+        self.rf64
+    }
+
+    /// data 塊是否被截斷 (文件短於塊頭聲明的大小)
+    #[wasm_bindgen]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// 揚聲器位置掩碼；非擴展格式時為 0
+    #[wasm_bindgen]
+    pub fn get_channel_mask(&self) -> u32 {
+        self.format.channel_mask.unwrap_or(0)
+    }
+
+    /// 每個通道的樣本數
+    #[wasm_bindgen]
+    pub fn get_num_frames(&self) -> f64 {
+        self.num_frames as f64
+    }
+
+    /// 時長 (秒)
+    #[wasm_bindgen]
+    pub fn get_duration(&self) -> f64 {
+        self.num_frames as f64 / self.format.sample_rate as f64
+    }
+
+    /// 所有塊的 ID (按文件順序)
+    #[wasm_bindgen]
+    pub fn get_chunk_ids(&self) -> Vec<String> {
+        self.chunks.iter().map(WavChunk::id_str).collect()
+    }
+
+    /// 所有塊數據的起始偏移 (字節，不含塊頭)
+    #[wasm_bindgen]
+    pub fn get_chunk_offsets(&self) -> Vec<f64> {
+        self.chunks.iter().map(|c| c.offset as f64).collect()
+    }
+
+    /// 所有塊數據的大小 (字節，不含填充字節)
+    #[wasm_bindgen]
+    pub fn get_chunk_sizes(&self) -> Vec<f64> {
+        self.chunks.iter().map(|c| c.size as f64).collect()
+    }
+
+    /// data 塊數據的起始偏移 (字節)
+    #[wasm_bindgen]
+    pub fn get_data_offset(&self) -> f64 {
+        self.data_chunk().offset as f64
+    }
+
+    /// data 塊聲明的大小 (字節)
+    #[wasm_bindgen]
+    pub fn get_data_size(&self) -> f64 {
+        self.data_chunk().size as f64
+    }
+}
+
+/// 寫入或替換一個塊，返回新的文件內容
+///
+/// 第一個 ID 為 `id` 的塊被替換為 `data`，其餘同 ID 的塊被刪除；
+/// 不存在時追加到文件末尾。其他塊 (包括 data) 按原樣複製，
+/// 並更新 RIFF 大小 (RF64 文件更新 ds64 中的 64 位大小)。
+pub fn replace_chunk(bytes: &[u8], id: &[u8; 4], data: &[u8]) -> Result<Vec<u8>, WavError> {
+    let info = WavInfo::parse(bytes)?;
+    if info.truncated {
+        return Err(WavError::Truncated { chunk: "data".to_string() });
+    }
+    if data.len() as u64 >= SIZE_PLACEHOLDER as u64 {
+        return Err(WavError::FileTooLarge);
+    }
+
+    let write_new = |output: &mut Vec<u8>| {
+        output.extend_from_slice(id);
+        output.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        output.extend_from_slice(data);
+        if data.len() % 2 == 1 {
+            output.push(0);
+        }
+    };
+
+    let mut output = Vec::with_capacity(bytes.len() + data.len() + 9);
+    output.extend_from_slice(&bytes[..12]);
+    let mut written = false;
+    for chunk in &info.chunks {
+        if &chunk.id == id {
+            if !written {
+                write_new(&mut output);
+                written = true;
+            }
+            continue;
+        }
+        if info.rf64 {
+            // RF64 的佔位大小與 ds64 大小表保持不變
+            let header = chunk.offset as usize - 8;
+            output.extend_from_slice(&bytes[header..chunk.offset as usize]);
+        } else {
+            // 寫入實際大小 (修復未回寫的 data 大小)
+            output.extend_from_slice(&chunk.id);
+            output.extend_from_slice(&(chunk.size as u32).to_le_bytes());
+        }
+        output.extend_from_slice(chunk.data(bytes));
+        if chunk.size % 2 == 1 {
+            let pad = usize::try_from(chunk.offset + chunk.size).ok().and_then(|i| bytes.get(i));
+            output.push(pad.copied().unwrap_or(0));
+        }
+    }
+    if !written {
+        write_new(&mut output);
+    }
+
+    let riff_size = output.len() as u64 - 8;
+    if info.rf64 {
+        // ds64 緊隨文件頭，其數據的前 8 字節為 RIFF 大小
+        output[20..28].copy_from_slice(&riff_size.to_le_bytes());
+    } else if riff_size >= SIZE_PLACEHOLDER as u64 {
+        return Err(WavError::FileTooLarge);
+    } else {
+        output[4..8].copy_from_slice(&(riff_size as u32).to_le_bytes());
+    }
+    Ok(output)
+}
+
+/// WavAudio: 解碼後的 WAV 文件
+///
+/// 除 data 以外的塊保留原始字節，供元數據解析使用。
+#[wasm_bindgen]
+pub struct WavAudio {
+    info: WavInfo,
+    chunk_data: Vec<Vec<u8>>,
+    channels: Vec<Vec<f32>>,
+}
+
+impl WavAudio {
+    /// 解碼完整的 WAV 文件
+    pub fn decode(bytes: &[u8]) -> Result<WavAudio, WavError> {
+        let info = WavInfo::parse(bytes)?;
+        let channels = info.decode_channels(bytes);
+        let chunk_data = info
+            .chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| if i == info.data_index { Vec::new() } else { chunk.data(bytes).to_vec() })
+            .collect();
+        Ok(WavAudio { info, chunk_data, channels })
+    }
+
+    /// 格式與塊結構
+    pub fn info(&self) -> &WavInfo {
+        &self.info
+    }
+
+    /// 去交錯的通道數據
+    pub fn channels(&self) -> &[Vec<f32>] {
+        &self.channels
+    }
+
+    /// 第一個 ID 為 `id` 的塊的數據 (data 塊返回 None)
+    pub fn chunk_bytes(&self, id: &[u8; 4]) -> Option<&[u8]> {
+        let index = self.info.chunks.iter().position(|c| &c.id == id)?;
+        (index != self.info.data_index).then(|| self.chunk_data[index].as_slice())
+    }
+}
+
+#[wasm_bindgen]
+impl WavAudio {
+    /// 格式與塊結構
+    #[wasm_bindgen]
+    pub fn get_info(&self) -> WavInfo {
+        self.info.clone()
+    }
+
+    /// 採樣率 (Hz)
+    #[wasm_bindgen]
+    pub fn get_sample_rate(&self) -> u32 {
+        self.info.format.sample_rate
+    }
+
+    /// 通道數
+    #[wasm_bindgen]
+    pub fn get_num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// 實際解碼的每通道樣本數
+    #[wasm_bindgen]
+    pub fn get_num_frames(&self) -> usize {
+        self.channels.first().map_or(0, Vec::len)
+    }
+
+    /// 第 `channel_idx` 個通道的樣本 (Float32Array，範圍 [-1, 1))；索引越界時返回空數組
+    #[wasm_bindgen]
+    pub fn get_channel(&self, channel_idx: usize) -> Vec<f32> {
+        self.channels.get(channel_idx).cloned().unwrap_or_default()
+    }
+
+    /// 第 `index` 個塊的原始數據；data 塊或索引越界時返回空數組
+    #[wasm_bindgen]
+    pub fn get_chunk_data(&self, index: usize) -> Vec<u8> {
+        self.chunk_data.get(index).cloned().unwrap_or_default()
+    }
+}
+
+/// 讀取 WAV 格式信息與塊列表 (不解碼樣本)
+///
+/// # Arguments
+/// * `bytes` - 文件內容 (Uint8Array)；讀取時長時只需傳入包含 data 塊頭的前綴
+///
+/// # Returns
+/// WavInfo 對象；文件無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+pub fn read_wav_info(bytes: &[u8]) -> Result<WavInfo, JsError> {
+    Ok(WavInfo::parse(bytes)?)
+}
+
+/// 解碼 WAV 文件
+///
+/// # Arguments
+/// * `bytes` - 完整的文件內容 (Uint8Array)
+///
+/// # Returns
+/// WavAudio 對象 (可直接傳給 WaveformEngine.load_wav)；文件無效時拋出帶有錯誤說明的 Error
+#[wasm_bindgen]
+pub fn decode_wav(bytes: &[u8]) -> Result<WavAudio, JsError> {
+    Ok(WavAudio::decode(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 16 位 PCM 單聲道 WAV，data 大小字段為 `data_size`
+    fn wav_with_data_size(samples: &[i16], data_size: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&8000u32.to_le_bytes());
+        bytes.extend_from_slice(&16000u32.to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_size.to_le_bytes());
+        for sample in samples {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn streamed_data_size_is_clamped_to_file() {
+        let samples = [0i16, 16384, -16384, 32767, -32768];
+        let bytes = wav_with_data_size(&samples, u32::MAX);
+        let info = WavInfo::parse(&bytes).unwrap();
+        assert_eq!(info.data_chunk().size, u32::MAX as u64);
+        assert_eq!(info.data_chunk().data(&bytes).len(), samples.len() * 2);
+
+        let channels = WavAudio::decode(&bytes).unwrap().channels().to_vec();
+        let expected: Vec<f32> = samples.iter().map(|&s| s as f32 / 32768.0).collect();
+        assert_eq!(channels, vec![expected]);
+    }
+
+    #[test]
+    fn chunk_data_never_panics_beyond_u32() {
+        let bytes = [0u8; 16];
+        for (offset, size) in [(8, u64::MAX), (u32::MAX as u64, 2), (1 << 32, 4), (u64::MAX, u64::MAX)] {
+            let chunk = WavChunk { id: *b"data", offset, size };
+            assert!(chunk.data(&bytes).len() <= bytes.len() - (offset.min(16) as usize));
+        }
+    }
+}
@@ -0,0 +1,439 @@
+// ============================================================
+// WAV 剪輯 (裁剪 / 拼接 / 分割)
+// 直接複製原始樣本字節，不重新編碼；保留 LIST/INFO 與廠商塊，
+// 更新 GUANO 的 Timestamp 與 Length、fact 塊的樣本數，裁剪時還後移
+// wamd 與 AudioMoth 注釋的時間、bext 的 TimeReference 以及 cue 點位置。
+// 可選在每段首尾加入短淡入淡出 (升餘弦) 以避免爆音。
+// ============================================================
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::guano::{GuanoMetadata, GUANO_CHUNK_ID};
+use crate::vendor_meta::{shift_list_info, shift_wamd_timestamp, RecordingMetadata, WAMD_CHUNK_ID};
+use crate::wav::{scale_sample, WavError, WavFormat, WavInfo};
+
+/// 超過此大小的輸出寫為 RF64
+const RIFF_MAX_SIZE: u64 = u32::MAX as u64;
+/// bext 塊中 TimeReference (自午夜起的樣本數，u64) 的偏移
+const BEXT_TIME_REFERENCE_OFFSET: usize = 338;
+/// cue 塊中每個 cue 點的字節數
+const CUE_POINT_SIZE: usize = 24;
+
+/// 剪輯錯誤
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditError {
+    /// 裁剪範圍為空或超出文件
+    InvalidRange { start_seconds: f64, end_seconds: f64 },
+    /// 淡入淡出時長為負數或非有限值
+    InvalidFade { fade_ms: f64 },
+    /// 拼接的文件格式不一致
+    FormatMismatch { index: usize },
+    /// 拼接時沒有輸入文件
+    NoInput,
+    /// WAV 文件無效
+    Wav(WavError),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::InvalidRange { start_seconds, end_seconds } => {
+                write!(f, "invalid range {}s..{}s", start_seconds, end_seconds)
+            }
+            EditError::InvalidFade { fade_ms } => write!(f, "invalid fade length {} ms", fade_ms),
+            EditError::FormatMismatch { index } => {
+                write!(f, "file {} has a different format from the first file", index)
+            }
+            EditError::NoInput => write!(f, "no input files"),
+            EditError::Wav(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl From<WavError> for EditError {
+    fn from(err: WavError) -> Self {
+        EditError::Wav(err)
+    }
+}
+
+/// 已解析的輸入文件
+struct Source<'a> {
+    bytes: &'a [u8],
+    info: WavInfo,
+    /// 文件中實際存在的完整幀數
+    num_frames: u64,
+}
+
+impl<'a> Source<'a> {
+    fn parse(bytes: &'a [u8]) -> Result<Source<'a>, EditError> {
+        let info = WavInfo::parse(bytes)?;
+        let available = info.data_chunk().data(bytes).len() as u64 / info.format().block_align as u64;
+        let num_frames = info.num_frames().min(available);
+        Ok(Source { bytes, info, num_frames })
+    }
+
+    fn sample_rate(&self) -> f64 {
+        self.info.format().sample_rate as f64
+    }
+
+    /// 第 `start..end` 幀的原始字節
+    fn frames(&self, start: u64, end: u64) -> &'a [u8] {
+        let block_align = self.info.format().block_align as usize;
+        let data = self.info.data_chunk().data(self.bytes);
+        &data[start as usize * block_align..end as usize * block_align]
+    }
+
+    /// 時間點所在的幀 (容忍乘法的浮點誤差，如 0.29 s × 100 Hz)
+    fn frame_at(&self, seconds: f64) -> u64 {
+        (seconds * self.sample_rate() + 1e-6).floor() as u64
+    }
+
+    /// 秒 -> 幀範圍；結束時間超出文件時截到文件結尾
+    fn frame_range(&self, start_seconds: f64, end_seconds: f64) -> Result<(u64, u64), EditError> {
+        let invalid = EditError::InvalidRange { start_seconds, end_seconds };
+        if !start_seconds.is_finite() || !end_seconds.is_finite() || start_seconds < 0.0 {
+            return Err(invalid);
+        }
+        let start = self.frame_at(start_seconds);
+        let end = self.frame_at(end_seconds).min(self.num_frames);
+        if end <= start {
+            return Err(invalid);
+        }
+        Ok((start, end))
+    }
+
+    fn guano(&self) -> Option<GuanoMetadata> {
+        GuanoMetadata::parse(self.chunk(GUANO_CHUNK_ID)?.1).ok()
+    }
+
+    /// 第一個 ID 為 `id` 的塊 (塊索引, 數據)
+    fn chunk(&self, id: &[u8; 4]) -> Option<(usize, &'a [u8])> {
+        let index = self.info.chunks().iter().position(|chunk| &chunk.id == id)?;
+        Some((index, self.info.chunks()[index].data(self.bytes)))
+    }
+
+    /// 錄音時的時間擴展係數 (GUANO TE 或 wamd Time Expansion，默認 1)
+    fn time_expansion(&self, guano: Option<&GuanoMetadata>) -> f64 {
+        guano
+            .and_then(|guano| guano.float("TE"))
+            .or_else(|| RecordingMetadata::from_wamd(self.chunk(WAMD_CHUNK_ID)?.1).time_expansion)
+            .filter(|&te| te > 0.0)
+            .unwrap_or(1.0)
+    }
+}
+
+fn fade_frames(fade_ms: f64, sample_rate: f64) -> Result<usize, EditError> {
+    if !fade_ms.is_finite() || fade_ms < 0.0 {
+        return Err(EditError::InvalidFade { fade_ms });
+    }
+    Ok((fade_ms * 1e-3 * sample_rate).round() as usize)
+}
+
+/// 對一段幀數據的首尾 `fade_frames` 幀施加升餘弦淡入淡出 (最多各佔一半)
+fn apply_fade(data: &mut [u8], format: &WavFormat, fade_frames: usize) {
+    let block_align = format.block_align as usize;
+    let sample_size = format.bytes_per_sample();
+    let num_frames = data.len() / block_align;
+    let fade = fade_frames.min(num_frames / 2);
+    if fade == 0 {
+        return;
+    }
+
+    for i in 0..fade {
+        let gain = 0.5 - 0.5 * (std::f64::consts::PI * (i as f64 + 0.5) / fade as f64).cos();
+        for frame in [i, num_frames - 1 - i] {
+            let bytes = &mut data[frame * block_align..(frame + 1) * block_align];
+            for sample in bytes.chunks_exact_mut(sample_size) {
+                scale_sample(sample, format.sample_format, gain);
+            }
+        }
+    }
+}
+
+/// 更新 GUANO 的開始時間與時長
+///
+/// 時間按錄音時的實際時間計算：時間擴展 (TE) 錄音的每幀時長為 1 / (採樣率 × TE)。
+fn update_guano(guano: &mut GuanoMetadata, seconds_per_frame: f64, offset_frames: u64, num_frames: u64) {
+    if let Some(timestamp) = guano.timestamp() {
+        guano.set_timestamp(&timestamp.add_seconds(offset_frames as f64 * seconds_per_frame));
+    }
+    let length = (num_frames as f64 * seconds_per_frame * 1e6).round() / 1e6;
+    guano.set("Length", &length.to_string());
+}
+
+/// bext 塊的 TimeReference 後移 `offset_frames` 個樣本；塊太短時返回 None
+fn shift_bext(data: &[u8], offset_frames: u64) -> Option<Vec<u8>> {
+    let range = BEXT_TIME_REFERENCE_OFFSET..BEXT_TIME_REFERENCE_OFFSET + 8;
+    let time_reference = u64::from_le_bytes(data.get(range.clone())?.try_into().unwrap());
+    let mut bext = data.to_vec();
+    bext[range].copy_from_slice(&time_reference.saturating_add(offset_frames).to_le_bytes());
+    Some(bext)
+}
+
+/// 只保留 `start..end` 幀內 (指向 data 塊) 的 cue 點，位置改為相對於裁剪起點；
+/// 沒有剩餘 cue 點時返回 None
+fn crop_cue(data: &[u8], start: u64, end: u64) -> Option<Vec<u8>> {
+    let count = u32::from_le_bytes(data.get(..4)?.try_into().unwrap()) as usize;
+    let points: Vec<Vec<u8>> = data[4..]
+        .chunks_exact(CUE_POINT_SIZE)
+        .take(count)
+        .filter_map(|point| {
+            let field = |i: usize| u32::from_le_bytes(point[i..i + 4].try_into().unwrap()) as u64;
+            let sample_offset = field(20);
+            if &point[8..12] != b"data" || sample_offset < start || sample_offset >= end {
+                return None;
+            }
+            let mut point = point.to_vec();
+            point[4..8].copy_from_slice(&(field(4).saturating_sub(start) as u32).to_le_bytes());
+            point[20..24].copy_from_slice(&((sample_offset - start) as u32).to_le_bytes());
+            Some(point)
+        })
+        .collect();
+    if points.is_empty() {
+        return None;
+    }
+    let mut cue = (points.len() as u32).to_le_bytes().to_vec();
+    points.iter().for_each(|point| cue.extend_from_slice(point));
+    Some(cue)
+}
+
+fn push_chunk(output: &mut Vec<u8>, id: &[u8], data: &[u8]) {
+    output.extend_from_slice(id);
+    output.extend_from_slice(&(data.len().min(u32::MAX as usize) as u32).to_le_bytes());
+    output.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        output.push(0);
+    }
+}
+
+/// 更新後的塊內容 (`template` 中的塊索引, 數據)：Some 替換原塊數據，None 刪除該塊
+type Replacement = (usize, Option<Vec<u8>>);
+
+/// 以 `template` 的塊結構寫出新文件
+///
+/// data 塊替換為 `data`，fact 塊的樣本數更新為 `num_frames`，ds64 按輸出大小重新生成；
+/// `replacements` 中列出的塊替換或刪除，其他塊原樣複製。
+fn assemble(template: &Source, data: &[u8], num_frames: u64, replacements: &[Replacement]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(data.len() + 4096);
+    for (index, chunk) in template.info.chunks().iter().enumerate() {
+        if let Some((_, replacement)) = replacements.iter().find(|(i, _)| *i == index) {
+            if let Some(data) = replacement {
+                push_chunk(&mut body, &chunk.id, data);
+            }
+            continue;
+        }
+        match &chunk.id {
+            b"ds64" => {}
+            b"data" => push_chunk(&mut body, b"data", data),
+            b"fact" if chunk.size >= 4 => {
+                let mut fact = chunk.data(template.bytes).to_vec();
+                fact[..4].copy_from_slice(&(num_frames.min(u32::MAX as u64) as u32).to_le_bytes());
+                push_chunk(&mut body, b"fact", &fact);
+            }
+            id => push_chunk(&mut body, id, chunk.data(template.bytes)),
+        }
+    }
+
+    let mut output = Vec::with_capacity(body.len() + 48);
+    let riff_size = 4 + body.len() as u64;
+    if riff_size <= RIFF_MAX_SIZE {
+        output.extend_from_slice(b"RIFF");
+        output.extend_from_slice(&(riff_size as u32).to_le_bytes());
+        output.extend_from_slice(b"WAVE");
+    } else {
+        // RF64：大小寫在緊隨文件頭的 ds64 塊中，data 塊頭使用佔位值
+        let mut ds64 = Vec::with_capacity(28);
+        ds64.extend_from_slice(&(riff_size + 36).to_le_bytes());
+        ds64.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        ds64.extend_from_slice(&num_frames.to_le_bytes());
+        ds64.extend_from_slice(&0u32.to_le_bytes());
+        output.extend_from_slice(b"RF64");
+        output.extend_from_slice(&u32::MAX.to_le_bytes());
+        output.extend_from_slice(b"WAVE");
+        push_chunk(&mut output, b"ds64", &ds64);
+    }
+    output.extend_from_slice(&body);
+    output
+}
+
+/// 裁剪 WAV 文件
+///
+/// 保留原始樣本格式與所有元數據塊，GUANO 的 Timestamp 後移到裁剪起點，
+/// Length 更新為裁剪後的時長；wamd 與 AudioMoth 注釋的時間、bext 的 TimeReference
+/// 同樣後移，cue 點只保留裁剪範圍內的部分。結束時間超出文件時截到文件結尾。
+pub fn crop(bytes: &[u8], start_seconds: f64, end_seconds: f64, fade_ms: f64) -> Result<Vec<u8>, EditError> {
+    let source = Source::parse(bytes)?;
+    let (start, end) = source.frame_range(start_seconds, end_seconds)?;
+    crop_frames(&source, start, end, fade_frames(fade_ms, source.sample_rate())?)
+}
+
+fn crop_frames(source: &Source, start: u64, end: u64, fade: usize) -> Result<Vec<u8>, EditError> {
+    let mut data = source.frames(start, end).to_vec();
+    apply_fade(&mut data, source.info.format(), fade);
+
+    let mut guano = source.guano();
+    let seconds_per_frame = 1.0 / (source.sample_rate() * source.time_expansion(guano.as_ref()));
+    let offset_seconds = start as f64 * seconds_per_frame;
+    let mut replacements: Vec<Replacement> = Vec::new();
+    if let (Some(guano), Some((index, _))) = (guano.as_mut(), source.chunk(GUANO_CHUNK_ID)) {
+        update_guano(guano, seconds_per_frame, start, end - start);
+        replacements.push((index, Some(guano.to_bytes())));
+    }
+    for (index, chunk) in source.info.chunks().iter().enumerate() {
+        let data = chunk.data(source.bytes);
+        let replacement = match &chunk.id {
+            b"cue " => Some(crop_cue(data, start, end)),
+            // 沒有可讀時間的 wamd / LIST / bext 原樣保留
+            b"wamd" if start > 0 => shift_wamd_timestamp(data, offset_seconds).map(Some),
+            b"LIST" if start > 0 => shift_list_info(data, offset_seconds).map(Some),
+            b"bext" if start > 0 => shift_bext(data, start).map(Some),
+            _ => None,
+        };
+        replacements.extend(replacement.map(|data| (index, data)));
+    }
+    Ok(assemble(source, &data, end - start, &replacements))
+}
+
+/// 在指定時間點分割 WAV 文件
+///
+/// 分割點 (秒) 自動排序，不在 (0, 時長) 內的分割點被忽略；
+/// 每段都按 `crop` 的方式保留並更新元數據。
+pub fn split(bytes: &[u8], boundaries_seconds: &[f64], fade_ms: f64) -> Result<Vec<Vec<u8>>, EditError> {
+    let source = Source::parse(bytes)?;
+    let fade = fade_frames(fade_ms, source.sample_rate())?;
+    let mut edges: Vec<u64> = boundaries_seconds
+        .iter()
+        .filter(|t| t.is_finite() && **t > 0.0)
+        .map(|&t| source.frame_at(t))
+        .filter(|&frame| frame > 0 && frame < source.num_frames)
+        .collect();
+    edges.push(0);
+    edges.push(source.num_frames);
+    edges.sort_unstable();
+    edges.dedup();
+
+    edges.windows(2).map(|pair| crop_frames(&source, pair[0], pair[1], fade)).collect()
+}
+
+/// 按順序拼接格式相同的 WAV 文件
+///
+/// 元數據取自第一個文件；GUANO Length 更新為總時長，Timestamp 保持第一個文件的開始時間。
+/// `fade_ms` 大於 0 時每個文件的首尾分別淡入淡出。
+pub fn concatenate(files: &[&[u8]], fade_ms: f64) -> Result<Vec<u8>, EditError> {
+    let sources = files.iter().map(|bytes| Source::parse(bytes)).collect::<Result<Vec<_>, _>>()?;
+    let first = sources.first().ok_or(EditError::NoInput)?;
+    let format = first.info.format();
+    if let Some(index) = sources.iter().position(|s| s.info.format() != format) {
+        return Err(EditError::FormatMismatch { index });
+    }
+    let fade = fade_frames(fade_ms, first.sample_rate())?;
+
+    let num_frames: u64 = sources.iter().map(|s| s.num_frames).sum();
+    let mut data = Vec::with_capacity(num_frames as usize * format.block_align as usize);
+    for source in &sources {
+        let start = data.len();
+        data.extend_from_slice(source.frames(0, source.num_frames));
+        apply_fade(&mut data[start..], format, fade);
+    }
+    let mut replacements: Vec<Replacement> = Vec::new();
+    if let (Some(mut guano), Some((index, _))) = (first.guano(), first.chunk(GUANO_CHUNK_ID)) {
+        let seconds_per_frame = 1.0 / (first.sample_rate() * first.time_expansion(Some(&guano)));
+        update_guano(&mut guano, seconds_per_frame, 0, num_frames);
+        replacements.push((index, Some(guano.to_bytes())));
+    }
+    Ok(assemble(first, &data, num_frames, &replacements))
+}
+
+/// 裁剪 WAV 文件 (保留元數據)
+///
+/// # Arguments
+/// * `bytes` - 文件內容 (Uint8Array)
+/// * `start_seconds` - 開始時間 (秒)
+/// * `end_seconds` - 結束時間 (秒)；超出文件時截到文件結尾
+/// * `fade_ms` - 首尾淡入淡出時長 (毫秒)，0 表示不淡入淡出
+///
+/// # Returns
+/// 新文件內容 (Uint8Array)；範圍無效或文件無效時拋出 Error
+#[wasm_bindgen]
+pub fn crop_wav(bytes: &[u8], start_seconds: f64, end_seconds: f64, fade_ms: f64) -> Result<Vec<u8>, JsError> {
+    Ok(crop(bytes, start_seconds, end_seconds, fade_ms)?)
+}
+
+/// WavSegments: 分割得到的各段文件
+#[wasm_bindgen]
+pub struct WavSegments {
+    segments: Vec<Vec<u8>>,
+}
+
+#[wasm_bindgen]
+impl WavSegments {
+    /// 段數
+    #[wasm_bindgen]
+    pub fn get_num_segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// 第 `index` 段的文件內容；索引越界時返回空數組
+    #[wasm_bindgen]
+    pub fn get_segment(&self, index: usize) -> Vec<u8> {
+        self.segments.get(index).cloned().unwrap_or_default()
+    }
+}
+
+/// 在指定時間點分割 WAV 文件 (保留元數據)
+///
+/// # Arguments
+/// * `bytes` - 文件內容 (Uint8Array)
+/// * `boundaries_seconds` - 分割點 (秒，Float64Array)
+/// * `fade_ms` - 每段首尾淡入淡出時長 (毫秒)
+///
+/// # Returns
+/// WavSegments 對象
+#[wasm_bindgen]
+pub fn split_wav(bytes: &[u8], boundaries_seconds: &[f64], fade_ms: f64) -> Result<WavSegments, JsError> {
+    Ok(WavSegments { segments: split(bytes, boundaries_seconds, fade_ms)? })
+}
+
+/// WavConcatenator: 逐個加入文件後拼接
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct WavConcatenator {
+    files: Vec<Vec<u8>>,
+}
+
+#[wasm_bindgen]
+impl WavConcatenator {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WavConcatenator {
+        WavConcatenator::default()
+    }
+
+    /// 加入一個文件 (Uint8Array)
+    #[wasm_bindgen]
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.files.push(bytes.to_vec());
+    }
+
+    /// 已加入的文件數
+    #[wasm_bindgen]
+    pub fn get_num_files(&self) -> usize {
+        self.files.len()
+    }
+
+    /// 按加入順序拼接
+    ///
+    /// # Arguments
+    /// * `fade_ms` - 每個文件首尾淡入淡出時長 (毫秒)
+    ///
+    /// # Returns
+    /// 新文件內容 (Uint8Array)；格式不一致或沒有文件時拋出 Error
+    #[wasm_bindgen]
+    pub fn concatenate(&self, fade_ms: f64) -> Result<Vec<u8>, JsError> {
+        let files: Vec<&[u8]> = self.files.iter().map(Vec::as_slice).collect();
+        Ok(concatenate(&files, fade_ms)?)
+    }
+}
@@ -0,0 +1,421 @@
+// ============================================================
+// Welch 功率譜密度估計
+// 分段 -> 去趨勢 -> 加窗 -> FFT -> 平均（均值或中位數），
+// 同時返回頻率軸，可選單邊 / 雙邊輸出與置信區間。
+// ============================================================
+
+use std::fmt;
+
+use num_complex::Complex;
+use rustfft::FftPlanner;
+use wasm_bindgen::prelude::*;
+
+use crate::create_window;
+
+/// 防止 log10(0) 的最小功率
+const MIN_POWER: f32 = 1e-20;
+
+/// 分段去趨勢方式（在加窗之前執行）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Detrend {
+    None,
+    /// 減去均值
+    Constant,
+    /// 減去最小二乘直線
+    Linear,
+}
+
+impl Detrend {
+    fn from_name(name: &str) -> Option<Detrend> {
+        match name {
+            "none" => Some(Detrend::None),
+            "constant" => Some(Detrend::Constant),
+            "linear" => Some(Detrend::Linear),
+            _ => None,
+        }
+    }
+
+    fn apply(self, segment: &mut [f32]) {
+        let n = segment.len() as f32;
+        match self {
+            Detrend::None => {}
+            Detrend::Constant => {
+                let mean = segment.iter().sum::<f32>() / n;
+                segment.iter_mut().for_each(|x| *x -= mean);
+            }
+            Detrend::Linear => {
+                // x = 0..n-1，均值為 (n-1)/2
+                let x_mean = (n - 1.0) / 2.0;
+                let y_mean = segment.iter().sum::<f32>() / n;
+                let mut sxy = 0.0f32;
+                let mut sxx = 0.0f32;
+                for (i, &y) in segment.iter().enumerate() {
+                    let dx = i as f32 - x_mean;
+                    sxy += dx * (y - y_mean);
+                    sxx += dx * dx;
+                }
+                let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+                for (i, y) in segment.iter_mut().enumerate() {
+                    *y -= y_mean + slope * (i as f32 - x_mean);
+                }
+            }
+        }
+    }
+}
+
+/// 分段週期圖的平均方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Average {
+    Mean,
+    /// 中位數 (對瞬態干擾更穩健，需要保存所有分段的週期圖)
+    Median,
+}
+
+impl Average {
+    fn from_name(name: &str) -> Option<Average> {
+        match name {
+            "mean" => Some(Average::Mean),
+            "median" => Some(Average::Median),
+            _ => None,
+        }
+    }
+}
+
+/// Welch 參數錯誤
+#[derive(Clone, Debug, PartialEq)]
+pub enum WelchError {
+    /// 未知的去趨勢方式
+    UnknownDetrend(String),
+    /// 未知的平均方式
+    UnknownAverage(String),
+    /// 未知的輸出方式
+    UnknownSides(String),
+}
+
+impl fmt::Display for WelchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WelchError::UnknownDetrend(name) => {
+                write!(f, "unknown detrend '{}' (expected none, constant or linear)", name)
+            }
+            WelchError::UnknownAverage(name) => write!(f, "unknown average '{}' (expected mean or median)", name),
+            WelchError::UnknownSides(name) => write!(f, "unknown sides '{}' (expected onesided or twosided)", name),
+        }
+    }
+}
+
+impl std::error::Error for WelchError {}
+
+/// 中位數平均的偏差校正係數
+///
+/// 對 χ²(2) 分佈取中位數相對於均值的偏差（與 scipy.signal.welch 相同）。
+fn median_bias(n: usize) -> f32 {
+    let mut bias = 1.0f32;
+    for k in 1..=(n - 1) / 2 {
+        let ii = 2.0 * k as f32;
+        bias += 1.0 / (ii + 1.0) - 1.0 / ii;
+    }
+    bias
+}
+
+/// Welch 估計的等效自由度
+///
+/// 重疊分段並不獨立 (Welch 1967)：ν = 2K / (1 + 2 Σ_{m=1}^{K-1} (1 - m/K) ρ²(m·step))，
+/// 其中 ρ(d) = Σ w[n]·w[n+d] / Σ w² 為窗函數的歸一化自相關。
+/// 中位數平均的方差約為均值的 1 / ln²2 倍 (χ²(2) 樣本中位數的漸近方差)，自由度相應縮小。
+fn equivalent_dof(window: &[f32], step: usize, num_segments: usize, average: Average) -> f64 {
+    let window_power: f64 = window.iter().map(|&w| (w as f64).powi(2)).sum();
+    let k = num_segments as f64;
+    let mut correlation = 0.0f64;
+    for m in 1..num_segments {
+        let lag = m * step;
+        if lag >= window.len() || window_power <= 0.0 {
+            break;
+        }
+        let lagged: f64 = window.iter().zip(&window[lag..]).map(|(&a, &b)| a as f64 * b as f64).sum();
+        let rho = lagged / window_power;
+        correlation += (1.0 - m as f64 / k) * rho * rho;
+    }
+    let dof = 2.0 * k / (1.0 + 2.0 * correlation);
+    match average {
+        Average::Mean => dof,
+        // 兩段以內中位數與均值相同
+        Average::Median if num_segments > 2 => dof * std::f64::consts::LN_2.powi(2),
+        Average::Median => dof,
+    }
+}
+
+/// 標準正態分佈分位數 (Acklam 有理近似，相對誤差約 1e-9)
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2,
+        1.38357751867269e2, -3.066479806614716e1, 2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2,
+        6.680131188771972e1, -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838,
+        -2.549732539343734, 4.374664141464968, 2.938163982698783,
+    ];
+    const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416];
+
+    let p = p.clamp(1e-12, 1.0 - 1e-12);
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p < 0.02425 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// χ² 分佈分位數 (Wilson–Hilferty 近似)
+fn chi2_quantile(p: f64, dof: f64) -> f64 {
+    let h = 2.0 / (9.0 * dof);
+    let z = normal_quantile(p);
+    (dof * (1.0 - h + z * h.sqrt()).powi(3)).max(0.0)
+}
+
+/// WelchSpectrum: Welch 功率譜密度估計結果
+///
+/// 數值為線性功率譜密度 (單位²/Hz)。單邊輸出的頻率為 0..=fs/2，
+/// 雙邊輸出按頻率遞增排列 (-fs/2..fs/2)。
+#[wasm_bindgen]
+pub struct WelchSpectrum {
+    frequencies: Vec<f32>,
+    values: Vec<f32>,
+    lower: Vec<f32>,
+    upper: Vec<f32>,
+    num_segments: usize,
+}
+
+impl WelchSpectrum {
+    fn empty() -> WelchSpectrum {
+        WelchSpectrum {
+            frequencies: Vec::new(),
+            values: Vec::new(),
+            lower: Vec::new(),
+            upper: Vec::new(),
+            num_segments: 0,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl WelchSpectrum {
+    /// 頻率軸 (Hz)
+    #[wasm_bindgen]
+    pub fn get_frequencies(&self) -> Vec<f32> {
+        self.frequencies.clone()
+    }
+
+    /// 功率譜密度 (線性)
+    #[wasm_bindgen]
+    pub fn get_values(&self) -> Vec<f32> {
+        self.values.clone()
+    }
+
+    /// 功率譜密度 (dB)
+    #[wasm_bindgen]
+    pub fn get_values_db(&self) -> Vec<f32> {
+        self.values.iter().map(|&v| 10.0 * v.max(MIN_POWER).log10()).collect()
+    }
+
+    /// 置信區間下限 (線性)；未請求置信區間時為空數組
+    #[wasm_bindgen]
+    pub fn get_lower(&self) -> Vec<f32> {
+        self.lower.clone()
+    }
+
+    /// 置信區間上限 (線性)；未請求置信區間時為空數組
+    #[wasm_bindgen]
+    pub fn get_upper(&self) -> Vec<f32> {
+        self.upper.clone()
+    }
+
+    /// 參與平均的分段數
+    #[wasm_bindgen]
+    pub fn get_num_segments(&self) -> usize {
+        self.num_segments
+    }
+}
+
+/// 使用 Welch 方法估計功率譜密度
+///
+/// 與 compute_power_spectrum 不同：去趨勢在加窗之前執行，重疊以樣本數指定
+/// (0 表示不重疊)，並同時返回頻率軸。
+///
+/// # Arguments
+/// * `audio_data` - 音頻數據 (Float32Array)
+/// * `sample_rate` - 採樣率 (Hz)
+/// * `segment_size` - 每段長度 (同時為 FFT 大小)
+/// * `noverlap` - 相鄰分段重疊的樣本數 (必須小於 segment_size)
+/// * `window_type` - 窗函數類型 (hann, hamming, blackman, gauss, rectangular, triangular)
+/// * `detrend` - "none"、"constant" 或 "linear"
+/// * `average` - "mean" 或 "median" (中位數對瞬態干擾更穩健，已做偏差校正)
+/// * `sides` - "onesided" 或 "twosided"
+/// * `confidence` - 置信水平 (例如 0.95)；<= 0 表示不計算置信區間
+///
+/// # Returns
+/// WelchSpectrum 對象；音頻短於一個分段或分段參數無效時返回空結果；
+/// detrend、average 或 sides 名稱未知時拋出 Error
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn compute_welch_spectrum(
+    audio_data: &[f32],
+    sample_rate: f32,
+    segment_size: usize,
+    noverlap: usize,
+    window_type: &str,
+    detrend: &str,
+    average: &str,
+    sides: &str,
+    confidence: f32,
+) -> Result<WelchSpectrum, JsError> {
+    let detrend = Detrend::from_name(detrend).ok_or_else(|| WelchError::UnknownDetrend(detrend.to_string()))?;
+    let average = Average::from_name(average).ok_or_else(|| WelchError::UnknownAverage(average.to_string()))?;
+    let one_sided = match sides {
+        "onesided" => true,
+        "twosided" => false,
+        _ => return Err(WelchError::UnknownSides(sides.to_string()).into()),
+    };
+    Ok(welch(audio_data, sample_rate, segment_size, noverlap, window_type, detrend, average, one_sided, confidence))
+}
+
+/// Welch 估計 (compute_welch_spectrum 的內部實現，參數已解析)
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn welch(
+    audio_data: &[f32],
+    sample_rate: f32,
+    segment_size: usize,
+    noverlap: usize,
+    window_type: &str,
+    detrend: Detrend,
+    average: Average,
+    one_sided: bool,
+    confidence: f32,
+) -> WelchSpectrum {
+    if segment_size == 0 || noverlap >= segment_size || audio_data.len() < segment_size || sample_rate <= 0.0 {
+        return WelchSpectrum::empty();
+    }
+
+    let step = segment_size - noverlap;
+    let num_segments = (audio_data.len() - segment_size) / step + 1;
+    let window = create_window(window_type, segment_size, 0.16);
+    // 密度歸一化: 1 / (fs * Σw²)
+    let window_power: f32 = window.iter().map(|&w| w * w).sum();
+    let scale = 1.0 / (sample_rate * window_power);
+
+    let mut planner = FftPlanner::new();
+    let fft = planner.plan_fft_forward(segment_size);
+    let mut buffer = vec![Complex::new(0.0f32, 0.0); segment_size];
+    let mut segment = vec![0.0f32; segment_size];
+
+    // 第 seg_idx 個分段的週期圖寫入 row
+    let mut periodogram = |seg_idx: usize, row: &mut [f32]| {
+        let start = seg_idx * step;
+        segment.copy_from_slice(&audio_data[start..start + segment_size]);
+        detrend.apply(&mut segment);
+
+        for ((slot, &x), &w) in buffer.iter_mut().zip(&segment).zip(&window) {
+            *slot = Complex::new(x * w, 0.0);
+        }
+        fft.process(&mut buffer);
+
+        for (p, c) in row.iter_mut().zip(&buffer) {
+            *p = c.norm_sqr() * scale;
+        }
+    };
+
+    // 分段平均
+    let mut values = vec![0.0f32; segment_size];
+    match average {
+        Average::Median => {
+            // 中位數需要所有分段的週期圖 (num_segments x segment_size)
+            let mut periodograms = vec![0.0f32; num_segments * segment_size];
+            for (seg_idx, row) in periodograms.chunks_exact_mut(segment_size).enumerate() {
+                periodogram(seg_idx, row);
+            }
+            let bias = median_bias(num_segments);
+            let mut column = vec![0.0f32; num_segments];
+            for (bin, value) in values.iter_mut().enumerate() {
+                for (seg_idx, slot) in column.iter_mut().enumerate() {
+                    *slot = periodograms[seg_idx * segment_size + bin];
+                }
+                column.sort_by(|a, b| a.total_cmp(b));
+                let mid = num_segments / 2;
+                let median = if num_segments.is_multiple_of(2) {
+                    0.5 * (column[mid - 1] + column[mid])
+                } else {
+                    column[mid]
+                };
+                *value = median / bias;
+            }
+        }
+        Average::Mean => {
+            // 均值只需累加
+            let mut row = vec![0.0f32; segment_size];
+            for seg_idx in 0..num_segments {
+                periodogram(seg_idx, &mut row);
+                for (value, &p) in values.iter_mut().zip(&row) {
+                    *value += p;
+                }
+            }
+            values.iter_mut().for_each(|v| *v /= num_segments as f32);
+        }
+    }
+
+    // 單邊 / 雙邊輸出
+    let bin_hz = sample_rate / segment_size as f32;
+    let (frequencies, values) = if !one_sided {
+        // 重排為頻率遞增 (-fs/2..fs/2)
+        let half = segment_size.div_ceil(2);
+        let order: Vec<usize> = (half..segment_size).chain(0..half).collect();
+        let frequencies = order
+            .iter()
+            .map(|&k| if k < half { k as f32 * bin_hz } else { (k as f32 - segment_size as f32) * bin_hz })
+            .collect();
+        (frequencies, order.iter().map(|&k| values[k]).collect())
+    } else {
+        let num_bins = segment_size / 2 + 1;
+        let mut one_sided: Vec<f32> = values[..num_bins].to_vec();
+        // 除 DC 與 Nyquist (偶數長度) 外，負頻率能量折疊到正頻率
+        let last = if segment_size.is_multiple_of(2) { num_bins - 1 } else { num_bins };
+        for v in one_sided.iter_mut().take(last).skip(1) {
+            *v *= 2.0;
+        }
+        ((0..num_bins).map(|k| k as f32 * bin_hz).collect(), one_sided)
+    };
+
+    // 置信區間: 等效自由度按窗函數與重疊計算，中位數平均再乘以其相對均值的效率
+    let (lower, upper) = if confidence > 0.0 && confidence < 1.0 {
+        let dof = equivalent_dof(&window, step, num_segments, average);
+        let alpha = 1.0 - confidence as f64;
+        let lower_factor = (dof / chi2_quantile(1.0 - alpha / 2.0, dof)) as f32;
+        let upper_factor = (dof / chi2_quantile(alpha / 2.0, dof).max(1e-12)) as f32;
+        (
+            values.iter().map(|&v| v * lower_factor).collect(),
+            values.iter().map(|&v| v * upper_factor).collect(),
+        )
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    WelchSpectrum {
+        frequencies,
+        values,
+        lower,
+        upper,
+        num_segments,
+    }
+}
@@ -0,0 +1,573 @@
+// ============================================================
+// Anabat 過零 (ZC) 文件
+// 讀寫 Anabat 132-135 型文件：固定長度的文本頭、數據信息表
+// (數據偏移、計時器分辨率、分頻比、錄音時間) 與變長編碼的
+// 過零間隔；以及從全頻譜音頻提取過零事件 (高通 -> 帶遲滯的
+// 過零計數 -> 每 N 個週期記錄一次時間)，以便與歷史數據比較。
+// ============================================================
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::iir::{BandType, FilterError, FilterFamily, IirFilter};
+use crate::timestamp::Timestamp;
+
+/// 文本頭之後的數據信息表偏移
+const DATA_INFO_OFFSET: usize = 0x11a;
+/// 132 型及以後文件中過零數據的起始偏移
+const DATA_OFFSET: usize = 0x150;
+/// 寫出的文件類型
+const WRITE_FILE_TYPE: u8 = 132;
+/// 標準計時器分辨率：res1 = 25000 時每個計時單位為 1 µs
+const STANDARD_RES1: u16 = 25_000;
+/// 轉換時前置高通濾波器的階數
+const ZC_HIGHPASS_ORDER: usize = 4;
+/// GPS 文本相對錄音時間字段的偏移與長度 (寫出時延伸到 DATA_OFFSET)
+const GPS_OFFSET: usize = 16;
+const GPS_LEN: usize = DATA_OFFSET - DATA_INFO_OFFSET - 7 - GPS_OFFSET;
+/// 單個間隔可編碼的最大值 (29 位)
+const MAX_INTERVAL: u32 = (1 << 29) - 1;
+
+/// 文本頭字段：名稱、偏移、長度
+const TEXT_FIELDS: &[(&str, usize, usize)] = &[
+    ("tape", 0x007, 8),
+    ("date", 0x00f, 8),
+    ("location", 0x017, 40),
+    ("species", 0x03f, 50),
+    ("spec", 0x071, 16),
+    ("note", 0x081, 73),
+    ("note1", 0x0ca, 80),
+];
+
+/// Anabat 文件錯誤
+#[derive(Clone, Debug, PartialEq)]
+pub enum ZcError {
+    /// 文件太短或數據信息表指針無效
+    NotAnabat,
+    /// 只支持 132-135 型文件
+    UnsupportedFileType { file_type: u8 },
+    /// 分頻比必須在 1-255 之間
+    InvalidDivisionRatio { ratio: u32 },
+    /// 採樣率必須為正數
+    InvalidSampleRate { sample_rate: f32 },
+    /// 未知的文本頭字段
+    UnknownField(String),
+    /// 濾波器設計失敗
+    Filter(FilterError),
+}
+
+impl fmt::Display for ZcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZcError::NotAnabat => write!(f, "not an Anabat zero-crossing file"),
+            ZcError::UnsupportedFileType { file_type } => {
+                write!(f, "unsupported Anabat file type {} (expected 132-135)", file_type)
+            }
+            ZcError::InvalidDivisionRatio { ratio } => {
+                write!(f, "division ratio must be between 1 and 255, got {}", ratio)
+            }
+            ZcError::InvalidSampleRate { sample_rate } => {
+                write!(f, "sample rate must be positive, got {}", sample_rate)
+            }
+            ZcError::UnknownField(name) => write!(
+                f,
+                "unknown header field '{}' (expected tape, date, location, species, spec, note, note1, id_code or gps)",
+                name
+            ),
+            ZcError::Filter(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ZcError {}
+
+impl From<FilterError> for ZcError {
+    fn from(err: FilterError) -> Self {
+        ZcError::Filter(err)
+    }
+}
+
+/// 讀取定長 ASCII 字段 (去掉末尾的 NUL 與空格)
+fn read_text(bytes: &[u8], offset: usize, len: usize) -> String {
+    let end = (offset + len).min(bytes.len());
+    let field = bytes.get(offset..end).unwrap_or(&[]);
+    let field = &field[..field.iter().position(|&b| b == 0).unwrap_or(field.len())];
+    String::from_utf8_lossy(field).trim_end().to_string()
+}
+
+/// 寫入定長 ASCII 字段 (非 ASCII 字符替換為 `?`，不足部分補空格)
+fn write_text(bytes: &mut [u8], offset: usize, len: usize, text: &str) {
+    let mut chars = text.chars().map(|c| if c.is_ascii() { c as u8 } else { b'?' });
+    for byte in &mut bytes[offset..offset + len] {
+        *byte = chars.next().unwrap_or(b' ');
+    }
+}
+
+/// 解碼 132-135 型的變長過零數據，返回各事件的間隔 (計時單位)
+///
+/// - `0xxxxxxx`：7 位有符號數，與上一個間隔的差
+/// - `100xxxxx` + 1 字節 / `101xxxxx` + 2 字節 / `110xxxxx` + 3 字節：13 / 21 / 29 位間隔
+/// - `111xxxxx` + 1 字節：狀態信息 (後續點的顯示樣式)，不產生事件
+fn decode_intervals(data: &[u8]) -> Vec<u32> {
+    let mut intervals = Vec::with_capacity(data.len());
+    let mut last = 0i64;
+    let mut i = 0;
+    while i < data.len() {
+        let byte = data[i];
+        let extra = match byte >> 5 {
+            0..=3 => 0,
+            4 => 1,
+            5 => 2,
+            6 => 3,
+            _ => {
+                i += 2;
+                continue;
+            }
+        };
+        if i + extra >= data.len() {
+            break;
+        }
+        if extra == 0 {
+            let difference = if byte & 0x40 != 0 { byte as i64 - 0x80 } else { byte as i64 };
+            last = (last + difference).max(0);
+        } else {
+            last = data[i + 1..=i + extra].iter().fold((byte & 0x1f) as i64, |acc, &b| (acc << 8) | b as i64);
+        }
+        intervals.push(last as u32);
+        i += 1 + extra;
+    }
+    intervals
+}
+
+/// 編碼過零間隔 (與前一間隔相差不超過 ±63 時使用 1 字節差值)
+fn encode_intervals(intervals: &[u32]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(intervals.len() * 2);
+    let mut last = 0i64;
+    for &interval in intervals {
+        let interval = interval.min(MAX_INTERVAL);
+        let difference = interval as i64 - last;
+        if (-64..=63).contains(&difference) {
+            data.push((difference & 0x7f) as u8);
+        } else {
+            let (prefix, extra) = match interval {
+                0..=0x1fff => (0x80u8, 1),
+                0x2000..=0x1f_ffff => (0xa0, 2),
+                _ => (0xc0, 3),
+            };
+            data.push(prefix | (interval >> (8 * extra)) as u8);
+            for k in (0..extra).rev() {
+                data.push((interval >> (8 * k)) as u8);
+            }
+        }
+        last = interval as i64;
+    }
+    data
+}
+
+/// ZcRecording: Anabat 過零錄音
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZcRecording {
+    file_type: u8,
+    division_ratio: u8,
+    /// 計時器分辨率 (每 25 ms 的計時單位數；25000 表示 1 µs)
+    res1: u16,
+    vres: u16,
+    /// 文本頭字段 (與 TEXT_FIELDS 順序相同)
+    text: Vec<String>,
+    /// 錄音開始時間 (本地時間，文件中沒有時區)
+    timestamp: Option<Timestamp>,
+    id_code: String,
+    gps: String,
+    /// 每個事件與前一事件的間隔 (計時單位)；第一個間隔從錄音開始計
+    intervals: Vec<u32>,
+}
+
+impl ZcRecording {
+    fn empty(division_ratio: u8) -> ZcRecording {
+        ZcRecording {
+            file_type: WRITE_FILE_TYPE,
+            division_ratio,
+            res1: STANDARD_RES1,
+            vres: 0,
+            text: vec![String::new(); TEXT_FIELDS.len()],
+            timestamp: None,
+            id_code: String::new(),
+            gps: String::new(),
+            intervals: Vec::new(),
+        }
+    }
+
+    /// 解析 Anabat 132-135 型文件
+    pub fn parse(bytes: &[u8]) -> Result<ZcRecording, ZcError> {
+        if bytes.len() < DATA_INFO_OFFSET + 7 {
+            return Err(ZcError::NotAnabat);
+        }
+        let info = u16::from_le_bytes([bytes[3], bytes[4]]) as usize;
+        if info + 7 > bytes.len() || info < DATA_INFO_OFFSET {
+            return Err(ZcError::NotAnabat);
+        }
+        let file_type = bytes[6];
+        if !(132..=135).contains(&file_type) {
+            return Err(ZcError::UnsupportedFileType { file_type });
+        }
+
+        let read_u16 = |offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+        let data_offset = read_u16(info) as usize;
+        let res1 = read_u16(info + 2);
+        let division_ratio = bytes[info + 4];
+        let vres = read_u16(info + 5);
+        if data_offset < info + 7 || data_offset > bytes.len() || res1 == 0 {
+            return Err(ZcError::NotAnabat);
+        }
+
+        // 132 型起：年 (u16)、月、日、時、分、秒、百分秒、微秒 (u16)、6 字節 ID、GPS 文本
+        let time = info + 7;
+        let timestamp = (bytes.len() >= time + 10)
+            .then(|| {
+                let micros = bytes[time + 7] as u32 * 10_000 + read_u16(time + 8) as u32;
+                Timestamp::new(
+                    read_u16(time) as i32,
+                    bytes[time + 2],
+                    bytes[time + 3],
+                    bytes[time + 4],
+                    bytes[time + 5],
+                    bytes[time + 6],
+                    micros.min(999_999),
+                    None,
+                )
+            })
+            .flatten();
+        let gps_end = (time + GPS_OFFSET + GPS_LEN).min(data_offset);
+
+        Ok(ZcRecording {
+            file_type,
+            division_ratio,
+            res1,
+            vres,
+            text: TEXT_FIELDS.iter().map(|&(_, offset, len)| read_text(bytes, offset, len)).collect(),
+            timestamp,
+            id_code: read_text(bytes, time + 10, 6),
+            gps: read_text(bytes, time + GPS_OFFSET, gps_end.saturating_sub(time + GPS_OFFSET)),
+            intervals: decode_intervals(&bytes[data_offset..]),
+        })
+    }
+
+    /// 從全頻譜音頻提取過零事件
+    ///
+    /// 信號經高通後以 ±threshold 遲滯檢測上升過零，每 `division_ratio` 個週期記錄一個事件，
+    /// 事件時間在越過閾值的兩個樣本之間線性插值，以 1 µs 計時單位保存。
+    ///
+    /// # Arguments
+    /// * `audio` - 輸入音頻
+    /// * `sample_rate` - 採樣率 (Hz)
+    /// * `division_ratio` - 分頻比 (1-255，Anabat 典型值 8 或 16)
+    /// * `sensitivity_db` - 靈敏度：觸發閾值為滿量程以下 `sensitivity_db` dB
+    /// * `highpass_hz` - 前置高通截止頻率 (Hz)，<= 0 表示不濾波
+    pub fn from_audio(
+        audio: &[f32],
+        sample_rate: f32,
+        division_ratio: u32,
+        sensitivity_db: f32,
+        highpass_hz: f32,
+    ) -> Result<ZcRecording, ZcError> {
+        if !(sample_rate > 0.0 && sample_rate.is_finite()) {
+            return Err(ZcError::InvalidSampleRate { sample_rate });
+        }
+        if !(1..=255).contains(&division_ratio) {
+            return Err(ZcError::InvalidDivisionRatio { ratio: division_ratio });
+        }
+        let filtered = if highpass_hz > 0.0 {
+            IirFilter::design(
+                FilterFamily::Butterworth,
+                BandType::Highpass,
+                ZC_HIGHPASS_ORDER,
+                sample_rate,
+                highpass_hz,
+                0.0,
+                0.0,
+                0.0,
+            )?
+            .apply(audio)
+        } else {
+            audio.to_vec()
+        };
+
+        let threshold = 10f32.powf(-sensitivity_db.max(0.0) / 20.0);
+        let micros_per_sample = 1e6 / sample_rate as f64;
+        let mut recording = ZcRecording::empty(division_ratio as u8);
+        let mut armed_low = false;
+        let mut cycles = 0;
+        let mut last_event = 0u64;
+        for i in 1..filtered.len() {
+            let x = filtered[i];
+            if x < -threshold {
+                armed_low = true;
+            } else if armed_low && x > threshold {
+                armed_low = false;
+                cycles += 1;
+                if cycles == division_ratio {
+                    cycles = 0;
+                    // 在前一樣本與當前樣本之間插值越過 +threshold 的時刻
+                    let previous = filtered[i - 1];
+                    let fraction = ((threshold - previous) / (x - previous)).clamp(0.0, 1.0) as f64;
+                    let time = ((i as f64 - 1.0 + fraction) * micros_per_sample).round() as u64;
+                    let mut interval = time - last_event;
+                    // 超過 29 位的靜音間隔拆分為多個事件
+                    while interval > MAX_INTERVAL as u64 {
+                        recording.intervals.push(MAX_INTERVAL);
+                        interval -= MAX_INTERVAL as u64;
+                    }
+                    recording.intervals.push(interval as u32);
+                    last_event = time;
+                }
+            }
+        }
+        Ok(recording)
+    }
+
+    /// 序列化為 Anabat 132 型文件
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; DATA_OFFSET];
+        bytes[3..5].copy_from_slice(&(DATA_INFO_OFFSET as u16).to_le_bytes());
+        bytes[6] = WRITE_FILE_TYPE;
+        for (&(_, offset, len), value) in TEXT_FIELDS.iter().zip(&self.text) {
+            write_text(&mut bytes, offset, len, value);
+        }
+
+        let info = DATA_INFO_OFFSET;
+        bytes[info..info + 2].copy_from_slice(&(DATA_OFFSET as u16).to_le_bytes());
+        bytes[info + 2..info + 4].copy_from_slice(&self.res1.to_le_bytes());
+        bytes[info + 4] = self.division_ratio;
+        bytes[info + 5..info + 7].copy_from_slice(&self.vres.to_le_bytes());
+        let time = info + 7;
+        if let Some(t) = &self.timestamp {
+            bytes[time..time + 2].copy_from_slice(&(t.year.clamp(0, u16::MAX as i32) as u16).to_le_bytes());
+            bytes[time + 2..time + 7].copy_from_slice(&[t.month, t.day, t.hour, t.minute, t.second]);
+            bytes[time + 7] = (t.microsecond / 10_000) as u8;
+            bytes[time + 8..time + 10].copy_from_slice(&((t.microsecond % 10_000) as u16).to_le_bytes());
+        }
+        write_text(&mut bytes, time + 10, 6, &self.id_code);
+        write_text(&mut bytes, time + GPS_OFFSET, GPS_LEN, &self.gps);
+
+        bytes.extend_from_slice(&encode_intervals(&self.intervals));
+        bytes
+    }
+
+    /// 每個計時單位的秒數
+    fn tick_seconds(&self) -> f64 {
+        0.025 / self.res1 as f64
+    }
+
+    /// 各事件的時間 (秒，從錄音開始計)
+    pub fn event_times(&self) -> Vec<f64> {
+        let tick = self.tick_seconds();
+        let mut ticks = 0u64;
+        self.intervals
+            .iter()
+            .map(|&interval| {
+                ticks += interval as u64;
+                ticks as f64 * tick
+            })
+            .collect()
+    }
+
+    /// 點 (時間秒, 頻率 Hz)：頻率 = 分頻比 / 與前一事件的間隔；第一個事件沒有前一事件，不產生點
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        let tick = self.tick_seconds();
+        let ratio = self.division_ratio.max(1) as f64;
+        self.event_times()
+            .into_iter()
+            .zip(&self.intervals)
+            .skip(1)
+            .filter(|&(_, &interval)| interval > 0)
+            .map(|(time, &interval)| (time, ratio / (interval as f64 * tick)))
+            .collect()
+    }
+
+    /// 錄音時長 (最後一個事件的時間，秒)
+    pub fn duration(&self) -> f64 {
+        self.intervals.iter().map(|&i| i as u64).sum::<u64>() as f64 * self.tick_seconds()
+    }
+
+    /// 錄音開始時間
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        self.timestamp
+    }
+
+    /// 設置錄音開始時間 (Anabat 文件只保存本地時間，UTC 偏移被忽略)
+    pub fn set_timestamp(&mut self, timestamp: Option<Timestamp>) {
+        self.timestamp = timestamp;
+    }
+
+    fn text_field(&mut self, name: &str) -> Result<&mut String, ZcError> {
+        match name {
+            "id_code" => Ok(&mut self.id_code),
+            "gps" => Ok(&mut self.gps),
+            _ => TEXT_FIELDS
+                .iter()
+                .position(|&(field, _, _)| field == name)
+                .map(|i| &mut self.text[i])
+                .ok_or_else(|| ZcError::UnknownField(name.to_string())),
+        }
+    }
+
+    /// 點陣強度圖 (u8，行優先，第一行為最高頻率)
+    ///
+    /// 每個點畫為 `dot_size` × `dot_size` 的方塊；同一像素被多個點覆蓋時強度增加
+    /// (1 個點為 160，每加倍 +24，最多 255)，沒有點的像素為 0。
+    #[allow(clippy::too_many_arguments)]
+    pub fn dot_levels(
+        &self,
+        start_time: f64,
+        end_time: f64,
+        freq_min: f64,
+        freq_max: f64,
+        log_scale: bool,
+        width: usize,
+        height: usize,
+        dot_size: usize,
+    ) -> Vec<u8> {
+        let mut counts = vec![0u32; width * height];
+        let log_scale = log_scale && freq_min > 0.0;
+        let (f0, f1) = if log_scale { (freq_min.ln(), freq_max.ln()) } else { (freq_min, freq_max) };
+        if width == 0 || height == 0 || end_time <= start_time || f1 <= f0 {
+            return vec![0; width * height];
+        }
+        let dot_size = dot_size.max(1);
+        let half = (dot_size - 1) / 2;
+
+        for (time, freq) in self.points() {
+            if time < start_time || time >= end_time || freq < freq_min || freq >= freq_max {
+                continue;
+            }
+            let f = if log_scale { freq.ln() } else { freq };
+            let x = ((time - start_time) / (end_time - start_time) * width as f64) as usize;
+            let y = height - 1 - ((f - f0) / (f1 - f0) * height as f64).min(height as f64 - 1.0) as usize;
+            for py in y.saturating_sub(half)..(y.saturating_sub(half) + dot_size).min(height) {
+                for px in x.saturating_sub(half)..(x.saturating_sub(half) + dot_size).min(width) {
+                    counts[py * width + px] += 1;
+                }
+            }
+        }
+
+        counts
+            .into_iter()
+            .map(|count| if count == 0 { 0 } else { (160.0 + 24.0 * (count as f32).log2()).min(255.0) as u8 })
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl ZcRecording {
+    /// 文件類型 (132-135)
+    #[wasm_bindgen]
+    pub fn get_file_type(&self) -> u8 {
+        self.file_type
+    }
+
+    /// 分頻比
+    #[wasm_bindgen]
+    pub fn get_division_ratio(&self) -> u8 {
+        self.division_ratio
+    }
+
+    /// 事件數
+    #[wasm_bindgen]
+    pub fn get_num_events(&self) -> usize {
+        self.intervals.len()
+    }
+
+    /// 錄音時長 (秒)
+    #[wasm_bindgen]
+    pub fn get_duration(&self) -> f64 {
+        self.duration()
+    }
+
+    /// 點的時間 (秒，Float64Array)
+    #[wasm_bindgen]
+    pub fn get_times(&self) -> Vec<f64> {
+        self.points().into_iter().map(|(t, _)| t).collect()
+    }
+
+    /// 點的頻率 (Hz，Float64Array，與 get_times 一一對應)
+    #[wasm_bindgen]
+    pub fn get_frequencies(&self) -> Vec<f64> {
+        self.points().into_iter().map(|(_, f)| f).collect()
+    }
+
+    /// 錄音開始時間 (ISO 8601，不帶時區)
+    #[wasm_bindgen]
+    pub fn get_timestamp_iso(&self) -> Option<String> {
+        self.timestamp.map(|t| t.to_iso8601())
+    }
+
+    /// 設置錄音開始時間
+    ///
+    /// # Arguments
+    /// * `text` - ISO 8601 日期時間；UTC 偏移被忽略 (文件保存本地時間)
+    #[wasm_bindgen]
+    pub fn set_timestamp_iso(&mut self, text: &str) -> bool {
+        match Timestamp::parse(text) {
+            Some(t) => {
+                self.timestamp = Some(Timestamp { utc_offset_minutes: None, ..t });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 文本頭字段 (tape、date、location、species、spec、note、note1、id_code、gps)
+    #[wasm_bindgen]
+    pub fn get_header_text(&mut self, field: &str) -> Result<String, JsError> {
+        Ok(self.text_field(field)?.clone())
+    }
+
+    /// 設置文本頭字段；寫出時截斷到字段長度
+    #[wasm_bindgen]
+    pub fn set_header_text(&mut self, field: &str, value: &str) -> Result<(), JsError> {
+        *self.text_field(field)? = value.to_string();
+        Ok(())
+    }
+
+    /// 序列化為 Anabat 132 型文件 (Uint8Array)
+    #[wasm_bindgen(js_name = to_bytes)]
+    pub fn to_bytes_js(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+}
+
+/// 讀取 Anabat 過零文件
+///
+/// # Arguments
+/// * `bytes` - 文件內容 (Uint8Array)
+///
+/// # Returns
+/// ZcRecording 對象；文件無效或類型不支持時拋出 Error
+#[wasm_bindgen]
+pub fn read_zc(bytes: &[u8]) -> Result<ZcRecording, JsError> {
+    Ok(ZcRecording::parse(bytes)?)
+}
+
+/// 從全頻譜音頻提取過零數據
+///
+/// # Arguments
+/// * `audio_data` - 輸入音頻 (Float32Array)
+/// * `sample_rate` - 採樣率 (Hz)
+/// * `division_ratio` - 分頻比 (1-255，典型值 8 或 16)
+/// * `sensitivity_db` - 靈敏度 (dB)：觸發閾值為滿量程以下該值，越大越靈敏
+/// * `highpass_hz` - 前置高通截止頻率 (Hz)，<= 0 表示不濾波
+///
+/// # Returns
+/// ZcRecording 對象 (可用 to_bytes() 寫出 Anabat 文件)；參數無效時拋出 Error
+#[wasm_bindgen]
+pub fn convert_to_zc(
+    audio_data: &[f32],
+    sample_rate: f32,
+    division_ratio: u32,
+    sensitivity_db: f32,
+    highpass_hz: f32,
+) -> Result<ZcRecording, JsError> {
+    Ok(ZcRecording::from_audio(audio_data, sample_rate, division_ratio, sensitivity_db, highpass_hz)?)
+}